@@ -7,14 +7,89 @@ struct VertexInput {
 // 頂点シェーダーの出力 / フラグメントシェーダーの入力
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) tex_coords: vec2<f32>,
+    // 画面上の位置（正規化デバイス座標）
+    @location(0) ndc: vec2<f32>,
 }
 
-// 頂点シェーダー
+// 表示パラメーター
+struct DisplayUniforms {
+    // 露出補正（EV）
+    exposure: f32,
+    // トーンマッピング（0: なし, 1: Reinhard, 2: ACES）
+    tone_mapping: u32,
+    // テクスチャの値がsRGBエンコードされたままかどうか（0: リニア, 1: sRGB）
+    srgb_encoded: u32,
+    // HDR画像かどうか（0: SDR, 1: HDR）
+    is_hdr: u32,
+    // 画面全体に対する画像の表示サイズの比率
+    image_scale: vec2<f32>,
+    // 背景の種類（0: 単色, 1: 市松模様）
+    background: u32,
+    // 市松模様の1マスの大きさ（ピクセル）
+    checker_size: f32,
+    // 背景色（リニア）
+    background_color: vec4<f32>,
+    // 切り替え前の画像の表示サイズの比率
+    previous_scale: vec2<f32>,
+    // 切り替え前の画像の値がsRGBエンコードされたままかどうか
+    previous_srgb_encoded: u32,
+    // 切り替え前の画像がHDR画像かどうか
+    previous_is_hdr: u32,
+    // 切り替え効果（0: なし, 1: クロスフェード, 2: スライド, 3: ズーム）
+    transition: u32,
+    // 切り替え効果の進み具合（0.0〜1.0）
+    progress: f32,
+    // スライドの向き（1.0: 右から, -1.0: 左から）
+    direction: f32,
+    // 比較表示（0: なし, 1: 左右に並べる, 2: 分割スライダー, 3: 差分）
+    compare_mode: u32,
+    // 比較する画像の表示サイズの比率
+    compare_scale: vec2<f32>,
+    // 比較する画像の値がsRGBエンコードされたままかどうか
+    compare_srgb_encoded: u32,
+    // 比較する画像がHDR画像かどうか
+    compare_is_hdr: u32,
+    // 拡大表示の移動量（表示領域に対する正規化デバイス座標）
+    view_offset: vec2<f32>,
+    // 分割スライダーの位置（ピクセル）
+    split_x: f32,
+    // 差分表示の見せ方（0: 絶対値, 1: 増幅, 2: しきい値）
+    difference_view: u32,
+    // 差分を増幅して表示するときの倍率
+    difference_amplification: f32,
+    // 変化したピクセルとみなす差分のしきい値
+    difference_threshold: f32,
+    // 色補正を行うかどうか（0: 行わない, 1: 行う）
+    adjust: u32,
+    // チャンネルの表示方法（0: 通常, 1: R, 2: G, 3: B, 4: A, 5: ストレートアルファ, 6: 乗算済みアルファ, 7: アルファマスク）
+    channel_view: u32,
+    // 明るさ（加算する値）
+    brightness: f32,
+    // コントラスト（中間の灰色を中心にした倍率）
+    contrast: f32,
+    // ガンマ
+    gamma: f32,
+    // 彩度（倍率）
+    saturation: f32,
+    // 拡大鏡の中心（ピクセル）
+    loupe_center: vec2<f32>,
+    // 拡大鏡の直径または一辺の長さ（ピクセル）
+    loupe_size: f32,
+    // 拡大鏡の倍率
+    loupe_magnification: f32,
+    // 画面全体の大きさ（ピクセル）
+    surface_size: vec2<f32>,
+    // 拡大鏡の形（0: 円形, 1: 四角形）
+    loupe_shape: u32,
+    // 拡大鏡の縁の太さ（ピクセル）
+    loupe_border: f32,
+}
+
+// 頂点シェーダー（画面全体を覆う四角形を描き、画像の配置はフラグメントシェーダーで計算する）
 @vertex
 fn vs_main(model: VertexInput) -> VertexOutput {
     var out: VertexOutput;
-    out.tex_coords = model.tex_coords;
+    out.ndc = model.position.xy;
     out.clip_position = vec4<f32>(model.position, 1.0);
     return out;
 }
@@ -25,8 +100,261 @@ var t_diffuse: texture_2d<f32>;
 @group(0) @binding(1)
 var s_diffuse: sampler;
 
+@group(1) @binding(0)
+var<uniform> display: DisplayUniforms;
+
+// 切り替え前の画像のテクスチャとサンプラー（切り替え中でない場合は現在の画像）
+@group(2) @binding(0)
+var t_previous: texture_2d<f32>;
+@group(2) @binding(1)
+var s_previous: sampler;
+
+// 比較する画像のテクスチャとサンプラー（比較表示中でない場合は現在の画像）
+@group(3) @binding(0)
+var t_compare: texture_2d<f32>;
+@group(3) @binding(1)
+var s_compare: sampler;
+
+// sRGBエンコードされた値をリニアに変換する
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let low = c / 12.92;
+    let high = pow((c + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, c <= vec3<f32>(0.04045));
+}
+
+// リニアな値をsRGBエンコードする
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3<f32>(0.0031308));
+}
+
+// ACESフィルミックトーンマッピング（Narkowiczによる近似）
+fn aces(c: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let cc = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((c * (a * c + b)) / (c * (cc * c + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// 明るさ・コントラスト → ガンマ → 彩度の順に色補正を行う（値はsRGBエンコードされた値で扱う）
+fn adjust_color(linear: vec3<f32>) -> vec3<f32> {
+    var c = linear_to_srgb(linear);
+    c = max((c - 0.5) * display.contrast + 0.5 + display.brightness, vec3<f32>(0.0));
+    c = pow(c, vec3<f32>(1.0 / display.gamma));
+    let luma = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    c = clamp(mix(vec3<f32>(luma), c, display.saturation), vec3<f32>(0.0), vec3<f32>(1.0));
+    return srgb_to_linear(c);
+}
+
+// 画像の透明部分の背景色を取得する
+fn background_at(position: vec2<f32>) -> vec3<f32> {
+    if (display.background == 1u) {
+        // 明るい灰色と灰色（sRGB の 204 と 153）の市松模様
+        let cell = vec2<i32>(floor(position / display.checker_size));
+        let light = ((cell.x + cell.y) & 1) == 0;
+        return select(vec3<f32>(0.319), vec3<f32>(0.604), light);
+    }
+    return display.background_color.rgb;
+}
+
+// 画面上の位置を、中央に配置した画像のテクスチャ座標に変換する
+fn image_uv(ndc: vec2<f32>, scale: vec2<f32>) -> vec2<f32> {
+    let p = ndc / scale;
+    return vec2<f32>(p.x * 0.5 + 0.5, 0.5 - p.y * 0.5);
+}
+
+// テクスチャ座標が画像の範囲内かどうかを判定する
+fn inside(uv: vec2<f32>) -> bool {
+    return all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+}
+
+// 露出補正・トーンマッピング・色補正を適用し、背景と合成する（画像の外側は背景色）
+fn shade(color: vec4<f32>, uv: vec2<f32>, srgb_encoded: u32, is_hdr: u32, position: vec2<f32>) -> vec3<f32> {
+    var c = color;
+    if (srgb_encoded == 1u) {
+        c = vec4<f32>(srgb_to_linear(c.rgb), c.a);
+    }
+
+    // 1つのチャンネルを表示する場合は、そのチャンネルの値を明るさとする不透明なグレースケールにする
+    let view = display.channel_view;
+    if (view >= 1u && view <= 3u) {
+        c = vec4<f32>(vec3<f32>(c[view - 1u]), 1.0);
+    } else if (view == 4u) {
+        // アルファは画像ファイルの値がそのまま明るさ（sRGB）になるようリニアに変換する
+        c = vec4<f32>(srgb_to_linear(vec3<f32>(c.a)), 1.0);
+    } else if (view == 5u) {
+        c = vec4<f32>(c.rgb, 1.0);
+    } else if (view == 6u) {
+        c = vec4<f32>(c.rgb * c.a, 1.0);
+    }
+
+    var rgb = c.rgb * exp2(display.exposure);
+    if (is_hdr == 1u) {
+        if (display.tone_mapping == 1u) {
+            rgb = rgb / (vec3<f32>(1.0) + rgb);
+        } else if (display.tone_mapping == 2u) {
+            rgb = aces(rgb);
+        }
+    }
+    rgb = clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+    if (display.adjust == 1u) {
+        rgb = adjust_color(rgb);
+    }
+    var composited = mix(background_at(position), rgb, c.a);
+    if (view == 7u) {
+        // 透明な部分ほど濃く赤いマスクを重ねる
+        composited = mix(composited, vec3<f32>(1.0, 0.0, 0.0), (1.0 - c.a) * 0.5);
+    }
+    return select(display.background_color.rgb, composited, inside(uv));
+}
+
+// テクスチャの値を画像ファイルに保存されている値（SDR画像はsRGBエンコード、HDR画像はリニア）に揃える
+fn stored_value(color: vec4<f32>, srgb_encoded: u32, is_hdr: u32) -> vec4<f32> {
+    if (srgb_encoded == 1u || is_hdr == 1u) {
+        return color;
+    }
+    // 8bit画像はsRGBテクスチャからリニアの値として読み出されるため、エンコードし直す
+    return vec4<f32>(linear_to_srgb(color.rgb), color.a);
+}
+
+// 2枚の画像の差分を表示する色を求める
+fn difference(current: vec4<f32>, current_uv: vec2<f32>, compare: vec4<f32>, compare_uv: vec2<f32>, position: vec2<f32>) -> vec3<f32> {
+    let in_current = inside(current_uv);
+    let in_compare = inside(compare_uv);
+    if (!in_current && !in_compare) {
+        return display.background_color.rgb;
+    }
+
+    // 片方の画像にしかない部分は最大の差分として扱う
+    var diff = vec4<f32>(1.0);
+    if (in_current && in_compare) {
+        diff = abs(stored_value(current, display.srgb_encoded, display.is_hdr)
+            - stored_value(compare, display.compare_srgb_encoded, display.compare_is_hdr));
+    }
+    let max_diff = max(max(diff.r, diff.g), max(diff.b, diff.a));
+
+    if (display.difference_view == 2u) {
+        // 変化したピクセルを赤で、それ以外を暗くした現在の画像のグレースケールで表示する
+        if (max_diff > display.difference_threshold) {
+            return vec3<f32>(1.0, 0.0, 0.0);
+        }
+        let base = shade(current, current_uv, display.srgb_encoded, display.is_hdr, position);
+        return vec3<f32>(dot(base, vec3<f32>(0.2126, 0.7152, 0.0722)) * 0.25);
+    }
+
+    // 透明度の差分も見えるよう、RGBの差分と大きい方を表示する
+    var rgb = max(diff.rgb, vec3<f32>(diff.a));
+    if (display.difference_view == 1u) {
+        rgb *= display.difference_amplification;
+    }
+    // 差分の値をそのまま画面の明るさ（sRGB）として表示する
+    return srgb_to_linear(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)));
+}
+
+// 画面上の位置に表示する色を求める
+//
+// ndc は画面全体に対する正規化デバイス座標、position は画面上の位置（ピクセル）。
+// nearest が true の場合はテクスチャを補間せず、最も近いピクセルの値をそのまま使う
+fn compose(screen_ndc: vec2<f32>, position: vec2<f32>, nearest: bool) -> vec3<f32> {
+    // 左右に並べる場合は画面の左半分と右半分をそれぞれ1つの表示領域として扱う
+    var ndc = screen_ndc;
+    var compare_side = false;
+    if (display.compare_mode == 1u) {
+        compare_side = ndc.x >= 0.0;
+        ndc.x = select(ndc.x * 2.0 + 1.0, ndc.x * 2.0 - 1.0, compare_side);
+    } else if (display.compare_mode == 2u) {
+        compare_side = position.x >= display.split_x;
+    }
+    // 拡大表示の移動量は比較する2枚に同じだけ適用する
+    ndc -= display.view_offset;
+
+    let t = display.progress;
+    var current_ndc = ndc;
+    var previous_ndc = ndc;
+    var current_scale = display.image_scale;
+    var previous_scale = display.previous_scale;
+    if (display.transition == 2u) {
+        // 新しい画像が画面の外から入り、前の画像が反対側へ出ていく
+        current_ndc.x -= 2.0 * (1.0 - t) * display.direction;
+        previous_ndc.x += 2.0 * t * display.direction;
+    } else if (display.transition == 3u) {
+        // 新しい画像が少し小さい状態から拡大し、前の画像は拡大しながら消える
+        current_scale *= mix(0.85, 1.0, t);
+        previous_scale *= mix(1.0, 1.15, t);
+    }
+
+    // テクスチャのサンプリングは均一な制御フローで行う
+    let current_uv = image_uv(current_ndc, current_scale);
+    let previous_uv = image_uv(previous_ndc, previous_scale);
+    let compare_uv = image_uv(ndc, display.compare_scale);
+    let current_color = textureSample(t_diffuse, s_diffuse, sample_uv(current_uv, textureDimensions(t_diffuse), nearest));
+    let previous_color = textureSample(t_previous, s_previous, sample_uv(previous_uv, textureDimensions(t_previous), nearest));
+    let compare_color = textureSample(t_compare, s_compare, sample_uv(compare_uv, textureDimensions(t_compare), nearest));
+
+    if (display.compare_mode == 3u) {
+        return difference(current_color, current_uv, compare_color, compare_uv, position);
+    }
+    if (display.compare_mode != 0u) {
+        // 分割スライダーの位置に境界線を引く
+        if (display.compare_mode == 2u && abs(position.x - display.split_x) < 1.0) {
+            return vec3<f32>(1.0, 1.0, 1.0);
+        }
+        if (compare_side) {
+            return shade(compare_color, compare_uv, display.compare_srgb_encoded, display.compare_is_hdr, position);
+        }
+    }
+
+    let current = shade(current_color, current_uv, display.srgb_encoded, display.is_hdr, position);
+    if (display.transition == 0u) {
+        return current;
+    }
+    let previous = shade(previous_color, previous_uv, display.previous_srgb_encoded, display.previous_is_hdr, position);
+
+    if (display.transition == 2u) {
+        // スライドでは2枚が重ならないため、位置に応じてどちらかを表示する
+        return select(select(current, previous, inside(previous_uv)), current, inside(current_uv));
+    }
+    return mix(previous, current, t);
+}
+
+// テクスチャをサンプリングする座標を求める（nearest が true の場合はピクセルの中心に揃える）
+fn sample_uv(uv: vec2<f32>, size: vec2<u32>, nearest: bool) -> vec2<f32> {
+    let texels = vec2<f32>(size);
+    return select(uv, (floor(uv * texels) + 0.5) / texels, nearest);
+}
+
 // フラグメントシェーダー
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    return vec4<f32>(compose(in.ndc, in.clip_position.xy, false), 1.0);
+}
+
+// 拡大鏡のフラグメントシェーダー（カーソルの周りを拡大して重ねる）
+@fragment
+fn fs_loupe(in: VertexOutput) -> @location(0) vec4<f32> {
+    let position = in.clip_position.xy;
+    let offset = position - display.loupe_center;
+    // 円形は中心からの距離、四角形は縦横の大きい方の距離で範囲を判定する
+    let distance = select(length(offset), max(abs(offset.x), abs(offset.y)), display.loupe_shape == 1u);
+    let radius = display.loupe_size * 0.5;
+
+    // 拡大鏡の中の位置を、拡大する前の画面上の位置に戻して色を求める
+    let source = display.loupe_center + offset / display.loupe_magnification;
+    let source_ndc = vec2<f32>(source.x / display.surface_size.x * 2.0 - 1.0, 1.0 - source.y / display.surface_size.y * 2.0);
+    let color = compose(source_ndc, source, true);
+
+    if (distance > radius) {
+        discard;
+    }
+    // 縁は明るい画像でも暗い画像でも見えるよう、外側を黒、内側を白にする
+    if (distance > radius - display.loupe_border * 0.5) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    if (distance > radius - display.loupe_border) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    return vec4<f32>(color, 1.0);
 }
@@ -0,0 +1,37 @@
+// オーバーレイ（文字・矩形・サムネイル）描画用シェーダー
+
+// 頂点シェーダーの入力
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+// 頂点シェーダーの出力 / フラグメントシェーダーの入力
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+// 頂点シェーダー
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.color = model.color;
+    out.clip_position = vec4<f32>(model.position, 0.0, 1.0);
+    return out;
+}
+
+// テクスチャ（グリフアトラスまたはサムネイル）とサンプラー
+@group(0) @binding(0)
+var t_overlay: texture_2d<f32>;
+@group(0) @binding(1)
+var s_overlay: sampler;
+
+// フラグメントシェーダー
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color * textureSample(t_overlay, s_overlay, in.tex_coords);
+}
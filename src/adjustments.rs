@@ -0,0 +1,168 @@
+use image::{DynamicImage, ImageBuffer, Rgba};
+use crate::color_management::{linear_to_srgb, srgb_to_linear};
+use crate::config::{Config, ToneMapping};
+
+/// 表示の色補正（明るさ・コントラスト・ガンマ・彩度）
+///
+/// 画像そのものは変更せず、表示するときにシェーダーで適用する。
+/// 補正は露出補正とトーンマッピングの後、sRGBエンコードされた値に対して
+/// 明るさ・コントラスト → ガンマ → 彩度の順に行う
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorAdjustments {
+    /// 明るさ（加算する値、0.0で補正なし）
+    pub brightness: f32,
+    /// コントラスト（中間の灰色を中心にした倍率、1.0で補正なし）
+    pub contrast: f32,
+    /// ガンマ（1.0より大きいと中間調が明るくなる、1.0で補正なし）
+    pub gamma: f32,
+    /// 彩度（倍率、0.0でグレースケール、1.0で補正なし）
+    pub saturation: f32,
+}
+
+/// 色補正の項目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjustment {
+    /// 明るさ
+    Brightness,
+    /// コントラスト
+    Contrast,
+    /// ガンマ
+    Gamma,
+    /// 彩度
+    Saturation,
+}
+
+impl ColorAdjustments {
+    /// 設定ファイルの値から色補正を作成する
+    ///
+    /// # Arguments
+    /// * `config` - アプリケーション設定
+    ///
+    /// # Returns
+    /// * `ColorAdjustments` - 色補正
+    pub fn from_config(config: &Config) -> Self {
+        ColorAdjustments {
+            brightness: config.brightness,
+            contrast: config.contrast,
+            gamma: config.gamma,
+            saturation: config.saturation,
+        }
+        .clamped()
+    }
+
+    /// 補正なしの状態かどうかを判定する
+    ///
+    /// # Returns
+    /// * `bool` - どの項目も補正しない場合は true
+    pub fn is_identity(&self) -> bool {
+        self.brightness == 0.0 && self.contrast == 1.0 && self.gamma == 1.0 && self.saturation == 1.0
+    }
+
+    /// 項目の値を1段階ずつ変更する
+    ///
+    /// # Arguments
+    /// * `adjustment` - 変更する項目
+    /// * `steps` - 変更する段階（負の値で小さくする）
+    pub fn step(&mut self, adjustment: Adjustment, steps: f32) {
+        match adjustment {
+            Adjustment::Brightness => self.brightness += 0.05 * steps,
+            Adjustment::Contrast => self.contrast += 0.1 * steps,
+            Adjustment::Gamma => self.gamma += 0.1 * steps,
+            Adjustment::Saturation => self.saturation += 0.1 * steps,
+        }
+        // 段階を重ねても補正なしの値に戻れるよう、刻みに丸める
+        self.brightness = (self.brightness * 100.0).round() / 100.0;
+        self.contrast = (self.contrast * 100.0).round() / 100.0;
+        self.gamma = (self.gamma * 100.0).round() / 100.0;
+        self.saturation = (self.saturation * 100.0).round() / 100.0;
+        *self = self.clamped();
+    }
+
+    /// 各項目を有効な範囲に収める
+    fn clamped(self) -> Self {
+        ColorAdjustments {
+            brightness: self.brightness.clamp(-1.0, 1.0),
+            contrast: self.contrast.clamp(0.0, 4.0),
+            gamma: self.gamma.clamp(0.1, 5.0),
+            saturation: self.saturation.clamp(0.0, 4.0),
+        }
+    }
+
+    /// OSDとログに表示する要約を作成する
+    ///
+    /// # Returns
+    /// * `String` - 各項目の値
+    pub fn summary(&self) -> String {
+        format!(
+            "明るさ {:+.2}  コントラスト {:.2}  ガンマ {:.2}  彩度 {:.2}",
+            self.brightness, self.contrast, self.gamma, self.saturation
+        )
+    }
+
+    /// sRGBエンコードされた色に補正を適用する（シェーダーと同じ計算）
+    ///
+    /// # Arguments
+    /// * `rgb` - sRGBエンコードされた色（0.0〜1.0）
+    ///
+    /// # Returns
+    /// * `[f32; 3]` - 補正後の色（0.0〜1.0）
+    fn apply_encoded(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let rgb = rgb.map(|c| (((c - 0.5) * self.contrast + 0.5 + self.brightness).max(0.0)).powf(1.0 / self.gamma));
+        let luma = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+        rgb.map(|c| (luma + (c - luma) * self.saturation).clamp(0.0, 1.0))
+    }
+
+    /// 画面に表示しているのと同じ見た目の画像を作成する（書き出し用）
+    ///
+    /// 露出補正とトーンマッピング（HDR画像のみ）を適用してsRGBエンコードし、
+    /// 色補正を適用する。8bit画像は8bit、それ以外は16bitの画像になる
+    ///
+    /// # Arguments
+    /// * `image` - 読み込んだ画像
+    /// * `exposure` - 露出補正（EV）
+    /// * `tone_mapping` - HDR画像のトーンマッピング
+    /// * `apply_adjustments` - 色補正を適用するかどうか
+    ///
+    /// # Returns
+    /// * `DynamicImage` - 表示と同じ見た目の画像
+    pub fn render(&self, image: &DynamicImage, exposure: f32, tone_mapping: ToneMapping, apply_adjustments: bool) -> DynamicImage {
+        let is_hdr = matches!(image, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_));
+        let is_8bit = image.color().bytes_per_pixel() == image.color().channel_count();
+        let gain = exposure.exp2();
+
+        let mut rgba = image.to_rgba32f();
+        for pixel in rgba.pixels_mut() {
+            let [r, g, b, a] = pixel.0;
+            // シェーダーと同じく、リニアに戻して露出補正とトーンマッピングを行う
+            let linear = if is_hdr { [r, g, b] } else { [r, g, b].map(srgb_to_linear) };
+            let mut rgb = linear.map(|c| c * gain);
+            if is_hdr {
+                rgb = match tone_mapping {
+                    ToneMapping::None => rgb,
+                    ToneMapping::Reinhard => rgb.map(|c| c / (1.0 + c)),
+                    ToneMapping::Aces => rgb.map(aces),
+                };
+            }
+            let mut encoded = rgb.map(|c| linear_to_srgb(c.clamp(0.0, 1.0)));
+            if apply_adjustments {
+                encoded = self.apply_encoded(encoded);
+            }
+            pixel.0 = [encoded[0], encoded[1], encoded[2], a.clamp(0.0, 1.0)];
+        }
+
+        if is_8bit {
+            let buffer: ImageBuffer<Rgba<u8>, Vec<u8>> =
+                ImageBuffer::from_fn(rgba.width(), rgba.height(), |x, y| Rgba(rgba.get_pixel(x, y).0.map(|c| (c * 255.0).round() as u8)));
+            DynamicImage::ImageRgba8(buffer)
+        } else {
+            let buffer: ImageBuffer<Rgba<u16>, Vec<u16>> =
+                ImageBuffer::from_fn(rgba.width(), rgba.height(), |x, y| Rgba(rgba.get_pixel(x, y).0.map(|c| (c * 65535.0).round() as u16)));
+            DynamicImage::ImageRgba16(buffer)
+        }
+    }
+}
+
+/// ACESフィルミックトーンマッピング（Narkowiczによる近似）
+fn aces(c: f32) -> f32 {
+    ((c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14)).clamp(0.0, 1.0)
+}
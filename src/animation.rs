@@ -0,0 +1,177 @@
+use anyhow::Result;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, ImageDecoder, RgbaImage};
+use log::{debug, warn};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 展開したフレームの合計サイズの上限（バイト）
+const MAX_FRAME_BYTES: usize = 1024 * 1024 * 1024;
+/// これより短い表示時間のフレームは、ブラウザと同様に既定の表示時間で表示する
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+/// 表示時間が短すぎるフレームの既定の表示時間
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// アニメーションの1フレーム
+pub struct AnimationFrame {
+    /// 画像全体に合成済みのフレーム
+    pub image: RgbaImage,
+    /// 表示時間
+    pub delay: Duration,
+}
+
+/// 展開したアニメーション画像
+pub struct DecodedAnimation {
+    /// フレーム
+    pub frames: Vec<AnimationFrame>,
+    /// 埋め込みICCプロファイル
+    pub icc_profile: Option<Vec<u8>>,
+}
+
+/// アニメーション画像の再生状態
+pub struct Animation {
+    /// フレーム
+    frames: Vec<AnimationFrame>,
+    /// 表示中のフレーム
+    current: usize,
+    /// 表示中のフレームを表示し始めた時刻
+    frame_started: Instant,
+    /// 最後まで再生した回数
+    loops: u32,
+}
+
+impl Animation {
+    /// 新しい Animation インスタンスを作成する
+    ///
+    /// # Arguments
+    /// * `frames` - フレーム（2つ以上）
+    ///
+    /// # Returns
+    /// * `Animation` - 先頭のフレームから再生するアニメーション
+    pub fn new(frames: Vec<AnimationFrame>) -> Self {
+        Animation {
+            frames,
+            current: 0,
+            frame_started: Instant::now(),
+            loops: 0,
+        }
+    }
+
+    /// 表示中のフレームの画像を取得する
+    ///
+    /// # Returns
+    /// * `&RgbaImage` - フレームの画像
+    pub fn current_frame(&self) -> &RgbaImage {
+        &self.frames[self.current].image
+    }
+
+    /// 1回分の再生時間を取得する
+    ///
+    /// # Returns
+    /// * `Duration` - 全フレームの表示時間の合計
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.delay).sum()
+    }
+
+    /// 最後まで1回以上再生したかどうかを取得する
+    ///
+    /// # Returns
+    /// * `bool` - 1回以上再生した場合は true
+    pub fn has_played_once(&self) -> bool {
+        self.loops > 0
+    }
+
+    /// 経過時間に応じて表示するフレームを進める
+    ///
+    /// # Returns
+    /// * `bool` - 表示するフレームが変わった場合は true
+    pub fn update(&mut self) -> bool {
+        let mut elapsed = self.frame_started.elapsed();
+        // 長時間描画されなかった場合は追いつこうとせず、次のフレームから再開する
+        if elapsed > self.duration() {
+            self.frame_started = Instant::now();
+            elapsed = self.frames[self.current].delay;
+        }
+
+        let mut changed = false;
+        while elapsed >= self.frames[self.current].delay {
+            let delay = self.frames[self.current].delay;
+            elapsed -= delay;
+            self.frame_started += delay;
+            self.current = (self.current + 1) % self.frames.len();
+            if self.current == 0 {
+                self.loops += 1;
+            }
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// アニメーション画像（GIF、APNG、アニメーションWebP）のフレームを展開する
+///
+/// # Arguments
+/// * `path` - 画像ファイルのパス
+///
+/// # Returns
+/// * `Result<Option<DecodedAnimation>>` - 展開したアニメーション（アニメーションでない場合は None）
+pub fn decode_frames(path: &Path) -> Result<Option<DecodedAnimation>> {
+    let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+    let reader = || -> Result<BufReader<File>> { Ok(BufReader::new(File::open(path)?)) };
+
+    let (frames, icc_profile) = match extension.as_deref() {
+        Some("gif") => {
+            let mut decoder = GifDecoder::new(reader()?)?;
+            let icc_profile = decoder.icc_profile().ok().flatten();
+            (collect_frames(decoder.into_frames())?, icc_profile)
+        }
+        Some("png") => {
+            let mut decoder = PngDecoder::new(reader()?)?;
+            if !decoder.is_apng()? {
+                return Ok(None);
+            }
+            let icc_profile = decoder.icc_profile().ok().flatten();
+            (collect_frames(decoder.apng()?.into_frames())?, icc_profile)
+        }
+        Some("webp") => {
+            let mut decoder = WebPDecoder::new(reader()?)?;
+            if !decoder.has_animation() {
+                return Ok(None);
+            }
+            let icc_profile = decoder.icc_profile().ok().flatten();
+            (collect_frames(decoder.into_frames())?, icc_profile)
+        }
+        _ => return Ok(None),
+    };
+
+    if frames.len() < 2 {
+        return Ok(None);
+    }
+    debug!("アニメーションを展開しました: {}フレーム {:?}", frames.len(), path);
+    Ok(Some(DecodedAnimation { frames, icc_profile }))
+}
+
+/// フレームを展開する（合計サイズが上限を超えた場合はそこで打ち切る）
+fn collect_frames(frames: image::Frames) -> Result<Vec<AnimationFrame>> {
+    let mut collected = Vec::new();
+    let mut total_bytes = 0;
+    for frame in frames {
+        let frame = frame?;
+        let delay = Duration::from(frame.delay());
+        let image = frame.into_buffer();
+        total_bytes += image.as_raw().len();
+        if total_bytes > MAX_FRAME_BYTES {
+            warn!("アニメーションが大きすぎるため、{}フレーム目以降を省略します", collected.len() + 1);
+            break;
+        }
+        collected.push(AnimationFrame {
+            image,
+            delay: if delay < MIN_FRAME_DELAY { DEFAULT_FRAME_DELAY } else { delay },
+        });
+    }
+    Ok(collected)
+}
@@ -0,0 +1,176 @@
+use anyhow::{Result, anyhow, bail};
+use image::{DynamicImage, ImageBuffer};
+use log::{debug, info, warn};
+use moxcms::{ColorProfile, DataColorSpace, Layout, TransformExecutor, TransformOptions};
+use std::path::Path;
+
+/// ICCプロファイルによるカラーマネジメント
+///
+/// 画像に埋め込まれたプロファイルから出力先プロファイル（既定はsRGB）へ変換する
+pub struct ColorManager {
+    /// 出力先のカラープロファイル
+    target: ColorProfile,
+}
+
+impl ColorManager {
+    /// 新しい ColorManager インスタンスを作成する
+    ///
+    /// 出力先プロファイルが読み込めない場合はsRGBを使用する
+    ///
+    /// # Arguments
+    /// * `target_profile_path` - 出力先ICCプロファイルのパス（None の場合はsRGB）
+    ///
+    /// # Returns
+    /// * `ColorManager` - カラーマネージャー
+    pub fn new(target_profile_path: Option<&Path>) -> Self {
+        let target = match target_profile_path {
+            Some(path) => match Self::load_profile(path) {
+                Ok(profile) => {
+                    info!("出力先ICCプロファイルを読み込みました: {:?}", path);
+                    profile
+                }
+                Err(e) => {
+                    warn!("出力先ICCプロファイルの読み込みに失敗したため、sRGBを使用します: {:?}", e);
+                    ColorProfile::new_srgb()
+                }
+            },
+            None => ColorProfile::new_srgb(),
+        };
+        ColorManager { target }
+    }
+
+    /// ICCプロファイルファイルを読み込む
+    ///
+    /// # Arguments
+    /// * `path` - ICCプロファイルのパス
+    ///
+    /// # Returns
+    /// * `Result<ColorProfile>` - カラープロファイル
+    fn load_profile(path: &Path) -> Result<ColorProfile> {
+        let bytes = std::fs::read(path)?;
+        ColorProfile::new_from_slice(&bytes).map_err(|e| anyhow!("ICCプロファイルの解析に失敗: {:?}", e))
+    }
+
+    /// 色変換できる画像かどうかを判定する
+    ///
+    /// 浮動小数点画像はリニアのHDR値として転送するため、出力先のトーンカーブでエンコードして
+    /// 0.0〜1.0 に切り詰める変換は行わない
+    ///
+    /// # Arguments
+    /// * `image` - 判定する画像
+    ///
+    /// # Returns
+    /// * `bool` - 変換できる場合は true
+    pub fn supports(image: &DynamicImage) -> bool {
+        !matches!(image, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_))
+    }
+
+    /// 埋め込みプロファイルから出力先プロファイルへ画像を変換する
+    ///
+    /// # Arguments
+    /// * `image` - 変換する画像
+    /// * `icc_profile` - 画像に埋め込まれたICCプロファイル
+    ///
+    /// # Returns
+    /// * `Result<DynamicImage>` - 変換後の画像（8bit/16bitの区別は維持する）
+    pub fn convert(&self, image: &DynamicImage, icc_profile: &[u8]) -> Result<DynamicImage> {
+        let source = ColorProfile::new_from_slice(icc_profile)
+            .map_err(|e| anyhow!("埋め込みICCプロファイルの解析に失敗: {:?}", e))?;
+
+        let is_gray = match source.color_space {
+            DataColorSpace::Rgb => false,
+            DataColorSpace::Gray => true,
+            other => bail!("未対応のプロファイル色空間です: {:?}", other),
+        };
+        // グレースケールはアルファを分離してGray→RGBで変換する
+        let (src_layout, dst_layout) = if is_gray { (Layout::Gray, Layout::Rgb) } else { (Layout::Rgba, Layout::Rgba) };
+        let options = TransformOptions::default();
+        let (width, height) = (image.width(), image.height());
+        let cms_error = |e| anyhow!("色変換に失敗: {:?}", e);
+
+        let converted = match image {
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+                bail!("浮動小数点画像の色変換には対応していません");
+            }
+            DynamicImage::ImageLuma16(_)
+            | DynamicImage::ImageLumaA16(_)
+            | DynamicImage::ImageRgb16(_)
+            | DynamicImage::ImageRgba16(_) => {
+                let transform = source
+                    .create_transform_16bit(src_layout, &self.target, dst_layout, options)
+                    .map_err(cms_error)?;
+                let src = if is_gray { image.to_luma_alpha16().into_raw() } else { image.to_rgba16().into_raw() };
+                let dst = apply_transform(transform.as_ref(), &src, is_gray)?;
+                DynamicImage::ImageRgba16(ImageBuffer::from_raw(width, height, dst).ok_or_else(buffer_error)?)
+            }
+            _ => {
+                let transform = source
+                    .create_transform_8bit(src_layout, &self.target, dst_layout, options)
+                    .map_err(cms_error)?;
+                let src = if is_gray { image.to_luma_alpha8().into_raw() } else { image.to_rgba8().into_raw() };
+                let dst = apply_transform(transform.as_ref(), &src, is_gray)?;
+                DynamicImage::ImageRgba8(ImageBuffer::from_raw(width, height, dst).ok_or_else(buffer_error)?)
+            }
+        };
+
+        debug!("ICCプロファイルで色変換しました: {:?}", source.color_space);
+        Ok(converted)
+    }
+}
+
+/// 色変換を実行し、RGBAのサンプル列を返す
+///
+/// # Arguments
+/// * `transform` - 色変換
+/// * `src` - RGBA、またはグレースケールの場合は輝度とアルファのサンプル列
+/// * `is_gray` - グレースケールかどうか
+///
+/// # Returns
+/// * `Result<Vec<T>>` - 変換後のRGBAサンプル列
+fn apply_transform<T: Copy + Default>(
+    transform: &dyn TransformExecutor<T>,
+    src: &[T],
+    is_gray: bool,
+) -> Result<Vec<T>> {
+    let cms_error = |e| anyhow!("色変換に失敗: {:?}", e);
+    if !is_gray {
+        let mut dst = vec![T::default(); src.len()];
+        transform.transform(src, &mut dst).map_err(cms_error)?;
+        return Ok(dst);
+    }
+
+    let pixel_count = src.len() / 2;
+    let luma: Vec<T> = src.chunks_exact(2).map(|p| p[0]).collect();
+    let mut rgb = vec![T::default(); pixel_count * 3];
+    transform.transform(&luma, &mut rgb).map_err(cms_error)?;
+
+    let mut rgba = Vec::with_capacity(pixel_count * 4);
+    for (color, gray_alpha) in rgb.chunks_exact(3).zip(src.chunks_exact(2)) {
+        rgba.extend_from_slice(color);
+        rgba.push(gray_alpha[1]);
+    }
+    Ok(rgba)
+}
+
+/// sRGBエンコードされた値をリニアに変換する
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// リニアな値をsRGBエンコードする
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// 変換後の画像バッファ作成エラー
+fn buffer_error() -> anyhow::Error {
+    anyhow!("色変換後の画像バッファ作成に失敗")
+}
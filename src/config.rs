@@ -1,13 +1,14 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use log::{info, warn};
 
 /// ソートアルゴリズムの種類
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SortAlgorithm {
     /// ファイル名でソート
+    #[default]
     FileName,
     /// ファイル名で自然順ソート（数字をゼロサプレスして比較）
     FileNameNatural,
@@ -17,19 +18,335 @@ pub enum SortAlgorithm {
     ModifiedTime,
 }
 
-impl Default for SortAlgorithm {
-    fn default() -> Self {
-        SortAlgorithm::FileName
+impl SortAlgorithm {
+    /// 次のソートアルゴリズムを取得する
+    /// 
+    /// # Returns
+    /// * `SortAlgorithm` - 切り替え後のソートアルゴリズム
+    pub fn next(self) -> Self {
+        match self {
+            SortAlgorithm::FileName => SortAlgorithm::FileNameNatural,
+            SortAlgorithm::FileNameNatural => SortAlgorithm::CreatedTime,
+            SortAlgorithm::CreatedTime => SortAlgorithm::ModifiedTime,
+            SortAlgorithm::ModifiedTime => SortAlgorithm::FileName,
+        }
+    }
+}
+
+/// 画像の表示サイズの決め方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FitMode {
+    /// ウィンドウの大きさを画像に合わせる
+    #[default]
+    Window,
+    /// ウィンドウの大きさを変えず、画像を拡大・縮小して収める
+    Fit,
+    /// ウィンドウの大きさを変えず、ウィンドウより大きい画像だけ縮小して収める
+    ShrinkOnly,
+    /// ウィンドウの大きさを変えず、等倍で表示する
+    Original,
+}
+
+impl FitMode {
+    /// 次の表示サイズの決め方を取得する
+    /// 
+    /// # Returns
+    /// * `FitMode` - 切り替え後の表示サイズの決め方
+    pub fn next(self) -> Self {
+        match self {
+            FitMode::Window => FitMode::Fit,
+            FitMode::Fit => FitMode::ShrinkOnly,
+            FitMode::ShrinkOnly => FitMode::Original,
+            FitMode::Original => FitMode::Window,
+        }
+    }
+}
+
+/// 画像の背景（透明部分と余白）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Background {
+    /// 黒
+    #[default]
+    Black,
+    /// 灰色
+    Gray,
+    /// 白
+    White,
+    /// 市松模様（余白は暗い灰色）
+    Checkerboard,
+}
+
+impl Background {
+    /// 次の背景を取得する
+    /// 
+    /// # Returns
+    /// * `Background` - 切り替え後の背景
+    pub fn next(self) -> Self {
+        match self {
+            Background::Black => Background::Gray,
+            Background::Gray => Background::White,
+            Background::White => Background::Checkerboard,
+            Background::Checkerboard => Background::Black,
+        }
     }
 }
 
+/// HDR画像をSDRサーフェスに表示する際のトーンマッピング
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToneMapping {
+    /// トーンマッピングなし（1.0を超える値はクリップ）
+    None,
+    /// Reinhard
+    Reinhard,
+    /// ACES フィルミック
+    #[default]
+    Aces,
+}
+
+impl ToneMapping {
+    /// 次のトーンマッピングを取得する
+    /// 
+    /// # Returns
+    /// * `ToneMapping` - 切り替え後のトーンマッピング
+    pub fn next(self) -> Self {
+        match self {
+            ToneMapping::None => ToneMapping::Reinhard,
+            ToneMapping::Reinhard => ToneMapping::Aces,
+            ToneMapping::Aces => ToneMapping::None,
+        }
+    }
+}
+
+/// OSD（画面上の情報表示）の表示位置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OsdPosition {
+    /// 左上
+    TopLeft,
+    /// 右上
+    TopRight,
+    /// 左下
+    #[default]
+    BottomLeft,
+    /// 右下
+    BottomRight,
+}
+
+/// フィルムストリップの表示位置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilmstripPosition {
+    /// 下端
+    #[default]
+    Bottom,
+    /// 上端
+    Top,
+    /// 左端
+    Left,
+    /// 右端
+    Right,
+}
+
+/// 画像を切り替えるときの効果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Transition {
+    /// 効果なし（すぐに切り替える）
+    #[default]
+    None,
+    /// クロスフェード
+    Crossfade,
+    /// スライド
+    Slide,
+    /// ズームしながらクロスフェード
+    Zoom,
+}
+
+/// 画像に重ねて表示する分析パネル
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scope {
+    /// 表示しない
+    #[default]
+    None,
+    /// R・G・B・輝度のヒストグラム
+    Histogram,
+    /// RGBの波形モニター
+    Waveform,
+}
+
+/// 画像のチャンネルの表示方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChannelView {
+    /// 通常の表示（背景とアルファ合成する）
+    #[default]
+    Color,
+    /// Rチャンネルをグレースケールで表示する
+    Red,
+    /// Gチャンネルをグレースケールで表示する
+    Green,
+    /// Bチャンネルをグレースケールで表示する
+    Blue,
+    /// アルファチャンネルをグレースケールで表示する
+    Alpha,
+    /// アルファを無視してRGBをそのまま表示する（ストレートアルファ）
+    Straight,
+    /// RGBにアルファを乗算して黒の上に表示する（乗算済みアルファ）
+    Premultiplied,
+    /// 通常の表示に、透明な部分を赤いマスクで重ねる
+    AlphaMask,
+}
+
+/// 拡大鏡の形
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoupeShape {
+    /// 円形
+    #[default]
+    Circle,
+    /// 四角形
+    Square,
+}
+
+/// 2枚の画像の比較表示の方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareMode {
+    /// 左右に並べる
+    #[default]
+    SideBySide,
+    /// 同じ位置に重ね、分割スライダーの左右で表示する画像を分ける
+    Split,
+    /// 2枚のピクセルの差分を表示する
+    Difference,
+}
+
+/// 差分表示の見せ方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DifferenceView {
+    /// 差分の絶対値
+    #[default]
+    Absolute,
+    /// 差分の絶対値を増幅したもの
+    Amplified,
+    /// しきい値を超えて変化したピクセルを強調したもの
+    Threshold,
+}
+
+/// スライドショーの表示順
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SlideshowOrder {
+    /// 画像リストの順
+    #[default]
+    Sequential,
+    /// ランダム（一巡するまで同じ画像は表示しない）
+    Random,
+}
+
 /// アプリケーション設定
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
-    /// 最小ウィンドウサイズ（幅、高さ）
+    /// 最小ウィンドウサイズ（幅、高さ、論理ピクセル）
     pub min_window_size: (u32, u32),
     /// 画像ファイルのソートアルゴリズム
     pub sort_algorithm: SortAlgorithm,
+    /// 露出補正の初期値（EV）
+    pub exposure: f32,
+    /// HDR画像のトーンマッピング
+    pub tone_mapping: ToneMapping,
+    /// 埋め込みICCプロファイルによる色変換を行うかどうか
+    pub color_management: bool,
+    /// 出力先ICCプロファイルのパス（未指定の場合はsRGB）
+    pub target_icc_profile: Option<PathBuf>,
+    /// オーバーレイの文字サイズ（論理ピクセル）
+    pub overlay_font_size: f32,
+    /// オーバーレイで使用する日本語フォントのパス（未指定の場合はOSのフォント設定から探す）
+    pub overlay_font_path: Option<PathBuf>,
+    /// OSDを表示するかどうか
+    pub show_osd: bool,
+    /// OSDの表示位置
+    pub osd_position: OsdPosition,
+    /// OSDの表示内容のテンプレート
+    pub osd_template: String,
+    /// OSDを自動的に隠すまでの秒数（0の場合は常に表示）
+    pub osd_auto_hide_secs: f32,
+    /// サムネイル一覧のサムネイルの大きさ（論理ピクセル）
+    pub thumbnail_size: u32,
+    /// サムネイルをディスクにキャッシュするかどうか
+    pub thumbnail_cache: bool,
+    /// サムネイルキャッシュの保存先（未指定の場合はOS標準のキャッシュディレクトリ）
+    pub thumbnail_cache_dir: Option<PathBuf>,
+    /// サムネイルキャッシュの容量の上限（MB）
+    pub thumbnail_cache_max_mb: u64,
+    /// フィルムストリップを表示するかどうか
+    pub show_filmstrip: bool,
+    /// フィルムストリップの表示位置
+    pub filmstrip_position: FilmstripPosition,
+    /// フィルムストリップのサムネイルの大きさ（論理ピクセル）
+    pub filmstrip_thumbnail_size: u32,
+    /// 操作がなくなってからフィルムストリップを隠すまでの秒数（0の場合は常に表示）
+    pub filmstrip_auto_hide_secs: f32,
+    /// 起動時に全画面表示にするかどうか
+    pub fullscreen: bool,
+    /// 全画面表示に排他モード（解像度を切り替える）を使うかどうか（false の場合はボーダーレス）
+    pub fullscreen_exclusive: bool,
+    /// 画像の表示サイズの決め方
+    pub fit_mode: FitMode,
+    /// 画像の背景
+    pub background: Background,
+    /// ウィンドウの位置・サイズと表示設定を状態ファイルに保存し、次回起動時に復元するかどうか
+    pub remember_window_state: bool,
+    /// 起動時にスライドショーを開始するかどうか
+    pub slideshow: bool,
+    /// スライドショーの1枚あたりの表示時間（秒）
+    pub slideshow_interval_secs: f32,
+    /// スライドショーの表示順
+    pub slideshow_order: SlideshowOrder,
+    /// スライドショーで最後の画像の後に最初から繰り返すかどうか（false の場合は停止する）
+    pub slideshow_loop: bool,
+    /// 画像を切り替えるときの効果
+    pub transition: Transition,
+    /// 切り替え効果の時間（秒）
+    pub transition_duration_secs: f32,
+    /// 起動時に見開き表示（2ページを並べて表示）にするかどうか
+    pub spread: bool,
+    /// 見開き表示で右から左へ読む（右綴じ）かどうか
+    pub right_to_left: bool,
+    /// 見開き表示で表紙（先頭のページ）を1ページで表示するかどうか
+    pub spread_cover_alone: bool,
+    /// 起動時に縦読み（Webtoon）表示にするかどうか
+    pub webtoon: bool,
+    /// 比較表示を始めたときの表示方法
+    pub compare_mode: CompareMode,
+    /// 差分表示の見せ方
+    pub difference_view: DifferenceView,
+    /// 差分を増幅して表示するときの倍率
+    pub difference_amplification: f32,
+    /// 変化したピクセルとみなす差分のしきい値（0.0〜1.0、8bit画像の1階調は約0.004）
+    pub difference_threshold: f32,
+    /// 画像に重ねて表示する分析パネル（ヒストグラム・波形モニター）
+    pub scope: Scope,
+    /// ヒストグラムの縦軸を対数にするかどうか
+    pub scope_log_scale: bool,
+    /// 表示の明るさ補正（加算する値、-1.0〜1.0）
+    pub brightness: f32,
+    /// 表示のコントラスト（倍率、0.0〜4.0）
+    pub contrast: f32,
+    /// 表示のガンマ（0.1〜5.0）
+    pub gamma: f32,
+    /// 表示の彩度（倍率、0.0〜4.0）
+    pub saturation: f32,
+    /// 画像を切り替えても露出補正と色補正を保つかどうか（false の場合は画像ごとに設定ファイルの値に戻す）
+    pub sticky_adjustments: bool,
+    /// 画像を書き出すときに色補正を適用するかどうか
+    pub export_adjustments: bool,
+    /// 画像とメタデータの書き出し先（未指定の場合は元の画像と同じディレクトリの export フォルダ）
+    pub export_dir: Option<PathBuf>,
+    /// 起動時のチャンネルの表示方法
+    pub channel_view: ChannelView,
+    /// 拡大鏡の倍率
+    pub loupe_magnification: f32,
+    /// 拡大鏡の直径または一辺の長さ（論理ピクセル）
+    pub loupe_size: f32,
+    /// 拡大鏡の形
+    pub loupe_shape: LoupeShape,
+    /// 画像が表示領域に収まらないときにミニマップを表示するかどうか
+    pub show_minimap: bool,
 }
 
 impl Default for Config {
@@ -37,6 +354,57 @@ impl Default for Config {
         Config {
             min_window_size: (800, 600),
             sort_algorithm: SortAlgorithm::FileName,
+            exposure: 0.0,
+            tone_mapping: ToneMapping::Aces,
+            color_management: true,
+            target_icc_profile: None,
+            overlay_font_size: 16.0,
+            overlay_font_path: None,
+            show_osd: true,
+            osd_position: OsdPosition::BottomLeft,
+            osd_template: "{name}  [{index}/{total}]  {width}x{height}  {size}  {zoom}  {format}".to_string(),
+            osd_auto_hide_secs: 3.0,
+            thumbnail_size: 192,
+            thumbnail_cache: false,
+            thumbnail_cache_dir: None,
+            thumbnail_cache_max_mb: 512,
+            show_filmstrip: false,
+            filmstrip_position: FilmstripPosition::Bottom,
+            filmstrip_thumbnail_size: 80,
+            filmstrip_auto_hide_secs: 2.0,
+            fullscreen: false,
+            fullscreen_exclusive: false,
+            fit_mode: FitMode::Window,
+            background: Background::Black,
+            remember_window_state: false,
+            slideshow: false,
+            slideshow_interval_secs: 5.0,
+            slideshow_order: SlideshowOrder::Sequential,
+            slideshow_loop: true,
+            transition: Transition::None,
+            transition_duration_secs: 0.4,
+            spread: false,
+            right_to_left: false,
+            spread_cover_alone: true,
+            webtoon: false,
+            compare_mode: CompareMode::SideBySide,
+            difference_view: DifferenceView::Absolute,
+            difference_amplification: 10.0,
+            difference_threshold: 0.01,
+            scope: Scope::None,
+            scope_log_scale: false,
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+            saturation: 1.0,
+            sticky_adjustments: true,
+            export_adjustments: true,
+            export_dir: None,
+            channel_view: ChannelView::Color,
+            loupe_magnification: 4.0,
+            loupe_size: 240.0,
+            loupe_shape: LoupeShape::Circle,
+            show_minimap: true,
         }
     }
 }
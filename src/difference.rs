@@ -0,0 +1,257 @@
+use anyhow::{Result, bail};
+use image::{DynamicImage, GenericImageView};
+use log::{debug, info, warn};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Instant;
+
+/// 差分の計算要求（要求の番号、表示中の画像、比較する画像、しきい値）
+type DifferenceRequest = (u64, Arc<DynamicImage>, Arc<DynamicImage>, f32);
+
+/// 2枚の画像のピクセルの差分の統計
+///
+/// 差分は画像ファイルに保存されている値（SDR画像はsRGBエンコードされた値、HDR画像はリニアの値）を
+/// 0.0〜1.0 に正規化し、RGBAの各チャンネルごとに求める。
+/// アルファも色と同じ1チャンネルとして扱うため、最大誤差・PSNR・変化したピクセルには透明度の違いも含まれる
+#[derive(Debug, Clone, Copy)]
+pub struct DifferenceStats {
+    /// 最大誤差（全チャンネルの差分の絶対値の最大）
+    pub max_error: f32,
+    /// PSNR（dB）。2枚が完全に一致する場合は無限大
+    pub psnr: f64,
+    /// しきい値を超えて変化したピクセルの数
+    pub changed_pixels: u64,
+    /// ピクセルの総数
+    pub total_pixels: u64,
+}
+
+impl DifferenceStats {
+    /// OSDとログに表示する要約を作成する
+    ///
+    /// # Returns
+    /// * `String` - 最大誤差・PSNR・変化したピクセル数をまとめた文字列
+    pub fn summary(&self) -> String {
+        let psnr = if self.psnr.is_finite() {
+            format!("{:.2} dB", self.psnr)
+        } else {
+            "∞".to_string()
+        };
+        let ratio = self.changed_pixels as f64 / self.total_pixels.max(1) as f64 * 100.0;
+        format!(
+            "最大誤差 {:.4}（8bit換算 {:.0}）  PSNR {}  変化 {} / {} px（{:.2}%）",
+            self.max_error,
+            self.max_error * 255.0,
+            psnr,
+            self.changed_pixels,
+            self.total_pixels,
+            ratio
+        )
+    }
+}
+
+/// 同じサイズの2枚の画像の差分の統計を計算する
+///
+/// # Arguments
+/// * `a` - 1枚目の画像
+/// * `b` - 2枚目の画像
+/// * `threshold` - 変化したピクセルとみなす差分のしきい値（いずれかのチャンネルの差分がこれを超えたら変化とする）
+///
+/// # Returns
+/// * `Result<DifferenceStats>` - 差分の統計（画像サイズが異なる場合はエラー）
+pub fn compute(a: &DynamicImage, b: &DynamicImage, threshold: f32) -> Result<DifferenceStats> {
+    let (a_width, a_height) = a.dimensions();
+    let (b_width, b_height) = b.dimensions();
+    if (a_width, a_height) != (b_width, b_height) {
+        bail!(
+            "画像サイズが異なるため差分を計算できません: {}x{} と {}x{}",
+            a_width, a_height, b_width, b_height
+        );
+    }
+
+    let (a, b) = (a.to_rgba32f(), b.to_rgba32f());
+    let mut max_error = 0.0f32;
+    let mut squared_sum = 0.0f64;
+    let mut changed_pixels = 0u64;
+    for (pa, pb) in a.pixels().zip(b.pixels()) {
+        let mut pixel_max = 0.0f32;
+        for (ca, cb) in pa.0.iter().zip(pb.0.iter()) {
+            let diff = (ca - cb).abs();
+            pixel_max = pixel_max.max(diff);
+            squared_sum += diff as f64 * diff as f64;
+        }
+        max_error = max_error.max(pixel_max);
+        if pixel_max > threshold {
+            changed_pixels += 1;
+        }
+    }
+
+    // 最大値を1.0としたPSNR（MSEはアルファを含む全ピクセル・全チャンネルの平均）
+    let total_pixels = a_width as u64 * a_height as u64;
+    let mse = squared_sum / (total_pixels.max(1) * 4) as f64;
+    let psnr = if mse > 0.0 { 10.0 * (1.0 / mse).log10() } else { f64::INFINITY };
+
+    Ok(DifferenceStats {
+        max_error,
+        psnr,
+        changed_pixels,
+        total_pixels,
+    })
+}
+
+/// 差分の統計を計算するワーカー
+///
+/// 大きな画像では計算に時間がかかるため、ワーカースレッドで計算し、
+/// 画像を切り替えるたびに `request` で計算し直す
+pub struct DifferenceAnalyzer {
+    /// 計算要求の送信側
+    requests: Sender<DifferenceRequest>,
+    /// 計算結果の受信側
+    results: Receiver<(u64, Result<DifferenceStats>)>,
+    /// 直前に要求した計算の番号
+    requested: u64,
+    /// 計算結果を待っているかどうか
+    pending: bool,
+    /// 表示中の画像の差分の統計
+    stats: Option<Result<DifferenceStats>>,
+}
+
+impl DifferenceAnalyzer {
+    /// 新しい DifferenceAnalyzer インスタンスを作成し、計算スレッドを起動する
+    ///
+    /// # Returns
+    /// * `DifferenceAnalyzer` - 差分の統計を計算するワーカー
+    pub fn new() -> Self {
+        let (requests, job_receiver) = mpsc::channel::<DifferenceRequest>();
+        let (result_sender, results) = mpsc::channel();
+        let spawned = thread::Builder::new()
+            .name("difference-analyzer".to_string())
+            .spawn(move || {
+                while let Ok(mut job) = job_receiver.recv() {
+                    // 計算待ちの間に画像が切り替わった場合は最新の画像だけを計算する
+                    while let Ok(next) = job_receiver.try_recv() {
+                        job = next;
+                    }
+                    let (id, image, compare, threshold) = job;
+                    let started = Instant::now();
+                    let result = compute(&image, &compare, threshold);
+                    debug!("差分の統計を計算しました: {}x{} ({:?})", image.width(), image.height(), started.elapsed());
+                    if result_sender.send((id, result)).is_err() {
+                        return;
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("差分の計算スレッドの起動に失敗: {:?}", e);
+        }
+
+        DifferenceAnalyzer {
+            requests,
+            results,
+            requested: 0,
+            pending: false,
+            stats: None,
+        }
+    }
+
+    /// 2枚の画像の差分の計算を要求する（計算が終わるまでは統計を持たない）
+    ///
+    /// # Arguments
+    /// * `image` - 表示中の画像
+    /// * `compare` - 比較する画像
+    /// * `threshold` - 変化したピクセルとみなす差分のしきい値
+    pub fn request(&mut self, image: Arc<DynamicImage>, compare: Arc<DynamicImage>, threshold: f32) {
+        self.requested += 1;
+        self.stats = None;
+        self.pending = true;
+        if self.requests.send((self.requested, image, compare, threshold)).is_err() {
+            warn!("差分の計算スレッドが停止しています");
+            self.pending = false;
+        }
+    }
+
+    /// 計算中の要求を取り消し、統計を破棄する
+    pub fn clear(&mut self) {
+        // 計算中の結果が届いても捨てられるよう、番号を進める
+        self.requested += 1;
+        self.stats = None;
+        self.pending = false;
+    }
+
+    /// 計算結果を受け取る
+    pub fn update(&mut self) {
+        while let Ok((id, result)) = self.results.try_recv() {
+            // 古い画像の計算結果は捨てる
+            if id != self.requested {
+                continue;
+            }
+            match &result {
+                Ok(stats) => info!("差分: {}", stats.summary()),
+                Err(e) => warn!("{}", e),
+            }
+            self.stats = Some(result);
+            self.pending = false;
+        }
+    }
+
+    /// 計算結果を待っているかどうかを取得する
+    ///
+    /// # Returns
+    /// * `bool` - 計算中の場合は true
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// 表示中の画像の差分の統計を取得する
+    ///
+    /// # Returns
+    /// * `Option<&Result<DifferenceStats>>` - 差分の統計（計算中または差分表示中でない場合は None）
+    pub fn stats(&self) -> Option<&Result<DifferenceStats>> {
+        self.stats.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn solid(width: u32, height: u32, pixel: [u8; 4]) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(width, height, Rgba(pixel)))
+    }
+
+    #[test]
+    fn identical_images_have_infinite_psnr() {
+        let image = solid(4, 3, [10, 20, 30, 255]);
+        let stats = compute(&image, &image, 0.0).unwrap();
+        assert_eq!(stats.max_error, 0.0);
+        assert!(stats.psnr.is_infinite());
+        assert_eq!(stats.changed_pixels, 0);
+        assert_eq!(stats.total_pixels, 12);
+        assert!(stats.summary().contains("∞"));
+    }
+
+    #[test]
+    fn mismatched_images_report_error_and_psnr() {
+        let mut b = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 0, 255]));
+        b.put_pixel(1, 1, Rgba([255, 0, 0, 255]));
+        let stats = compute(&solid(2, 2, [0, 0, 0, 255]), &DynamicImage::ImageRgba8(b), 0.5).unwrap();
+        assert_eq!(stats.max_error, 1.0);
+        assert_eq!(stats.changed_pixels, 1);
+        // 16チャンネルのうち1つだけが1.0ずれている: MSE = 1/16
+        assert!((stats.psnr - 10.0 * 16f64.log10()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn alpha_is_included_in_the_statistics() {
+        let stats = compute(&solid(1, 1, [0, 0, 0, 255]), &solid(1, 1, [0, 0, 0, 0]), 0.5).unwrap();
+        assert_eq!(stats.max_error, 1.0);
+        assert_eq!(stats.changed_pixels, 1);
+        assert!(stats.psnr.is_finite());
+    }
+
+    #[test]
+    fn different_sizes_are_rejected() {
+        assert!(compute(&solid(2, 2, [0; 4]), &solid(2, 3, [0; 4]), 0.0).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::Arc;
+use std::time::Instant;
+use crate::config::FilmstripPosition;
+use crate::image_handler::{ImageFile, ImageHandler};
+use crate::overlay::{OverlayRenderer, Rect};
+use crate::thumbnail_cache::ThumbnailCache;
+use crate::thumbnail_store::ThumbnailStore;
+
+/// セルの間隔（論理ピクセル）
+const CELL_PADDING: f32 = 6.0;
+/// 帯の背景色
+const STRIP_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+/// 表示中の画像の枠の色
+const CURRENT_COLOR: [f32; 4] = [0.1, 0.4, 1.0, 1.0];
+
+/// 画面の端に前後の画像のサムネイルを並べるフィルムストリップ
+pub struct Filmstrip {
+    /// サムネイル
+    thumbnails: ThumbnailStore,
+    /// サムネイルの一辺（論理ピクセル）
+    thumbnail_size: u32,
+    /// 表示位置
+    position: FilmstripPosition,
+    /// 自動的に隠すまでの秒数（0の場合は常に表示）
+    auto_hide_secs: f32,
+    /// 最後に操作があった時刻
+    shown_at: Instant,
+    /// 直前のフレームで描画した帯の矩形
+    strip: Option<Rect>,
+    /// 直前のフレームで描画したセル（インデックスと矩形）
+    cells: Vec<(usize, Rect)>,
+}
+
+impl Filmstrip {
+    /// 新しい Filmstrip インスタンスを作成する
+    ///
+    /// # Arguments
+    /// * `images` - 画像ファイルのリスト
+    /// * `decoder` - サムネイル生成に使う画像ハンドラー
+    /// * `cache` - ディスク上のサムネイルキャッシュ
+    /// * `thumbnail_size` - サムネイルの一辺（論理ピクセル）
+    /// * `position` - 表示位置
+    /// * `auto_hide_secs` - 自動的に隠すまでの秒数（0の場合は常に表示）
+    ///
+    /// # Returns
+    /// * `Filmstrip` - フィルムストリップ
+    pub fn new(
+        images: &[ImageFile],
+        decoder: Arc<ImageHandler>,
+        cache: Option<Arc<ThumbnailCache>>,
+        thumbnail_size: u32,
+        position: FilmstripPosition,
+        auto_hide_secs: f32,
+    ) -> Self {
+        Filmstrip {
+            thumbnails: ThumbnailStore::new(images, decoder, cache, thumbnail_size),
+            thumbnail_size,
+            position,
+            auto_hide_secs,
+            shown_at: Instant::now(),
+            strip: None,
+            cells: Vec::new(),
+        }
+    }
+
+    /// 操作があったことを通知し、自動で隠すまでの時間をリセットする
+    pub fn touch(&mut self) {
+        self.shown_at = Instant::now();
+    }
+
+    /// 表示するかどうかを判定する
+    ///
+    /// # Arguments
+    /// * `cursor` - マウスカーソルの位置（帯の上にある間は隠さない）
+    ///
+    /// # Returns
+    /// * `bool` - 表示する場合は true
+    pub fn is_visible(&self, cursor: (f32, f32)) -> bool {
+        self.auto_hide_secs <= 0.0
+            || self.shown_at.elapsed().as_secs_f32() <= self.auto_hide_secs
+            || self.strip.is_some_and(|strip| strip.contains(cursor.0, cursor.1))
+    }
+
+    /// 隠れている間はクリックに反応しないよう、直前の描画結果を破棄する
+    pub fn hide(&mut self) {
+        self.strip = None;
+        self.cells.clear();
+    }
+
+    /// 帯の矩形を取得する
+    ///
+    /// # Returns
+    /// * `Option<Rect>` - 直前のフレームで描画した帯の矩形（非表示の場合は None）
+    pub fn strip(&self) -> Option<Rect> {
+        self.strip
+    }
+
+    /// 指定した位置にあるサムネイルのインデックスを取得する
+    ///
+    /// # Arguments
+    /// * `x` - X座標（ピクセル）
+    /// * `y` - Y座標（ピクセル）
+    ///
+    /// # Returns
+    /// * `Option<usize>` - 画像のインデックス
+    pub fn index_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.cells.iter().find(|(_, cell)| cell.contains(x, y)).map(|(index, _)| *index)
+    }
+
+    /// レイアウトの更新とサムネイルの転送・生成要求を行う
+    ///
+    /// # Arguments
+    /// * `device` - WGPU デバイス
+    /// * `queue` - WGPU キュー
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `screen_size` - 画面サイズ（ピクセル）
+    /// * `scale_factor` - 表示倍率
+    /// * `current` - 表示中の画像のインデックス
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        overlay: &OverlayRenderer,
+        screen_size: (f32, f32),
+        scale_factor: f32,
+        current: usize,
+    ) {
+        let padding = CELL_PADDING * scale_factor;
+        let size = self.thumbnail_size as f32 * scale_factor;
+        let thickness = size + padding * 2.0;
+        let step = size + padding;
+        let (width, height) = screen_size;
+
+        let (strip, horizontal) = match self.position {
+            FilmstripPosition::Bottom => (Rect::new(0.0, height - thickness, width, thickness), true),
+            FilmstripPosition::Top => (Rect::new(0.0, 0.0, width, thickness), true),
+            FilmstripPosition::Left => (Rect::new(0.0, 0.0, thickness, height), false),
+            FilmstripPosition::Right => (Rect::new(width - thickness, 0.0, thickness, height), false),
+        };
+        self.strip = Some(strip);
+
+        // 表示中の画像を中央に置き、両側に入るだけ並べる
+        let length = if horizontal { width } else { height };
+        let side = ((length / 2.0) / step).ceil() as usize + 1;
+        let center = (length - size) / 2.0;
+        let total = self.thumbnails.len();
+        let first = current.saturating_sub(side);
+        let last = (current + side + 1).min(total);
+
+        self.cells.clear();
+        for index in first..last {
+            let offset = center + (index as f32 - current as f32) * step;
+            let cell = if horizontal {
+                Rect::new(offset.round(), strip.y + padding, size, size)
+            } else {
+                Rect::new(strip.x + padding, offset.round(), size, size)
+            };
+            self.cells.push((index, cell));
+        }
+
+        self.thumbnails.update(device, queue, overlay, first..last, side);
+    }
+
+    /// フィルムストリップを描画する
+    ///
+    /// # Arguments
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `queue` - WGPU キュー
+    /// * `current` - 表示中の画像のインデックス
+    /// * `font_size` - 文字サイズ（論理ピクセル）
+    pub fn draw(&self, overlay: &mut OverlayRenderer, queue: &wgpu::Queue, current: usize, font_size: f32) {
+        let Some(strip) = self.strip else {
+            return;
+        };
+        overlay.rect(strip, STRIP_COLOR);
+        for (index, cell) in &self.cells {
+            self.thumbnails.draw(overlay, queue, *index, *cell, font_size * 0.7);
+            if *index == current {
+                let border = (CELL_PADDING / 2.0 * cell.w / self.thumbnail_size as f32).max(2.0);
+                let outline = Rect::new(cell.x - border, cell.y - border, cell.w + border * 2.0, cell.h + border * 2.0);
+                overlay.rect_outline(outline, border, CURRENT_COLOR);
+            }
+        }
+    }
+}
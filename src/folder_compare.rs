@@ -0,0 +1,122 @@
+use log::info;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use crate::image_handler::{self, ImageFile};
+
+/// 2つのディレクトリの画像をファイル名で対応付けた比較
+///
+/// 左側のディレクトリの画像を表示し、同じ名前の右側のディレクトリの画像を比較する画像として並べる
+pub struct FolderComparison {
+    /// 左側（表示する画像）のディレクトリ
+    left_dir: PathBuf,
+    /// 右側（比較する画像）のディレクトリ
+    right_dir: PathBuf,
+    /// ファイル名から右側の画像のパスを引く表
+    counterparts: HashMap<String, PathBuf>,
+    /// 左側にしかない画像のファイル名
+    left_only: Vec<String>,
+    /// 右側にしかない画像のファイル名
+    right_only: Vec<String>,
+}
+
+impl FolderComparison {
+    /// 2つのディレクトリの画像をファイル名で対応付ける
+    ///
+    /// # Arguments
+    /// * `left_dir` - 左側のディレクトリ
+    /// * `right_dir` - 右側のディレクトリ
+    /// * `left` - 左側のディレクトリの画像ファイル
+    /// * `right` - 右側のディレクトリの画像ファイル
+    ///
+    /// # Returns
+    /// * `FolderComparison` - 対応付けの結果
+    pub fn new(left_dir: &Path, right_dir: &Path, left: &[ImageFile], right: &[ImageFile]) -> Self {
+        let names = |images: &[ImageFile]| -> HashSet<String> { images.iter().map(|image| image.name.clone()).collect() };
+        let (left_names, right_names) = (names(left), names(right));
+        let counterparts: HashMap<String, PathBuf> = right
+            .iter()
+            .filter(|image| left_names.contains(&image.name))
+            .map(|image| (image.name.clone(), image.path.clone()))
+            .collect();
+        let only = |images: &[ImageFile], others: &HashSet<String>| -> Vec<String> {
+            let mut names: Vec<String> = images
+                .iter()
+                .filter(|image| !others.contains(&image.name))
+                .map(|image| image.name.clone())
+                .collect();
+            names.sort_by(|a, b| image_handler::natural_sort_compare(a, b));
+            names
+        };
+
+        FolderComparison {
+            left_dir: left_dir.to_path_buf(),
+            right_dir: right_dir.to_path_buf(),
+            counterparts,
+            left_only: only(left, &right_names),
+            right_only: only(right, &left_names),
+        }
+    }
+
+    /// 両方のディレクトリにある画像だけを、ファイル名の自然順に並べて取得する
+    ///
+    /// # Arguments
+    /// * `left` - 左側のディレクトリの画像ファイル
+    ///
+    /// # Returns
+    /// * `Vec<ImageFile>` - 対応する画像がある左側の画像ファイル
+    pub fn matched(&self, left: &[ImageFile]) -> Vec<ImageFile> {
+        let mut images: Vec<ImageFile> = left
+            .iter()
+            .filter(|image| self.counterparts.contains_key(&image.name))
+            .cloned()
+            .collect();
+        images.sort_by(|a, b| image_handler::natural_sort_compare(&a.name, &b.name));
+        images
+    }
+
+    /// 左側の画像と同じ名前の右側の画像のパスを取得する
+    ///
+    /// # Arguments
+    /// * `name` - 左側の画像のファイル名
+    ///
+    /// # Returns
+    /// * `Option<&Path>` - 右側の画像のパス（同じ名前の画像がない場合は None）
+    pub fn counterpart(&self, name: &str) -> Option<&Path> {
+        self.counterparts.get(name).map(PathBuf::as_path)
+    }
+
+    /// 片方のディレクトリにしかない画像の一覧をログに出力する
+    pub fn log_unmatched(&self) {
+        info!(
+            "フォルダー比較: {:?} | {:?}（一致 {}件、左のみ {}件、右のみ {}件）",
+            self.left_dir,
+            self.right_dir,
+            self.counterparts.len(),
+            self.left_only.len(),
+            self.right_only.len()
+        );
+        for name in &self.left_only {
+            info!("左のみ: {}", name);
+        }
+        for name in &self.right_only {
+            info!("右のみ: {}", name);
+        }
+    }
+
+    /// OSDに表示する要約を作成する
+    ///
+    /// # Returns
+    /// * `String` - 比較しているディレクトリ名と、片方にしかない画像の数
+    pub fn summary(&self) -> String {
+        let dir_name = |dir: &Path| {
+            dir.file_name().map_or_else(|| dir.display().to_string(), |name| name.to_string_lossy().into_owned())
+        };
+        format!(
+            "フォルダー比較: {} | {}  （左のみ {}件、右のみ {}件）",
+            dir_name(&self.left_dir),
+            dir_name(&self.right_dir),
+            self.left_only.len(),
+            self.right_only.len()
+        )
+    }
+}
@@ -0,0 +1,323 @@
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::image_handler::{ImageFile, ImageHandler};
+use crate::overlay::{OverlayRenderer, Rect};
+use crate::thumbnail_cache::ThumbnailCache;
+use crate::thumbnail_store::ThumbnailStore;
+
+/// セルの間隔（論理ピクセル）
+const CELL_PADDING: f32 = 12.0;
+/// ダブルクリックと判定する間隔
+pub const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+/// 選択中のセルの枠の色
+const SELECTION_COLOR: [f32; 4] = [0.1, 0.4, 1.0, 1.0];
+/// 文字色
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// グリッドのレイアウト（ピクセル単位）
+#[derive(Debug, Clone, Copy)]
+struct GridLayout {
+    /// 列数
+    columns: usize,
+    /// セルの幅（間隔を含む）
+    cell_width: f32,
+    /// セルの高さ（間隔とファイル名を含む）
+    cell_height: f32,
+    /// サムネイルの表示領域の一辺
+    thumbnail_size: f32,
+    /// 左端の余白（グリッドを中央に寄せる）
+    origin_x: f32,
+    /// ヘッダーの高さ（グリッドの上端）
+    header_height: f32,
+    /// セルの間隔
+    padding: f32,
+    /// 画面の高さ
+    screen_height: f32,
+}
+
+impl GridLayout {
+    /// グリッド部分の高さを取得する
+    fn view_height(&self) -> f32 {
+        (self.screen_height - self.header_height).max(1.0)
+    }
+
+    /// 画面内に収まる行数を取得する
+    fn visible_rows(&self) -> usize {
+        ((self.view_height() / self.cell_height).floor() as usize).max(1)
+    }
+}
+
+/// サムネイルを格子状に並べて表示するギャラリー
+pub struct Gallery {
+    /// 画像ファイル名
+    names: Vec<String>,
+    /// 選択中のインデックス
+    selected: usize,
+    /// 縦方向のスクロール量（ピクセル）
+    scroll: f32,
+    /// 選択中のセルが見えるようにスクロールする必要があるかどうか
+    scroll_to_selected: bool,
+    /// サムネイル
+    thumbnails: ThumbnailStore,
+    /// サムネイルの一辺（論理ピクセル）
+    thumbnail_size: u32,
+    /// 文字サイズ（論理ピクセル）
+    font_size: f32,
+    /// 直前のフレームのレイアウト
+    layout: Option<GridLayout>,
+    /// 直前にクリックしたセルと時刻（ダブルクリック判定用）
+    last_click: Option<(usize, Instant)>,
+}
+
+impl Gallery {
+    /// 新しい Gallery インスタンスを作成する
+    ///
+    /// # Arguments
+    /// * `images` - 表示する画像ファイルのリスト
+    /// * `decoder` - サムネイル生成に使う画像ハンドラー
+    /// * `cache` - ディスク上のサムネイルキャッシュ
+    /// * `selected` - 最初に選択するインデックス
+    /// * `thumbnail_size` - サムネイルの一辺（論理ピクセル）
+    /// * `font_size` - 文字サイズ（論理ピクセル）
+    ///
+    /// # Returns
+    /// * `Gallery` - ギャラリー
+    pub fn new(
+        images: &[ImageFile],
+        decoder: Arc<ImageHandler>,
+        cache: Option<Arc<ThumbnailCache>>,
+        selected: usize,
+        thumbnail_size: u32,
+        font_size: f32,
+    ) -> Self {
+        Gallery {
+            names: images.iter().map(|image| image.name.clone()).collect(),
+            selected,
+            scroll: 0.0,
+            scroll_to_selected: true,
+            thumbnails: ThumbnailStore::new(images, decoder, cache, thumbnail_size),
+            thumbnail_size,
+            font_size,
+            layout: None,
+            last_click: None,
+        }
+    }
+
+    /// 選択中のインデックスを取得する
+    ///
+    /// # Returns
+    /// * `usize` - 選択中のインデックス
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// 指定したインデックスを選択し、見える位置までスクロールする
+    ///
+    /// # Arguments
+    /// * `index` - 選択するインデックス
+    pub fn select(&mut self, index: usize) {
+        if !self.names.is_empty() {
+            self.selected = index.min(self.names.len() - 1);
+            self.scroll_to_selected = true;
+        }
+    }
+
+    /// 選択を指定した数だけ移動する
+    ///
+    /// # Arguments
+    /// * `delta` - 移動量（負の値で前へ）
+    pub fn move_selection(&mut self, delta: isize) {
+        let target = self.selected.saturating_add_signed(delta);
+        self.select(target);
+    }
+
+    /// 選択を行単位で移動する
+    ///
+    /// # Arguments
+    /// * `rows` - 移動する行数（負の値で上へ）
+    pub fn move_rows(&mut self, rows: isize) {
+        let columns = self.layout.map_or(1, |layout| layout.columns) as isize;
+        let target = self.selected as isize + rows * columns;
+        // 範囲外に出る場合は同じ列に留まる
+        if target >= 0 && (target as usize) < self.names.len() {
+            self.select(target as usize);
+        } else if rows > 0 {
+            self.select(self.names.len().saturating_sub(1));
+        } else {
+            self.select(0);
+        }
+    }
+
+    /// 選択を1画面分移動する
+    ///
+    /// # Arguments
+    /// * `pages` - 移動する画面数（負の値で上へ）
+    pub fn move_pages(&mut self, pages: isize) {
+        let rows = self.layout.map_or(1, |layout| layout.visible_rows()) as isize;
+        self.move_rows(pages * rows);
+    }
+
+    /// 縦方向にスクロールする
+    ///
+    /// # Arguments
+    /// * `delta` - スクロール量（ピクセル、正の値で下へ）
+    pub fn scroll_by(&mut self, delta: f32) {
+        self.scroll += delta;
+        self.scroll_to_selected = false;
+    }
+
+    /// マウスホイール1段あたりのスクロール量を取得する
+    ///
+    /// # Returns
+    /// * `f32` - スクロール量（ピクセル）
+    pub fn wheel_step(&self) -> f32 {
+        self.layout.map_or(100.0, |layout| layout.cell_height * 0.5)
+    }
+
+    /// クリックされたセルを選択する
+    ///
+    /// # Arguments
+    /// * `x` - クリック位置のX座標（ピクセル）
+    /// * `y` - クリック位置のY座標（ピクセル）
+    ///
+    /// # Returns
+    /// * `bool` - 同じセルをダブルクリックした場合は true
+    pub fn click(&mut self, x: f32, y: f32) -> bool {
+        let Some(index) = self.index_at(x, y) else {
+            return false;
+        };
+        self.select(index);
+        let now = Instant::now();
+        let double_click = self.last_click
+            .is_some_and(|(last, time)| last == index && now.duration_since(time) < DOUBLE_CLICK_INTERVAL);
+        self.last_click = if double_click { None } else { Some((index, now)) };
+        double_click
+    }
+
+    /// 指定した位置にあるセルのインデックスを取得する
+    fn index_at(&self, x: f32, y: f32) -> Option<usize> {
+        let layout = self.layout?;
+        if y < layout.header_height || x < layout.origin_x {
+            return None;
+        }
+        let column = ((x - layout.origin_x) / layout.cell_width) as usize;
+        let row = ((y - layout.header_height + self.scroll) / layout.cell_height) as usize;
+        let index = row * layout.columns + column;
+        (column < layout.columns && index < self.names.len()).then_some(index)
+    }
+
+    /// 画面サイズからレイアウトを計算する
+    fn compute_layout(&self, overlay: &OverlayRenderer, screen_size: (f32, f32), scale_factor: f32) -> GridLayout {
+        let padding = CELL_PADDING * scale_factor;
+        let thumbnail_size = self.thumbnail_size as f32 * scale_factor;
+        let caption_height = overlay.line_height(self.font_size * 0.8);
+        let cell_width = thumbnail_size + padding;
+        let cell_height = thumbnail_size + caption_height + padding;
+        let columns = (((screen_size.0 - padding) / cell_width).floor() as usize).max(1);
+        let grid_width = columns as f32 * cell_width + padding;
+        GridLayout {
+            columns,
+            cell_width,
+            cell_height,
+            thumbnail_size,
+            origin_x: ((screen_size.0 - grid_width) / 2.0).max(0.0) + padding,
+            header_height: overlay.line_height(self.font_size) + padding * 2.0,
+            padding,
+            screen_height: screen_size.1,
+        }
+    }
+
+    /// レイアウトの更新、生成済みサムネイルの転送、表示範囲のサムネイル生成要求を行う
+    ///
+    /// # Arguments
+    /// * `device` - WGPU デバイス
+    /// * `queue` - WGPU キュー
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `screen_size` - 画面サイズ（ピクセル）
+    /// * `scale_factor` - 表示倍率
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        overlay: &OverlayRenderer,
+        screen_size: (f32, f32),
+        scale_factor: f32,
+    ) {
+        let layout = self.compute_layout(overlay, screen_size, scale_factor);
+        self.layout = Some(layout);
+
+        // 選択中のセルが見えるようにスクロール位置を合わせる
+        let selected_top = (self.selected / layout.columns) as f32 * layout.cell_height;
+        if self.scroll_to_selected {
+            if selected_top < self.scroll {
+                self.scroll = selected_top;
+            } else if selected_top + layout.cell_height > self.scroll + layout.view_height() {
+                self.scroll = selected_top + layout.cell_height - layout.view_height();
+            }
+            self.scroll_to_selected = false;
+        }
+        let rows = self.names.len().div_ceil(layout.columns);
+        let content_height = rows as f32 * layout.cell_height + layout.padding;
+        self.scroll = self.scroll.clamp(0.0, (content_height - layout.view_height()).max(0.0));
+
+        // 表示範囲に加えて前後1画面分を先読みする
+        let visible = self.visible_range(&layout);
+        let prefetch = layout.columns * layout.visible_rows();
+        self.thumbnails.update(device, queue, overlay, visible, prefetch);
+    }
+
+    /// 画面内に表示されるセルの範囲を取得する
+    fn visible_range(&self, layout: &GridLayout) -> Range<usize> {
+        let first_row = (self.scroll / layout.cell_height).floor() as usize;
+        let last_row = ((self.scroll + layout.view_height()) / layout.cell_height).ceil() as usize;
+        let start = (first_row * layout.columns).min(self.names.len());
+        let end = (last_row * layout.columns).min(self.names.len());
+        start..end
+    }
+
+    /// ギャラリーを描画する
+    ///
+    /// # Arguments
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `queue` - WGPU キュー
+    /// * `screen_width` - 画面の幅（ピクセル）
+    pub fn draw(&self, overlay: &mut OverlayRenderer, queue: &wgpu::Queue, screen_width: f32) {
+        let Some(layout) = self.layout else {
+            return;
+        };
+        let caption_size = self.font_size * 0.8;
+        let scale_factor = layout.padding / CELL_PADDING;
+
+        for index in self.visible_range(&layout) {
+            let column = index % layout.columns;
+            let row = index / layout.columns;
+            let x = layout.origin_x + column as f32 * layout.cell_width;
+            let y = layout.header_height + row as f32 * layout.cell_height - self.scroll;
+            let cell = Rect::new(x, y, layout.thumbnail_size, layout.thumbnail_size);
+
+            self.thumbnails.draw(overlay, queue, index, cell, caption_size);
+            if index == self.selected {
+                let border = 3.0 * scale_factor;
+                let outline = Rect::new(x - border, y - border, cell.w + border * 2.0, cell.h + border * 2.0);
+                overlay.rect_outline(outline, border, SELECTION_COLOR);
+            }
+            overlay.text(
+                queue,
+                x,
+                y + layout.thumbnail_size + 2.0,
+                caption_size,
+                TEXT_COLOR,
+                &self.names[index],
+                Some(layout.thumbnail_size),
+            );
+        }
+
+        // スクロールしたセルの上に重ねてヘッダーを描画する
+        overlay.rect(Rect::new(0.0, 0.0, screen_width, layout.header_height), [0.0, 0.0, 0.0, 1.0]);
+        if let Some(name) = self.names.get(self.selected) {
+            let header = format!("{}  [{}/{}]", name, self.selected + 1, self.names.len());
+            overlay.text(queue, layout.padding, layout.padding, self.font_size, TEXT_COLOR, &header, Some(screen_width - layout.padding * 2.0));
+        }
+    }
+}
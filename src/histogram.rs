@@ -0,0 +1,232 @@
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use log::{debug, warn};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Instant;
+use crate::config::Scope;
+use crate::overlay::{OverlayRenderer, Rect};
+use crate::pixel_inspector::PixelSample;
+
+/// ヒストグラムの階級の数
+const BINS: usize = 256;
+/// 集計に使うピクセル数の上限（超える場合は間引いて集計する）
+const MAX_SAMPLES: u64 = 4_000_000;
+/// 波形モニターの画像の大きさ（横は画像の横位置、縦は値）
+const WAVEFORM_SIZE: (u32, u32) = (256, 128);
+/// パネルの大きさ（論理ピクセル）
+const PANEL_SIZE: (f32, f32) = (256.0, 128.0);
+/// パネルの背景色
+const PANEL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+/// ヒストグラムの各チャンネル（R, G, B, 輝度）の色
+const CHANNEL_COLORS: [[f32; 4]; 4] = [
+    [1.0, 0.1, 0.1, 0.5],
+    [0.1, 1.0, 0.1, 0.5],
+    [0.15, 0.3, 1.0, 0.5],
+    [1.0, 1.0, 1.0, 0.35],
+];
+
+/// 1枚の画像の集計結果
+struct ScopeData {
+    /// R, G, B, 輝度の各チャンネルのヒストグラム
+    histogram: [[u32; BINS]; 4],
+    /// RGBの波形モニターの画像
+    waveform: RgbaImage,
+}
+
+/// ヒストグラムと波形モニター
+///
+/// 集計はワーカースレッドで行い、画像を切り替えるたびに `request` で集計し直す
+pub struct Scopes {
+    /// 集計要求の送信側（要求の番号と画像）
+    requests: Sender<(u64, Arc<DynamicImage>)>,
+    /// 集計結果の受信側
+    results: Receiver<(u64, ScopeData)>,
+    /// 直前に要求した集計の番号
+    requested: u64,
+    /// 表示中の画像の集計結果
+    data: Option<ScopeData>,
+    /// GPUに転送した波形モニターのテクスチャ
+    waveform: Option<wgpu::BindGroup>,
+}
+
+impl Scopes {
+    /// 新しい Scopes インスタンスを作成し、集計スレッドを起動する
+    ///
+    /// # Returns
+    /// * `Scopes` - ヒストグラムと波形モニター
+    pub fn new() -> Self {
+        let (requests, job_receiver) = mpsc::channel::<(u64, Arc<DynamicImage>)>();
+        let (result_sender, results) = mpsc::channel();
+        let spawned = thread::Builder::new()
+            .name("scope-analyzer".to_string())
+            .spawn(move || {
+                while let Ok(mut job) = job_receiver.recv() {
+                    // 集計待ちの間に画像が切り替わった場合は最新の画像だけを集計する
+                    while let Ok(next) = job_receiver.try_recv() {
+                        job = next;
+                    }
+                    let (id, image) = job;
+                    let started = Instant::now();
+                    let data = analyze(&image);
+                    debug!("ヒストグラムを集計しました: {}x{} ({:?})", image.width(), image.height(), started.elapsed());
+                    if result_sender.send((id, data)).is_err() {
+                        return;
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("ヒストグラムの集計スレッドの起動に失敗: {:?}", e);
+        }
+
+        Scopes {
+            requests,
+            results,
+            requested: 0,
+            data: None,
+            waveform: None,
+        }
+    }
+
+    /// 画像の集計を要求する（集計が終わるまでは何も表示しない）
+    ///
+    /// # Arguments
+    /// * `image` - 表示中の画像
+    pub fn request(&mut self, image: Arc<DynamicImage>) {
+        self.requested += 1;
+        self.data = None;
+        self.waveform = None;
+        if self.requests.send((self.requested, image)).is_err() {
+            warn!("ヒストグラムの集計スレッドが停止しています");
+        }
+    }
+
+    /// 集計結果を受け取り、波形モニターをGPUに転送する
+    ///
+    /// # Arguments
+    /// * `device` - WGPU デバイス
+    /// * `queue` - WGPU キュー
+    /// * `overlay` - オーバーレイレンダラー
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, overlay: &OverlayRenderer) {
+        while let Ok((id, data)) = self.results.try_recv() {
+            // 古い画像の集計結果は捨てる
+            if id == self.requested {
+                self.waveform = Some(overlay.create_image_texture(device, queue, &data.waveform));
+                self.data = Some(data);
+            }
+        }
+    }
+
+    /// パネルの大きさを取得する
+    ///
+    /// # Arguments
+    /// * `scale_factor` - 表示倍率
+    ///
+    /// # Returns
+    /// * `(f32, f32)` - 幅と高さ（ピクセル）
+    pub fn panel_size(scale_factor: f32) -> (f32, f32) {
+        ((PANEL_SIZE.0 * scale_factor).round(), (PANEL_SIZE.1 * scale_factor).round())
+    }
+
+    /// ヒストグラムまたは波形モニターを描画する
+    ///
+    /// # Arguments
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `queue` - WGPU キュー
+    /// * `panel` - 描画する矩形（ピクセル）
+    /// * `scope` - 表示する内容
+    /// * `log_scale` - ヒストグラムの縦軸を対数にするかどうか
+    /// * `font_size` - 見出しの文字サイズ（論理ピクセル）
+    pub fn draw(&self, overlay: &mut OverlayRenderer, queue: &wgpu::Queue, panel: Rect, scope: Scope, log_scale: bool, font_size: f32) {
+        overlay.rect(panel, PANEL_COLOR);
+        let title = match (scope, log_scale) {
+            (Scope::Waveform, _) => "波形モニター",
+            (_, true) => "ヒストグラム（対数）",
+            (_, false) => "ヒストグラム",
+        };
+        let small = font_size * 0.75;
+        let padding = (panel.h * 0.04).round();
+        overlay.text(queue, panel.x + padding, panel.y + padding, small, [1.0, 1.0, 1.0, 0.8], title, Some(panel.w - padding * 2.0));
+
+        let Some(data) = &self.data else {
+            return;
+        };
+        match scope {
+            Scope::Histogram => Self::draw_histogram(overlay, panel, &data.histogram, log_scale),
+            Scope::Waveform => {
+                if let Some(bind_group) = &self.waveform {
+                    overlay.image(panel, bind_group);
+                }
+            }
+            Scope::None => {}
+        }
+    }
+
+    /// ヒストグラムを棒グラフで描画する
+    fn draw_histogram(overlay: &mut OverlayRenderer, panel: Rect, histogram: &[[u32; BINS]; 4], log_scale: bool) {
+        let max = histogram.iter().flatten().copied().max().unwrap_or(0).max(1);
+        let height = |count: u32| -> f32 {
+            let ratio = if log_scale {
+                (count as f32).ln_1p() / (max as f32).ln_1p()
+            } else {
+                count as f32 / max as f32
+            };
+            (ratio * panel.h).round()
+        };
+        let bin_width = panel.w / BINS as f32;
+        for (channel, color) in histogram.iter().zip(CHANNEL_COLORS) {
+            for (bin, &count) in channel.iter().enumerate() {
+                let h = height(count);
+                if h > 0.0 {
+                    let x = panel.x + bin as f32 * bin_width;
+                    overlay.rect(Rect::new(x, panel.y + panel.h - h, bin_width, h), color);
+                }
+            }
+        }
+    }
+}
+
+/// 画像のヒストグラムと波形モニターを集計する
+///
+/// 値は画像ファイルに保存されている値（SDR画像はsRGBエンコードされた値、HDR画像はリニアの値）を
+/// 0.0〜1.0 の範囲で集計し、輝度はRec.709の係数で求める
+fn analyze(image: &DynamicImage) -> ScopeData {
+    let (width, height) = image.dimensions();
+    // 大きな画像は縦横を同じ間隔で間引いて集計する
+    let pixels = width as u64 * height as u64;
+    let step = ((pixels as f64 / MAX_SAMPLES as f64).sqrt().ceil() as u32).max(1);
+
+    let (wave_width, wave_height) = WAVEFORM_SIZE;
+    let mut histogram = [[0u32; BINS]; 4];
+    let mut waveform = vec![[0u32; 3]; (wave_width * wave_height) as usize];
+    let level = |v: f32, levels: usize| -> usize { ((v.clamp(0.0, 1.0) * (levels - 1) as f32).round()) as usize };
+
+    for y in (0..height).step_by(step as usize) {
+        for x in (0..width).step_by(step as usize) {
+            let Some(sample) = PixelSample::read(image, x, y) else {
+                continue;
+            };
+            let [r, g, b, _] = sample.rgba;
+            let luma = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+            for (channel, value) in [r, g, b, luma].into_iter().enumerate() {
+                histogram[channel][level(value, BINS)] += 1;
+            }
+
+            let column = (x as u64 * wave_width as u64 / width as u64) as u32;
+            for (channel, value) in [r, g, b].into_iter().enumerate() {
+                let row = (wave_height - 1) as usize - level(value, wave_height as usize);
+                waveform[row * wave_width as usize + column as usize][channel] += 1;
+            }
+        }
+    }
+
+    // 波形モニターは集まった数を対数で明るさにし、RGBを加算した色で表示する
+    let max = waveform.iter().flatten().copied().max().unwrap_or(0).max(1) as f32;
+    let intensity = |count: u32| -> u8 { ((count as f32).ln_1p() / max.ln_1p() * 255.0).round() as u8 };
+    let waveform = RgbaImage::from_fn(wave_width, wave_height, |x, y| {
+        let [r, g, b] = waveform[(y * wave_width + x) as usize].map(intensity);
+        Rgba([r, g, b, r.max(g).max(b)])
+    });
+
+    ScopeData { histogram, waveform }
+}
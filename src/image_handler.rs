@@ -1,14 +1,26 @@
 use anyhow::Result;
-use image::DynamicImage;
-use log::{debug, info, error};
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use log::{debug, info, error, warn};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use std::cmp::Ordering;
+use crate::animation::{self, Animation};
+use crate::color_management::ColorManager;
 use crate::config::{Config, SortAlgorithm};
+use crate::metadata::ImageMetadata;
+use crate::ora_loader::{self, ContainerKind};
+use crate::psd_loader;
 
 /// サポートされている画像フォーマット
-const SUPPORTED_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "psd"];
+const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "gif", "webp", "psd", "psb", "ora", "kra", "tif", "tiff", "exr", "hdr",
+];
+
+/// 書き出し先を指定しない場合に、元の画像と同じディレクトリに作る書き出し用のフォルダ名
+///
+/// 画像リストはサブディレクトリを検索しないため、書き出したファイルは画像リストに含まれない
+pub const EXPORT_DIR_NAME: &str = "export";
 
 /// 画像ファイル情報
 #[derive(Debug, Clone)]
@@ -55,6 +67,10 @@ pub struct ImageHandler {
     pub current_index: usize,
     /// 設定
     config: Config,
+    /// カラーマネージャー
+    color_manager: ColorManager,
+    /// ICCプロファイルによる色変換を行うかどうか
+    color_management: bool,
 }
 
 impl ImageHandler {
@@ -66,9 +82,12 @@ impl ImageHandler {
     /// # Returns
     /// * `ImageHandler` - 画像ハンドラー
     pub fn new(config: Config) -> Self {
+        let color_manager = ColorManager::new(config.target_icc_profile.as_deref());
         ImageHandler {
             images: Vec::new(),
             current_index: 0,
+            color_management: config.color_management,
+            color_manager,
             config,
         }
     }
@@ -153,7 +172,7 @@ impl ImageHandler {
     /// 
     /// # Arguments
     /// * `images` - ソート対象の画像ファイルリスト
-    fn sort_images(&self, images: &mut Vec<ImageFile>) {
+    fn sort_images(&self, images: &mut [ImageFile]) {
         match self.config.sort_algorithm {
             SortAlgorithm::FileName => {
                 images.sort_by(|a, b| a.name.cmp(&b.name));
@@ -164,11 +183,11 @@ impl ImageHandler {
                 debug!("ファイル名で自然順ソートしました");
             }
             SortAlgorithm::CreatedTime => {
-                images.sort_by(|a, b| a.created.cmp(&b.created));
+                images.sort_by_key(|image| image.created);
                 debug!("作成日時でソートしました");
             }
             SortAlgorithm::ModifiedTime => {
-                images.sort_by(|a, b| a.modified.cmp(&b.modified));
+                images.sort_by_key(|image| image.modified);
                 debug!("更新日時でソートしました");
             }
         }
@@ -204,54 +223,258 @@ impl ImageHandler {
 
     /// 画像ファイルを読み込む
     /// 
+    /// カラーマネジメントが有効な場合は、埋め込みICCプロファイルから出力先プロファイルへ変換する
+    /// 
     /// # Arguments
     /// * `path` - 画像ファイルのパス
     /// 
     /// # Returns
     /// * `Result<DynamicImage>` - 読み込まれた画像
     pub fn load_image(&self, path: &Path) -> Result<DynamicImage> {
+        let (img, icc_profile) = self.decode_image(path)?;
+
+        match icc_profile {
+            Some(_) if self.color_management && !ColorManager::supports(&img) => {
+                debug!("浮動小数点画像のため、ICCプロファイルによる色変換を行いません: {:?}", path);
+                Ok(img)
+            }
+            Some(icc_profile) if self.color_management => {
+                match self.color_manager.convert(&img, &icc_profile) {
+                    Ok(converted) => Ok(converted),
+                    Err(e) => {
+                        warn!("ICCプロファイルによる色変換に失敗したため、変換せずに表示します: {:?}", e);
+                        Ok(img)
+                    }
+                }
+            }
+            _ => Ok(img),
+        }
+    }
+
+    /// 画像ファイルを読み込み、アニメーション画像の場合は全フレームもあわせて返す
+    ///
+    /// アニメーション画像はフレームの展開を一度だけ行い、最初のフレームを静止画として使う
+    ///
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    ///
+    /// # Returns
+    /// * `Result<(DynamicImage, Option<Animation>)>` - 読み込まれた画像と、アニメーション（アニメーション画像でない場合は None）
+    pub fn load_image_with_animation(&self, path: &Path) -> Result<(DynamicImage, Option<Animation>)> {
+        match self.load_animation(path) {
+            Ok(Some(animation)) => {
+                let image = DynamicImage::ImageRgba8(animation.current_frame().clone());
+                return Ok((image, Some(animation)));
+            }
+            Ok(None) => {}
+            Err(e) => warn!("アニメーションの読み込みに失敗したため、静止画として読み込みます: {:?}", e),
+        }
+        Ok((self.load_image(path)?, None))
+    }
+
+    /// アニメーション画像（GIF、APNG、アニメーションWebP）の全フレームを読み込む
+    /// 
+    /// カラーマネジメントが有効な場合は、静止画と同様に各フレームを色変換する
+    /// 
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    /// 
+    /// # Returns
+    /// * `Result<Option<Animation>>` - アニメーション（アニメーション画像でない場合は None）
+    fn load_animation(&self, path: &Path) -> Result<Option<Animation>> {
+        let Some(decoded) = animation::decode_frames(path)? else {
+            return Ok(None);
+        };
+        let mut frames = decoded.frames;
+
+        if let Some(icc_profile) = decoded.icc_profile
+            && self.color_management
+        {
+            let converted: Result<Vec<_>> = frames
+                .iter()
+                .map(|frame| {
+                    let image = DynamicImage::ImageRgba8(frame.image.clone());
+                    self.color_manager.convert(&image, &icc_profile).map(|image| image.to_rgba8())
+                })
+                .collect();
+            match converted {
+                Ok(images) => {
+                    for (frame, image) in frames.iter_mut().zip(images) {
+                        frame.image = image;
+                    }
+                }
+                Err(e) => warn!("ICCプロファイルによる色変換に失敗したため、変換せずに表示します: {:?}", e),
+            }
+        }
+        Ok(Some(Animation::new(frames)))
+    }
+
+    /// 画像ファイルをデコードし、埋め込みICCプロファイルとともに返す
+    /// 
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    /// 
+    /// # Returns
+    /// * `Result<(DynamicImage, Option<Vec<u8>>)>` - デコードされた画像と埋め込みICCプロファイル
+    fn decode_image(&self, path: &Path) -> Result<(DynamicImage, Option<Vec<u8>>)> {
         debug!("画像ファイルを読み込み中: {:?}", path);
         
-        // PSDファイルの場合は専用の処理を行う
-        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-            if ext.to_lowercase() == "psd" {
-                return self.load_psd_image(path);
-            }
+        // PSD/PSBファイルの場合は専用の処理を行う
+        if let Some(ext) = path.extension().and_then(|e| e.to_str())
+            && matches!(ext.to_lowercase().as_str(), "psd" | "psb")
+        {
+            return self.load_psd_image(path);
+        }
+
+        // OpenRaster/Kritaファイルの場合はZIPコンテナから統合画像を取り出す
+        if let Some(kind) = ContainerKind::from_path(path) {
+            return ora_loader::load_container_image(path, kind);
         }
         
-        let img = image::open(path)?;
+        let mut decoder = ImageReader::open(path)?.with_guessed_format()?.into_decoder()?;
+        let icc_profile = decoder.icc_profile().ok().flatten();
+        let img = DynamicImage::from_decoder(decoder)?;
         debug!("画像ファイルの読み込み完了: {}x{}", img.width(), img.height());
-        Ok(img)
+        Ok((img, icc_profile))
     }
     
-    /// PSDファイルを読み込む
+    /// PSD/PSBファイルを読み込む
     /// 
     /// # Arguments
-    /// * `path` - PSDファイルのパス
+    /// * `path` - PSD/PSBファイルのパス
     /// 
     /// # Returns
-    /// * `Result<DynamicImage>` - 読み込まれた画像
-    fn load_psd_image(&self, path: &Path) -> Result<DynamicImage> {
+    /// * `Result<(DynamicImage, Option<Vec<u8>>)>` - 読み込まれた画像と埋め込みICCプロファイル
+    fn load_psd_image(&self, path: &Path) -> Result<(DynamicImage, Option<Vec<u8>>)> {
         debug!("PSDファイルを読み込み中: {:?}", path);
         
         // ファイルを読み込む
         let bytes = std::fs::read(path)?;
         
-        // PSDファイルをパース
-        let psd = psd::Psd::from_bytes(&bytes)?;
+        // 統合画像を展開（取得できない場合はレイヤー合成やサムネイルにフォールバック）
+        let (img, icc_profile) = psd_loader::load_psd(&bytes)?;
         
-        // 最終合成画像を取得（RGBA形式）
-        let rgba_data = psd.rgba();
-        let width = psd.width();
-        let height = psd.height();
-        
-        debug!("PSDファイルの読み込み完了: {}x{}", width, height);
-        
-        // RGBAバッファからDynamicImageを作成
-        let img_buffer = image::RgbaImage::from_raw(width, height, rgba_data)
-            .ok_or_else(|| anyhow::anyhow!("PSDからの画像バッファ作成に失敗"))?;
-        
-        Ok(DynamicImage::ImageRgba8(img_buffer))
+        debug!("PSDファイルの読み込み完了: {}x{}", img.width(), img.height());
+        Ok((img, icc_profile))
+    }
+
+    /// 現在のソートアルゴリズムを取得する
+    /// 
+    /// # Returns
+    /// * `SortAlgorithm` - ソートアルゴリズム
+    pub fn sort_algorithm(&self) -> SortAlgorithm {
+        self.config.sort_algorithm
+    }
+
+    /// ソートアルゴリズムを変更して画像リストを並べ替える
+    /// 
+    /// 並べ替えた後も同じ画像を表示対象にする
+    /// 
+    /// # Arguments
+    /// * `sort_algorithm` - 新しいソートアルゴリズム
+    pub fn set_sort_algorithm(&mut self, sort_algorithm: SortAlgorithm) {
+        let current_path = self.current_image().map(|image| image.path.clone());
+        self.config.sort_algorithm = sort_algorithm;
+
+        let mut images = std::mem::take(&mut self.images);
+        self.sort_images(&mut images);
+        self.images = images;
+
+        if let Some(path) = current_path
+            && let Some(index) = self.images.iter().position(|image| image.path == path)
+        {
+            self.current_index = index;
+        }
+        info!("ソートアルゴリズム: {:?}", sort_algorithm);
+    }
+
+    /// 画像リストを置き換える
+    /// 
+    /// 置き換えた後も同じ画像を表示対象にし、含まれていない場合は先頭の画像を表示対象にする
+    /// 
+    /// # Arguments
+    /// * `images` - 新しい画像ファイルのリスト
+    pub fn replace_images(&mut self, images: Vec<ImageFile>) {
+        let current_path = self.current_image().map(|image| image.path.clone());
+        self.images = images;
+        self.current_index = current_path
+            .and_then(|path| self.images.iter().position(|image| image.path == path))
+            .unwrap_or(0);
+    }
+
+    /// カラーマネジメントが有効かどうかを取得する
+    /// 
+    /// # Returns
+    /// * `bool` - 有効な場合は true
+    pub fn color_management(&self) -> bool {
+        self.color_management
+    }
+
+    /// カラーマネジメントの有効・無効を切り替える
+    /// 
+    /// # Arguments
+    /// * `enabled` - 有効にする場合は true
+    pub fn set_color_management(&mut self, enabled: bool) {
+        self.color_management = enabled;
+        info!("カラーマネジメント: {}", if enabled { "有効" } else { "無効" });
+    }
+
+    /// 画像ファイルのレイヤー名一覧を取得する
+    /// 
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    /// 
+    /// # Returns
+    /// * `Result<Vec<String>>` - レイヤー名のリスト（レイヤーを持たない形式の場合は空）
+    pub fn layer_names(&self, path: &Path) -> Result<Vec<String>> {
+        if let Some(kind) = ContainerKind::from_path(path) {
+            return ora_loader::layer_names(path, kind);
+        }
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+            Some("psd" | "psb") => psd_loader::layer_names(&std::fs::read(path)?),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 画像ファイルの幅と高さを取得する
+    /// 
+    /// 画像データは展開せず、ヘッダー（OpenRaster/Kritaはレイヤー構造のXML）のみを読む
+    /// 
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    /// 
+    /// # Returns
+    /// * `Result<(u32, u32)>` - 幅と高さ
+    pub fn image_dimensions(&self, path: &Path) -> Result<(u32, u32)> {
+        if let Some(kind) = ContainerKind::from_path(path) {
+            return ora_loader::dimensions(path, kind);
+        }
+        let is_psd = path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| matches!(ext.to_lowercase().as_str(), "psd" | "psb"));
+        if is_psd {
+            return psd_loader::dimensions(path);
+        }
+        Ok(ImageReader::open(path)?.with_guessed_format()?.into_dimensions()?)
+    }
+
+    /// 画像ファイルのメタデータ（EXIF、XMP、IPTC、PNGテキスト）を取得する
+    /// 
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    /// 
+    /// # Returns
+    /// * `Result<ImageMetadata>` - メタデータ
+    pub fn read_metadata(&self, path: &Path) -> Result<ImageMetadata> {
+        ImageMetadata::read(path)
+    }
+
+    /// 画像ファイルのリストを取得する
+    /// 
+    /// # Returns
+    /// * `&[ImageFile]` - ソート済みの画像ファイルのリスト
+    pub fn images(&self) -> &[ImageFile] {
+        &self.images
     }
 
     /// 画像が空かどうかを確認する
@@ -271,6 +494,33 @@ impl ImageHandler {
     }
 }
 
+/// 既存のファイルと重ならない名前で新しいファイルを作成する
+///
+/// `<stem>.<extension>` が既にある場合は `<stem> (2).<extension>`、`<stem> (3).<extension>` … の順に試す
+///
+/// # Arguments
+/// * `dir` - ファイルを作成するディレクトリ
+/// * `stem` - 拡張子を除いたファイル名
+/// * `extension` - 拡張子
+///
+/// # Returns
+/// * `Result<(PathBuf, fs::File)>` - 作成したファイルのパスと、書き込み用に開いたファイル
+pub fn create_unique_file(dir: &Path, stem: &str, extension: &str) -> Result<(PathBuf, fs::File)> {
+    let mut number = 1;
+    loop {
+        let file_name = match number {
+            1 => format!("{}.{}", stem, extension),
+            n => format!("{} ({}).{}", stem, n, extension),
+        };
+        let path = dir.join(file_name);
+        match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => number += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
 /// 自然順ソート比較関数
 /// 
 /// 文字列内の数字部分を数値として比較し、ゼロサプレスした自然順ソートを行う
@@ -287,7 +537,7 @@ impl ImageHandler {
 /// // 通常の文字列ソート: "file1.jpg", "file10.jpg", "file2.jpg"
 /// // 自然順ソート:       "file1.jpg", "file2.jpg", "file10.jpg"
 /// ```
-fn natural_sort_compare(a: &str, b: &str) -> Ordering {
+pub fn natural_sort_compare(a: &str, b: &str) -> Ordering {
     let mut chars_a = a.chars().peekable();
     let mut chars_b = b.chars().peekable();
 
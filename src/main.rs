@@ -1,16 +1,43 @@
-#![windows_subsystem = "windows"]
+#![windows_subsystem = "windows"]
 use anyhow::Result;
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 use log::{error, info};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use winit::event_loop::EventLoop;
 
+mod adjustments;
+mod animation;
+mod color_management;
 mod config;
+mod difference;
+mod filmstrip;
+mod folder_compare;
+mod gallery;
+mod histogram;
 mod image_handler;
+mod metadata;
+mod minimap;
+mod ora_loader;
+mod osd;
+mod overlay;
+mod pixel_inspector;
+mod psd_loader;
+mod slideshow;
+mod spread;
+mod thumbnail;
+mod thumbnail_cache;
+mod thumbnail_store;
+mod view_state;
 mod viewer;
+mod webtoon;
 
-use config::Config;
+use config::{Config, SlideshowOrder};
+use folder_compare::FolderComparison;
 use image_handler::ImageHandler;
+use thumbnail::ThumbnailLoader;
+use thumbnail_cache::ThumbnailCache;
+use view_state::ViewState;
 use viewer::ImageViewer;
 
 /// ログを初期化する
@@ -31,7 +58,8 @@ fn init_logging() -> Result<()> {
         .level(log::LevelFilter::Info)
         .level_for("wgpu", log::LevelFilter::Warn)
         .level_for("winit", log::LevelFilter::Warn)
-        .chain(std::io::stdout())
+        // 標準出力はメタデータなどの書き出しに使うため、ログは標準エラー出力に出す
+        .chain(std::io::stderr())
         .apply()?;
     Ok(())
 }
@@ -50,9 +78,103 @@ fn parse_args() -> clap::ArgMatches {
                 .required(true)
                 .index(1),
         )
+        .arg(
+            Arg::new("fullscreen")
+                .long("fullscreen")
+                .help("全画面表示で起動する")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("slideshow")
+                .long("slideshow")
+                .value_name("SECONDS")
+                .help("スライドショーを開始する（表示時間の秒数を指定すると設定より優先する）")
+                .num_args(0..=1)
+                .value_parser(clap::value_parser!(f32)),
+        )
+        .arg(
+            Arg::new("shuffle")
+                .long("shuffle")
+                .help("スライドショーをランダム順にする")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("spread")
+                .long("spread")
+                .help("見開き表示（2ページを並べて表示）で起動する")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("rtl")
+                .long("rtl")
+                .help("見開き表示で右から左へ読む（右綴じの漫画など）")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("webtoon")
+                .long("webtoon")
+                .help("縦読み（画像を縦に並べてスクロールする表示）で起動する")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("compare")
+                .long("compare")
+                .value_name("PATH")
+                .help("指定した画像を、開いた画像と並べて比較表示する（ディレクトリを指定した場合は同じ名前の画像どうしを比較する）"),
+        )
+        .arg(
+            Arg::new("print-metadata")
+                .long("print-metadata")
+                .help("ウィンドウを開かずに画像のメタデータを標準出力に書き出して終了する")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("warm-thumbnails")
+                .long("warm-thumbnails")
+                .help("ウィンドウを開かずにサムネイルを生成してキャッシュに保存し、終了する")
+                .action(ArgAction::SetTrue),
+        )
         .get_matches()
 }
 
+/// 画像リストのサムネイルを生成してディスクキャッシュに保存する
+/// 
+/// # Arguments
+/// * `config` - アプリケーション設定
+/// * `image_handler` - 画像リストを読み込んだ画像ハンドラー
+/// 
+/// # Returns
+/// * `Result<()>` - 成功時は Ok(())
+fn warm_thumbnails(config: &Config, image_handler: &ImageHandler) -> Result<()> {
+    let cache = ThumbnailCache::new(config)
+        .map(Arc::new)
+        .ok_or_else(|| anyhow::anyhow!("サムネイルキャッシュが無効になっています（設定ファイルで thumbnail_cache = true にしてください）"))?;
+    let decoder = Arc::new(ImageHandler::new(config.clone()));
+    let loader = ThumbnailLoader::new(decoder, cache.size(), Some(cache.clone()));
+
+    let jobs: Vec<_> = image_handler.images()
+        .iter()
+        .enumerate()
+        .map(|(index, image)| (index, image.path.clone()))
+        .collect();
+    let total = jobs.len();
+    loader.request(jobs);
+
+    let mut failed = 0;
+    for done in 1..=total {
+        let Some(result) = loader.wait() else { break };
+        if result.image.is_none() {
+            failed += 1;
+        }
+        if done % 50 == 0 || done == total {
+            info!("サムネイルを生成中: {}/{}", done, total);
+        }
+    }
+    cache.evict()?;
+    info!("サムネイルの生成が完了しました: {}個（失敗 {}個）", total, failed);
+    Ok(())
+}
+
 /// アプリケーションのメイン処理
 /// 
 /// # Returns
@@ -69,9 +191,41 @@ fn run_app() -> Result<()> {
 
     // 設定を読み込み
     let config_path = Config::get_config_path()?;
-    let config = Config::load(&config_path)?;
+    let mut config = Config::load(&config_path)?;
     info!("設定を読み込みました: {:?}", config);
 
+    // 前回終了時の表示設定で上書きする
+    let view_state = if config.remember_window_state {
+        let view_state = ViewState::load(&ViewState::get_state_path()?);
+        view_state.apply_to(&mut config);
+        Some(view_state)
+    } else {
+        None
+    };
+
+    // コマンドライン引数の指定を設定より優先する
+    if matches.get_flag("fullscreen") {
+        config.fullscreen = true;
+    }
+    if matches.contains_id("slideshow") {
+        config.slideshow = true;
+        if let Some(&interval) = matches.get_one::<f32>("slideshow") {
+            config.slideshow_interval_secs = interval;
+        }
+    }
+    if matches.get_flag("shuffle") {
+        config.slideshow_order = SlideshowOrder::Random;
+    }
+    if matches.get_flag("spread") {
+        config.spread = true;
+    }
+    if matches.get_flag("rtl") {
+        config.right_to_left = true;
+    }
+    if matches.get_flag("webtoon") {
+        config.webtoon = true;
+    }
+
     // 画像ハンドラーを初期化
     let mut image_handler = ImageHandler::new(config.clone());
 
@@ -93,11 +247,58 @@ fn run_app() -> Result<()> {
 
     info!("{}個の画像ファイルが見つかりました", image_handler.len());
 
+    // メタデータの書き出しが指定された場合はウィンドウを開かずに終了
+    if matches.get_flag("print-metadata") {
+        let targets: Vec<&Path> = if path.is_file() {
+            vec![path]
+        } else {
+            image_handler.images().iter().map(|image| image.path.as_path()).collect()
+        };
+        for target in targets {
+            match image_handler.read_metadata(target) {
+                Ok(metadata) => println!("{}", metadata.to_text(target)),
+                Err(e) => error!("メタデータの読み込みに失敗: {:?} ({:?})", target, e),
+            }
+        }
+        return Ok(());
+    }
+
+    // サムネイルの事前生成が指定された場合はウィンドウを開かずに終了
+    if matches.get_flag("warm-thumbnails") {
+        return warm_thumbnails(&config, &image_handler);
+    }
+
     // イベントループを作成
     let event_loop = EventLoop::new()?;
 
+    // 比較する画像を確認する
+    let compare_path = matches.get_one::<String>("compare").map(PathBuf::from);
+    let mut folder_comparison = None;
+    if let Some(compare_path) = &compare_path {
+        if compare_path.is_dir() {
+            // ディレクトリどうしを比較する場合は、両方にある画像だけを表示対象にする
+            let left_dir = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+            let mut right_handler = ImageHandler::new(config.clone());
+            right_handler.load_images_from_directory(compare_path)?;
+            let comparison = FolderComparison::new(left_dir, compare_path, image_handler.images(), right_handler.images());
+            comparison.log_unmatched();
+            image_handler.replace_images(comparison.matched(image_handler.images()));
+            if image_handler.is_empty() {
+                return Err(anyhow::anyhow!("名前が一致する画像ファイルが見つかりません: {:?} | {:?}", left_dir, compare_path));
+            }
+            folder_comparison = Some(comparison);
+        } else if !compare_path.is_file() {
+            return Err(anyhow::anyhow!("比較する画像ファイルが存在しません: {:?}", compare_path));
+        }
+    }
+
     // 画像ビューアーを初期化
-    let viewer = ImageViewer::new(config, image_handler);
+    let mut viewer = ImageViewer::new(config, image_handler, view_state);
+    if let Some(comparison) = folder_comparison {
+        viewer.set_folder_comparison(comparison);
+    } else if let Some(compare_path) = compare_path {
+        viewer.set_compare_path(compare_path);
+    }
     info!("画像ビューアーを初期化しました");
 
     // アプリケーションを実行
@@ -0,0 +1,614 @@
+use anyhow::Result;
+use exif::{In, Tag, Value};
+use flate2::read::ZlibDecoder;
+use log::debug;
+use std::fmt::Write as _;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use crate::ora_loader::unescape_xml;
+use crate::psd_loader::{self, PsdDocument};
+
+/// PNGファイルのシグネチャ
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+/// PSDのIPTC-NAAリソースID
+const RESOURCE_IPTC: u16 = 1028;
+/// PSDのEXIFリソースID
+const RESOURCE_EXIF: u16 = 1058;
+/// PSDのXMPリソースID
+const RESOURCE_XMP: u16 = 1060;
+/// 圧縮されたテキストを展開する最大のバイト数（壊れたファイルで巨大な領域を確保しないための上限）
+const MAX_INFLATED_SIZE: u64 = 1 << 24;
+
+/// 表示するEXIFタグと表示名
+const EXIF_FIELDS: &[(Tag, &str)] = &[
+    (Tag::Make, "メーカー"),
+    (Tag::Model, "カメラ"),
+    (Tag::LensModel, "レンズ"),
+    (Tag::ExposureTime, "シャッタースピード"),
+    (Tag::FNumber, "絞り値"),
+    (Tag::PhotographicSensitivity, "ISO感度"),
+    (Tag::FocalLength, "焦点距離"),
+    (Tag::FocalLengthIn35mmFilm, "焦点距離（35mm換算）"),
+    (Tag::ExposureBiasValue, "露出補正"),
+    (Tag::ExposureProgram, "露出プログラム"),
+    (Tag::MeteringMode, "測光方式"),
+    (Tag::Flash, "フラッシュ"),
+    (Tag::WhiteBalance, "ホワイトバランス"),
+    (Tag::DateTimeOriginal, "撮影日時"),
+    (Tag::DateTimeDigitized, "デジタル化日時"),
+    (Tag::DateTime, "更新日時"),
+    (Tag::Orientation, "向き"),
+    (Tag::Software, "ソフトウェア"),
+    (Tag::Artist, "作者"),
+    (Tag::Copyright, "著作権"),
+    (Tag::ImageDescription, "説明"),
+];
+
+/// 表示するXMPプロパティと表示名
+const XMP_FIELDS: &[(&str, &str)] = &[
+    ("dc:title", "タイトル"),
+    ("dc:creator", "作成者"),
+    ("dc:description", "説明"),
+    ("dc:subject", "キーワード"),
+    ("dc:rights", "著作権"),
+    ("xmp:Rating", "レーティング"),
+    ("xmp:Label", "ラベル"),
+    ("xmp:CreatorTool", "作成ツール"),
+    ("xmp:CreateDate", "作成日時"),
+    ("xmp:ModifyDate", "更新日時"),
+    ("photoshop:DateCreated", "作成日"),
+    ("photoshop:City", "市区町村"),
+    ("photoshop:Country", "国"),
+];
+
+/// 表示するIPTC（レコード2）データセットと表示名
+const IPTC_FIELDS: &[(u8, &str)] = &[
+    (5, "タイトル"),
+    (25, "キーワード"),
+    (55, "作成日"),
+    (80, "作成者"),
+    (90, "市区町村"),
+    (101, "国"),
+    (105, "見出し"),
+    (110, "クレジット"),
+    (116, "著作権"),
+    (120, "説明"),
+];
+
+/// メタデータの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataGroup {
+    /// EXIF
+    Exif,
+    /// EXIFのGPS情報
+    Gps,
+    /// XMP
+    Xmp,
+    /// IPTC-IIM
+    Iptc,
+    /// PNGのテキストチャンク（tEXt/zTXt/iTXt）
+    PngText,
+}
+
+impl MetadataGroup {
+    /// 表示用のラベルを取得する
+    ///
+    /// # Returns
+    /// * `&str` - ラベル
+    pub fn label(self) -> &'static str {
+        match self {
+            MetadataGroup::Exif => "EXIF",
+            MetadataGroup::Gps => "GPS",
+            MetadataGroup::Xmp => "XMP",
+            MetadataGroup::Iptc => "IPTC",
+            MetadataGroup::PngText => "PNG",
+        }
+    }
+}
+
+/// メタデータの項目
+#[derive(Debug, Clone)]
+pub struct MetadataEntry {
+    /// メタデータの種類
+    pub group: MetadataGroup,
+    /// 項目名
+    pub key: String,
+    /// 値
+    pub value: String,
+}
+
+/// 画像ファイルのメタデータ
+#[derive(Debug, Clone, Default)]
+pub struct ImageMetadata {
+    /// メタデータの項目
+    pub entries: Vec<MetadataEntry>,
+}
+
+impl ImageMetadata {
+    /// 画像ファイルからメタデータを読み込む
+    ///
+    /// 個々の形式の解析に失敗した場合は、その形式の項目を省略する
+    ///
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    ///
+    /// # Returns
+    /// * `Result<ImageMetadata>` - メタデータ
+    pub fn read(path: &Path) -> Result<Self> {
+        debug!("メタデータを読み込み中: {:?}", path);
+        let bytes = std::fs::read(path)?;
+        let mut metadata = ImageMetadata::default();
+
+        let is_psd = path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e.to_lowercase().as_str(), "psd" | "psb"));
+
+        if is_psd {
+            if let Ok(document) = PsdDocument::parse(&bytes) {
+                if let Some(exif) = document.resource(RESOURCE_EXIF)
+                    .and_then(|data| exif::Reader::new().read_raw(data.to_vec()).ok())
+                {
+                    metadata.push_exif(&exif);
+                }
+                if let Some(xmp) = document.resource(RESOURCE_XMP) {
+                    metadata.push_xmp(&String::from_utf8_lossy(xmp));
+                }
+                if let Some(iptc) = document.resource(RESOURCE_IPTC) {
+                    metadata.push_iptc(iptc);
+                }
+            }
+        } else {
+            if let Ok(exif) = exif::Reader::new().read_from_container(&mut Cursor::new(&bytes)) {
+                metadata.push_exif(&exif);
+            }
+            if let Some(xmp) = find_xmp_packet(&bytes) {
+                metadata.push_xmp(&xmp);
+            }
+            if let Some(iptc) = find_jpeg_iptc(&bytes) {
+                metadata.push_iptc(iptc);
+            }
+            if bytes.starts_with(PNG_SIGNATURE) {
+                metadata.push_png_text(&bytes);
+            }
+        }
+
+        debug!("メタデータの読み込み完了: {}項目", metadata.entries.len());
+        Ok(metadata)
+    }
+
+    /// 項目を追加する
+    fn push(&mut self, group: MetadataGroup, key: &str, value: String) {
+        let value = value.trim().to_string();
+        if !value.is_empty() {
+            self.entries.push(MetadataEntry {
+                group,
+                key: key.to_string(),
+                value,
+            });
+        }
+    }
+
+    /// EXIFの項目を追加する
+    fn push_exif(&mut self, exif: &exif::Exif) {
+        for (tag, label) in EXIF_FIELDS {
+            if let Some(field) = exif.get_field(*tag, In::PRIMARY) {
+                let value = field.display_value().with_unit(exif).to_string();
+                self.push(MetadataGroup::Exif, label, value.trim_matches('"').to_string());
+            }
+        }
+
+        let coordinate = |value_tag: Tag, ref_tag: Tag| -> Option<f64> {
+            let Value::Rational(ref parts) = exif.get_field(value_tag, In::PRIMARY)?.value else {
+                return None;
+            };
+            let degrees = parts.iter()
+                .zip([1.0, 60.0, 3600.0])
+                .map(|(part, divisor)| part.to_f64() / divisor)
+                .sum::<f64>();
+            let reference = exif.get_field(ref_tag, In::PRIMARY)?.display_value().to_string();
+            Some(if reference.contains('S') || reference.contains('W') { -degrees } else { degrees })
+        };
+        if let (Some(latitude), Some(longitude)) = (
+            coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef),
+            coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef),
+        ) {
+            self.push(MetadataGroup::Gps, "緯度経度", format!("{:.6}, {:.6}", latitude, longitude));
+        }
+        if let Some(field) = exif.get_field(Tag::GPSAltitude, In::PRIMARY) {
+            self.push(MetadataGroup::Gps, "高度", field.display_value().with_unit(exif).to_string());
+        }
+        if let Some(field) = exif.get_field(Tag::GPSDateStamp, In::PRIMARY) {
+            self.push(MetadataGroup::Gps, "日付", field.display_value().to_string().trim_matches('"').to_string());
+        }
+    }
+
+    /// XMPの項目を追加する
+    fn push_xmp(&mut self, packet: &str) {
+        for (name, label) in XMP_FIELDS {
+            if let Some(value) = xmp_property(packet, name) {
+                self.push(MetadataGroup::Xmp, label, value);
+            }
+        }
+    }
+
+    /// IPTC-IIMの項目を追加する
+    ///
+    /// # Arguments
+    /// * `data` - IPTC-NAAデータ（0x1Cで始まるデータセットの列）
+    fn push_iptc(&mut self, data: &[u8]) {
+        let mut values: Vec<(u8, String)> = Vec::new();
+        let mut pos = 0;
+        while pos + 5 <= data.len() && data[pos] == 0x1C {
+            let record = data[pos + 1];
+            let dataset = data[pos + 2];
+            let mut len = u16::from_be_bytes([data[pos + 3], data[pos + 4]]) as usize;
+            pos += 5;
+            // 最上位ビットが立っている場合は拡張データセットで、下位ビットが長さフィールドのバイト数を表す
+            if len & 0x8000 != 0 {
+                let Some(field) = data.get(pos..pos + (len & 0x7FFF)).filter(|field| field.len() <= 4) else { break };
+                len = field.iter().fold(0, |len, &b| (len << 8) | b as usize);
+                pos += field.len();
+            }
+            let Some(value) = pos.checked_add(len).and_then(|end| data.get(pos..end)) else { break };
+            if record == 2 {
+                values.push((dataset, String::from_utf8_lossy(value).into_owned()));
+            }
+            pos += len;
+        }
+
+        for (dataset, label) in IPTC_FIELDS {
+            let joined = values.iter()
+                .filter(|(d, _)| d == dataset)
+                .map(|(_, v)| v.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.push(MetadataGroup::Iptc, label, joined);
+        }
+    }
+
+    /// PNGのテキストチャンクを追加する
+    ///
+    /// # Arguments
+    /// * `bytes` - PNGファイル全体のバイト列
+    fn push_png_text(&mut self, bytes: &[u8]) {
+        let mut pos = PNG_SIGNATURE.len();
+        while pos + 8 <= bytes.len() {
+            let len = u32::from_be_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]]) as usize;
+            let chunk_type = &bytes[pos + 4..pos + 8];
+            // 長さがファイルの残りを超えるチャンクは壊れているため、そこで読み取りを終える
+            let Some(data) = (pos + 8).checked_add(len).and_then(|end| bytes.get(pos + 8..end)) else { break };
+            pos += 12 + len;
+
+            let entry = match chunk_type {
+                b"tEXt" => parse_text_chunk(data),
+                b"zTXt" => parse_ztxt_chunk(data),
+                b"iTXt" => parse_itxt_chunk(data),
+                b"IEND" => break,
+                _ => None,
+            };
+            // XMPはXMPとして表示済みのため除外する
+            if let Some((keyword, text)) = entry
+                && keyword != "XML:com.adobe.xmp"
+            {
+                self.push(MetadataGroup::PngText, &keyword, text);
+            }
+        }
+    }
+
+    /// メタデータが空かどうかを確認する
+    ///
+    /// # Returns
+    /// * `bool` - 項目がない場合は true
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 表示用の行リストを作成する
+    ///
+    /// 複数行の値は1行にまとめる
+    ///
+    /// # Returns
+    /// * `Vec<String>` - 「[種類] 項目名: 値」形式の行
+    pub fn lines(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let value = entry.value.split_whitespace().collect::<Vec<_>>().join(" ");
+                format!("[{}] {}: {}", entry.group.label(), entry.key, value)
+            })
+            .collect()
+    }
+
+    /// 書き出し用のテキストを作成する
+    ///
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    ///
+    /// # Returns
+    /// * `String` - 画像ファイル名を見出しにしたテキスト（値の改行は維持する）
+    pub fn to_text(&self, path: &Path) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "# {}", path.display());
+        if self.entries.is_empty() {
+            let _ = writeln!(text, "(メタデータなし)");
+        }
+        for entry in &self.entries {
+            let _ = writeln!(text, "[{}] {}: {}", entry.group.label(), entry.key, entry.value);
+        }
+        text
+    }
+}
+
+/// ファイル中のXMPパケットを探す
+///
+/// # Arguments
+/// * `bytes` - ファイル全体のバイト列
+///
+/// # Returns
+/// * `Option<String>` - XMPパケット
+fn find_xmp_packet(bytes: &[u8]) -> Option<String> {
+    let start = find_bytes(bytes, b"<x:xmpmeta")?;
+    let end_tag = b"</x:xmpmeta>";
+    let end = find_bytes(&bytes[start..], end_tag)? + start + end_tag.len();
+    Some(String::from_utf8_lossy(&bytes[start..end]).into_owned())
+}
+
+/// バイト列中の部分列の位置を探す
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// XMPパケットからプロパティの値を取り出す
+///
+/// 属性形式（`name="value"`）と要素形式（`<name>...</name>`）の両方に対応し、
+/// 要素形式で `rdf:li` を含む場合はそれらを連結する
+///
+/// # Arguments
+/// * `packet` - XMPパケット
+/// * `name` - 接頭辞付きのプロパティ名
+///
+/// # Returns
+/// * `Option<String>` - 値
+fn xmp_property(packet: &str, name: &str) -> Option<String> {
+    if let Some(value) = xmp_attribute(packet, name) {
+        return Some(value);
+    }
+
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let start = packet.find(&open)?;
+    let content_start = start + packet[start..].find('>')? + 1;
+    let content_end = content_start + packet[content_start..].find(&close)?;
+    let content = &packet[content_start..content_end];
+
+    let items: Vec<String> = content
+        .split("<rdf:li")
+        .skip(1)
+        .filter_map(|item| {
+            let text = &item[item.find('>')? + 1..];
+            Some(unescape_xml(&text[..text.find("</rdf:li>")?]))
+        })
+        .collect();
+    if items.is_empty() {
+        Some(unescape_xml(content.trim()))
+    } else {
+        Some(items.join(", "))
+    }
+}
+
+/// XMPパケットから属性形式のプロパティの値を取り出す
+///
+/// # Arguments
+/// * `packet` - XMPパケット
+/// * `name` - 接頭辞付きのプロパティ名
+///
+/// # Returns
+/// * `Option<String>` - 実体参照を展開した値（二重引用符と単一引用符のどちらで囲まれていてもよい）
+fn xmp_attribute(packet: &str, name: &str) -> Option<String> {
+    let mut search_from = 0;
+    while let Some(found) = packet[search_from..].find(name) {
+        let pos = search_from + found;
+        search_from = pos + name.len();
+        // 別の名前の一部（`xmp:Rating` に対する `MicrosoftPhoto:Rating` など）は対象外
+        if !packet[..pos].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let Some(after) = packet[search_from..].trim_start().strip_prefix('=') else { continue };
+        let after = after.trim_start();
+        let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else { continue };
+        let value = &after[1..];
+        return value.find(quote).map(|end| unescape_xml(&value[..end]));
+    }
+    None
+}
+
+/// JPEGのAPP13セグメントからIPTC-NAAデータを探す
+///
+/// # Arguments
+/// * `bytes` - JPEGファイル全体のバイト列
+///
+/// # Returns
+/// * `Option<&[u8]>` - IPTC-NAAデータ
+fn find_jpeg_iptc(bytes: &[u8]) -> Option<&[u8]> {
+    const PHOTOSHOP_HEADER: &[u8] = b"Photoshop 3.0\0";
+
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= bytes.len() && bytes[pos] == 0xFF {
+        let marker = bytes[pos + 1];
+        // マーカーの前の詰め物（0xFF）を読み飛ばす
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        // SOS以降は画像データのため探索を終える
+        if marker == 0xDA {
+            break;
+        }
+        let len = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let segment = bytes.get(pos + 4..pos + 2 + len)?;
+        if marker == 0xED && segment.starts_with(PHOTOSHOP_HEADER) {
+            return psd_loader::parse_resources(&segment[PHOTOSHOP_HEADER.len()..])
+                .into_iter()
+                .find(|resource| resource.id == RESOURCE_IPTC)
+                .map(|resource| resource.data);
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+/// tEXtチャンクを解析する（Latin-1）
+fn parse_text_chunk(data: &[u8]) -> Option<(String, String)> {
+    let separator = data.iter().position(|&b| b == 0)?;
+    let latin1 = |bytes: &[u8]| bytes.iter().map(|&b| b as char).collect::<String>();
+    Some((latin1(&data[..separator]), latin1(&data[separator + 1..])))
+}
+
+/// zTXtチャンクを解析する（zlib圧縮されたLatin-1）
+fn parse_ztxt_chunk(data: &[u8]) -> Option<(String, String)> {
+    let separator = data.iter().position(|&b| b == 0)?;
+    let keyword = data[..separator].iter().map(|&b| b as char).collect();
+    let text = inflate(data.get(separator + 2..)?)?;
+    Some((keyword, text.iter().map(|&b| b as char).collect()))
+}
+
+/// iTXtチャンクを解析する（UTF-8、圧縮されている場合もある）
+fn parse_itxt_chunk(data: &[u8]) -> Option<(String, String)> {
+    let separator = data.iter().position(|&b| b == 0)?;
+    let keyword = String::from_utf8_lossy(&data[..separator]).into_owned();
+    let compressed = *data.get(separator + 1)? == 1;
+    let rest = data.get(separator + 3..)?;
+    // 言語タグと翻訳済みキーワードを読み飛ばす
+    let language_end = rest.iter().position(|&b| b == 0)?;
+    let rest = &rest[language_end + 1..];
+    let translated_end = rest.iter().position(|&b| b == 0)?;
+    let text = &rest[translated_end + 1..];
+    let text = if compressed { inflate(text)? } else { text.to_vec() };
+    Some((keyword, String::from_utf8_lossy(&text).into_owned()))
+}
+
+/// zlib圧縮されたデータを展開する
+fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    ZlibDecoder::new(data).take(MAX_INFLATED_SIZE + 1).read_to_end(&mut output).ok()?;
+    (output.len() as u64 <= MAX_INFLATED_SIZE).then_some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+
+    fn png_chunk(chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(chunk_type);
+        chunk.extend_from_slice(data);
+        // 読み取りではCRCを検証しないため、値は何でもよい
+        chunk.extend_from_slice(&[0; 4]);
+        chunk
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn png_text(chunks: &[Vec<u8>]) -> Vec<(String, String)> {
+        let mut bytes = PNG_SIGNATURE.to_vec();
+        for chunk in chunks {
+            bytes.extend_from_slice(chunk);
+        }
+        let mut metadata = ImageMetadata::default();
+        metadata.push_png_text(&bytes);
+        metadata.entries.into_iter().map(|entry| (entry.key, entry.value)).collect()
+    }
+
+    #[test]
+    fn png_text_stops_at_truncated_or_oversized_chunks() {
+        let text = png_chunk(b"tEXt", b"Title\0First");
+        let mut truncated = png_chunk(b"tEXt", b"Comment\0Second");
+        truncated.truncate(truncated.len() - 8);
+        assert_eq!(png_text(&[text.clone(), truncated]), [("Title".to_string(), "First".to_string())]);
+
+        let mut oversized = png_chunk(b"tEXt", b"Comment\0Second");
+        oversized[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(png_text(&[text, oversized]), [("Title".to_string(), "First".to_string())]);
+    }
+
+    #[test]
+    fn png_text_reads_compressed_chunks() {
+        let mut ztxt = b"Comment\0\0".to_vec();
+        ztxt.extend_from_slice(&deflate(b"Latin-1 \xE9"));
+        let mut itxt = b"Description\0\x01\0ja\0\xE8\xAA\xAC\xE6\x98\x8E\0".to_vec();
+        itxt.extend_from_slice(&deflate("説明文".as_bytes()));
+        let plain = b"Author\0\0\0\0\0Name".to_vec();
+        assert_eq!(
+            png_text(&[png_chunk(b"zTXt", &ztxt), png_chunk(b"iTXt", &itxt), png_chunk(b"iTXt", &plain)]),
+            [
+                ("Comment".to_string(), "Latin-1 é".to_string()),
+                ("Description".to_string(), "説明文".to_string()),
+                ("Author".to_string(), "Name".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn png_text_skips_corrupt_compressed_text() {
+        let itxt = b"Description\0\x01\0\0\0not zlib".to_vec();
+        assert!(png_text(&[png_chunk(b"iTXt", &itxt)]).is_empty());
+    }
+
+    #[test]
+    fn xmp_property_reads_quoted_attributes_with_entities() {
+        let packet = r#"<rdf:Description MicrosoftPhoto:Rating="99" xmp:Rating = '4'
+            xmp:Label="Tom &amp; &quot;Jerry&quot;" xmp:CreatorTool='It&apos;s &#x41;&#66;'/>"#;
+        assert_eq!(xmp_property(packet, "xmp:Rating").as_deref(), Some("4"));
+        assert_eq!(xmp_property(packet, "xmp:Label").as_deref(), Some("Tom & \"Jerry\""));
+        assert_eq!(xmp_property(packet, "xmp:CreatorTool").as_deref(), Some("It's AB"));
+    }
+
+    #[test]
+    fn xmp_property_reads_element_lists() {
+        let packet = "<dc:subject><rdf:Bag><rdf:li>A &lt;B&gt;</rdf:li><rdf:li>C</rdf:li></rdf:Bag></dc:subject>";
+        assert_eq!(xmp_property(packet, "dc:subject").as_deref(), Some("A <B>, C"));
+    }
+
+    #[test]
+    fn iptc_reads_extended_datasets_and_stops_at_truncated_data() {
+        let mut data = vec![0x1C, 2, 5, 0, 5];
+        data.extend_from_slice(b"Title");
+        data.extend_from_slice(&[0x1C, 2, 120, 0x80, 2, 0, 4]);
+        data.extend_from_slice(b"Text");
+        data.extend_from_slice(&[0x1C, 2, 80, 0xFF, 0xFF]);
+        data.extend_from_slice(b"Truncated");
+        let mut metadata = ImageMetadata::default();
+        metadata.push_iptc(&data);
+        let entries: Vec<_> = metadata.entries.iter().map(|entry| (entry.key.as_str(), entry.value.as_str())).collect();
+        assert_eq!(entries, [("タイトル", "Title"), ("説明", "Text")]);
+    }
+
+    #[test]
+    fn find_jpeg_iptc_skips_fill_bytes_and_rejects_truncated_segments() {
+        let iptc = [0x1C, 2, 5, 0, 1, b'T'];
+        let mut segment = b"Photoshop 3.0\0".to_vec();
+        segment.extend_from_slice(b"8BIM");
+        segment.extend_from_slice(&RESOURCE_IPTC.to_be_bytes());
+        segment.extend_from_slice(&[0, 0]);
+        segment.extend_from_slice(&(iptc.len() as u32).to_be_bytes());
+        segment.extend_from_slice(&iptc);
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xFF, 0xED];
+        jpeg.extend_from_slice(&(segment.len() as u16 + 2).to_be_bytes());
+        jpeg.extend_from_slice(&segment);
+        assert_eq!(find_jpeg_iptc(&jpeg), Some(&iptc[..]));
+
+        jpeg.truncate(jpeg.len() - 4);
+        assert_eq!(find_jpeg_iptc(&jpeg), None);
+    }
+}
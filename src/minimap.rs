@@ -0,0 +1,134 @@
+use image::DynamicImage;
+use log::debug;
+use std::sync::Arc;
+use std::time::Instant;
+use crate::overlay::{OverlayRenderer, Rect};
+
+/// 縮小画像の長い辺の大きさ（ピクセル）
+const THUMBNAIL_SIZE: u32 = 256;
+/// ミニマップの長い辺の大きさ（論理ピクセル）
+const MINIMAP_SIZE: f32 = 160.0;
+/// ミニマップの背景色
+const PANEL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+/// 表示範囲の枠の色
+const VIEWPORT_COLOR: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+/// 表示範囲の外側を暗くする色
+const SHADE_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.45];
+
+/// 拡大表示中に画像全体と表示範囲を示すミニマップ
+///
+/// 画像全体の縮小画像の上に表示範囲の枠を描き、クリックやドラッグで表示位置を移動できる
+pub struct Minimap {
+    /// 縮小画像を作成した元画像
+    source: Option<Arc<DynamicImage>>,
+    /// GPUに転送した縮小画像
+    thumbnail: Option<wgpu::BindGroup>,
+    /// 直前に描画したミニマップの矩形（表示していない場合は None）
+    rect: Option<Rect>,
+}
+
+impl Minimap {
+    /// 新しい Minimap インスタンスを作成する
+    ///
+    /// # Returns
+    /// * `Minimap` - ミニマップ
+    pub fn new() -> Self {
+        Minimap {
+            source: None,
+            thumbnail: None,
+            rect: None,
+        }
+    }
+
+    /// 表示中の画像が変わった場合は縮小画像を作り直してGPUに転送する
+    ///
+    /// # Arguments
+    /// * `device` - WGPU デバイス
+    /// * `queue` - WGPU キュー
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `image` - 表示中の画像
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, overlay: &OverlayRenderer, image: &Arc<DynamicImage>) {
+        if self.source.as_ref().is_some_and(|source| Arc::ptr_eq(source, image)) {
+            return;
+        }
+        let started = Instant::now();
+        let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgba8();
+        debug!("ミニマップの縮小画像を作成しました: {}x{} ({:?})", thumbnail.width(), thumbnail.height(), started.elapsed());
+        self.thumbnail = Some(overlay.create_image_texture(device, queue, &thumbnail));
+        self.source = Some(image.clone());
+    }
+
+    /// ミニマップの大きさを取得する
+    ///
+    /// # Arguments
+    /// * `image_size` - 元画像の幅と高さ
+    /// * `scale_factor` - 表示倍率
+    ///
+    /// # Returns
+    /// * `(f32, f32)` - 幅と高さ（ピクセル、元画像のアスペクト比を保つ）
+    pub fn size((width, height): (u32, u32), scale_factor: f32) -> (f32, f32) {
+        let scale = MINIMAP_SIZE * scale_factor / width.max(height).max(1) as f32;
+        ((width as f32 * scale).round().max(1.0), (height as f32 * scale).round().max(1.0))
+    }
+
+    /// ミニマップを描画する
+    ///
+    /// # Arguments
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `rect` - 描画する矩形（ピクセル）
+    /// * `viewport` - 画像全体に対する表示範囲（0.0〜1.0 の割合）
+    /// * `scale_factor` - 表示倍率
+    pub fn draw(&mut self, overlay: &mut OverlayRenderer, rect: Rect, viewport: Rect, scale_factor: f32) {
+        let Some(thumbnail) = &self.thumbnail else {
+            self.rect = None;
+            return;
+        };
+        let border = scale_factor.round().max(1.0);
+        overlay.rect(Rect::new(rect.x - border, rect.y - border, rect.w + border * 2.0, rect.h + border * 2.0), PANEL_COLOR);
+        overlay.image(rect, thumbnail);
+
+        // 表示範囲の外側を暗くし、表示範囲に枠を描く
+        let view = Rect::new(
+            rect.x + viewport.x * rect.w,
+            rect.y + viewport.y * rect.h,
+            (viewport.w * rect.w).max(1.0),
+            (viewport.h * rect.h).max(1.0),
+        );
+        overlay.rect(Rect::new(rect.x, rect.y, rect.w, view.y - rect.y), SHADE_COLOR);
+        overlay.rect(Rect::new(rect.x, view.y + view.h, rect.w, rect.y + rect.h - view.y - view.h), SHADE_COLOR);
+        overlay.rect(Rect::new(rect.x, view.y, view.x - rect.x, view.h), SHADE_COLOR);
+        overlay.rect(Rect::new(view.x + view.w, view.y, rect.x + rect.w - view.x - view.w, view.h), SHADE_COLOR);
+        overlay.rect_outline(view, border, VIEWPORT_COLOR);
+        self.rect = Some(rect);
+    }
+
+    /// ミニマップを表示していない状態にする
+    pub fn hide(&mut self) {
+        self.rect = None;
+    }
+
+    /// 指定した位置がミニマップの上にあるかどうかを確認する
+    ///
+    /// # Arguments
+    /// * `x` - X座標
+    /// * `y` - Y座標
+    ///
+    /// # Returns
+    /// * `bool` - ミニマップの上の場合は true
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        self.rect.is_some_and(|rect| rect.contains(x, y))
+    }
+
+    /// 画面上の位置を画像全体に対する位置に変換する（ミニマップの外側は端の位置にする）
+    ///
+    /// # Arguments
+    /// * `x` - X座標
+    /// * `y` - Y座標
+    ///
+    /// # Returns
+    /// * `Option<(f32, f32)>` - 画像全体に対する位置（0.0〜1.0、ミニマップを表示していない場合は None）
+    pub fn image_position(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        let rect = self.rect?;
+        Some((((x - rect.x) / rect.w).clamp(0.0, 1.0), ((y - rect.y) / rect.h).clamp(0.0, 1.0)))
+    }
+}
@@ -0,0 +1,306 @@
+use anyhow::{Result, anyhow, bail};
+use image::{DynamicImage, ImageDecoder};
+use image::codecs::png::PngDecoder;
+use log::{debug, warn};
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// 統合画像のエントリ名（OpenRaster / Krita 共通）
+const MERGED_IMAGE_ENTRY: &str = "mergedimage.png";
+/// 1つのエントリから読み込む最大のバイト数（壊れたファイルで巨大な領域を確保しないための上限）
+const MAX_ENTRY_SIZE: u64 = 1 << 30;
+
+/// ZIPコンテナ形式のドキュメントの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerKind {
+    /// OpenRaster (.ora)
+    OpenRaster,
+    /// Krita (.kra)
+    Krita,
+}
+
+impl ContainerKind {
+    /// 拡張子からドキュメントの種類を判定する
+    ///
+    /// # Arguments
+    /// * `path` - ファイルのパス
+    ///
+    /// # Returns
+    /// * `Option<ContainerKind>` - 対応する種類。対象外の場合は None
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "ora" => Some(ContainerKind::OpenRaster),
+            "kra" => Some(ContainerKind::Krita),
+            _ => None,
+        }
+    }
+
+    /// サムネイルのエントリ名を取得する
+    fn thumbnail_entry(self) -> &'static str {
+        match self {
+            ContainerKind::OpenRaster => "Thumbnails/thumbnail.png",
+            ContainerKind::Krita => "preview.png",
+        }
+    }
+
+    /// レイヤー構造を記述したXMLのエントリ名を取得する
+    fn layer_stack_entry(self) -> &'static str {
+        match self {
+            ContainerKind::OpenRaster => "stack.xml",
+            ContainerKind::Krita => "maindoc.xml",
+        }
+    }
+}
+
+/// ZIPアーカイブ内のエントリを読み込む
+///
+/// ZIPヘッダーのサイズは信用せず、実際に読み込めた分だけ領域を確保する
+///
+/// # Arguments
+/// * `archive` - ZIPアーカイブ
+/// * `name` - エントリ名
+///
+/// # Returns
+/// * `Result<Vec<u8>>` - エントリの内容
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>> {
+    let entry = archive.by_name(name)?;
+    let mut data = Vec::new();
+    entry.take(MAX_ENTRY_SIZE + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > MAX_ENTRY_SIZE {
+        bail!("エントリが大きすぎます: {}（上限 {} バイト）", name, MAX_ENTRY_SIZE);
+    }
+    Ok(data)
+}
+
+/// OpenRaster / Krita ファイルの統合画像を読み込む
+///
+/// 統合画像が含まれていない場合はサムネイルにフォールバックする
+///
+/// # Arguments
+/// * `path` - ファイルのパス
+/// * `kind` - ドキュメントの種類
+///
+/// # Returns
+/// * `Result<(DynamicImage, Option<Vec<u8>>)>` - 読み込まれた画像と埋め込みICCプロファイル
+pub fn load_container_image(path: &Path, kind: ContainerKind) -> Result<(DynamicImage, Option<Vec<u8>>)> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+
+    match read_entry(&mut archive, MERGED_IMAGE_ENTRY) {
+        Ok(data) => {
+            let mut decoder = PngDecoder::new(Cursor::new(data))?;
+            let icc_profile = decoder.icc_profile().ok().flatten();
+            let img = DynamicImage::from_decoder(decoder)?;
+            debug!("{:?}の統合画像を展開: {}x{}", kind, img.width(), img.height());
+            return Ok((img, icc_profile));
+        }
+        Err(e) => warn!("{:?}の統合画像が見つからないため、サムネイルを使用します: {}", kind, e),
+    }
+
+    let data = read_entry(&mut archive, kind.thumbnail_entry())
+        .map_err(|e| anyhow!("{:?}に統合画像もサムネイルも含まれていません: {}", kind, e))?;
+    let img = image::load_from_memory_with_format(&data, image::ImageFormat::Png)?;
+    debug!("{:?}のサムネイルを展開: {}x{}", kind, img.width(), img.height());
+    Ok((img, None))
+}
+
+/// OpenRaster / Krita ファイルのレイヤー名一覧を取得する
+///
+/// # Arguments
+/// * `path` - ファイルのパス
+/// * `kind` - ドキュメントの種類
+///
+/// # Returns
+/// * `Result<Vec<String>>` - 上から順に並んだレイヤー名
+pub fn layer_names(path: &Path, kind: ContainerKind) -> Result<Vec<String>> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let xml = String::from_utf8(read_entry(&mut archive, kind.layer_stack_entry())?)?;
+    Ok(stack_layer_names(&xml))
+}
+
+/// OpenRaster / Krita ファイルの画像の幅と高さを取得する
+///
+/// 統合画像は展開せず、レイヤー構造のXMLに記述された大きさを読む
+///
+/// # Arguments
+/// * `path` - ファイルのパス
+/// * `kind` - ドキュメントの種類
+///
+/// # Returns
+/// * `Result<(u32, u32)>` - 幅と高さ
+pub fn dimensions(path: &Path, kind: ContainerKind) -> Result<(u32, u32)> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let xml = String::from_utf8(read_entry(&mut archive, kind.layer_stack_entry())?)?;
+    stack_dimensions(&xml, kind).ok_or_else(|| anyhow!("{:?}のXMLに画像の大きさが記述されていません", kind))
+}
+
+/// レイヤー構造のXMLから画像の幅と高さを取り出す
+///
+/// # Arguments
+/// * `xml` - stack.xml（Kritaの場合は maindoc.xml）の内容
+/// * `kind` - ドキュメントの種類
+///
+/// # Returns
+/// * `Option<(u32, u32)>` - 幅と高さ
+fn stack_dimensions(xml: &str, kind: ContainerKind) -> Option<(u32, u32)> {
+    let (element, width, height) = match kind {
+        ContainerKind::OpenRaster => ("image", "w", "h"),
+        ContainerKind::Krita => ("IMAGE", "width", "height"),
+    };
+    let tag = xml_elements(xml, element).next()?;
+    let width = xml_attribute(tag, width)?.trim().parse().ok()?;
+    let height = xml_attribute(tag, height)?.trim().parse().ok()?;
+    Some((width, height))
+}
+
+/// レイヤー構造のXMLからレイヤー名を取り出す
+///
+/// # Arguments
+/// * `xml` - stack.xml（Kritaの場合は maindoc.xml）の内容
+///
+/// # Returns
+/// * `Vec<String>` - 文書内の順に並んだレイヤー名
+fn stack_layer_names(xml: &str) -> Vec<String> {
+    xml_elements(xml, "layer")
+        .filter_map(|tag| xml_attribute(tag, "name"))
+        .collect()
+}
+
+/// XMLから指定した名前の要素の開始タグを列挙する
+///
+/// 属性値に `<` は含まれないため、`<` で区切った断片を開始タグとして扱う
+///
+/// # Arguments
+/// * `xml` - XML文字列
+/// * `element` - 要素名
+///
+/// # Returns
+/// * `impl Iterator<Item = &str>` - `<` と要素名を除いたタグの残りの部分
+fn xml_elements<'a>(xml: &'a str, element: &'a str) -> impl Iterator<Item = &'a str> {
+    xml.split('<').filter_map(move |tag| {
+        let rest = tag.strip_prefix(element)?;
+        rest.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/').then_some(rest)
+    })
+}
+
+/// XMLの開始タグから属性値を取り出す
+///
+/// 属性を先頭から順に読み進めるため、他の属性値の中にある `name=` や `>` は無視される
+///
+/// # Arguments
+/// * `tag` - 要素名より後ろのタグ文字列
+/// * `name` - 属性名
+///
+/// # Returns
+/// * `Option<String>` - 実体参照を展開した属性値
+fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag.trim_start();
+    while !rest.is_empty() && !rest.starts_with(['>', '/']) {
+        let name_end = rest.find(|c: char| c == '=' || c.is_whitespace())?;
+        let attribute = &rest[..name_end];
+        let after = rest[name_end..].trim_start().strip_prefix('=')?.trim_start();
+        let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value_len = after[1..].find(quote)?;
+        let value = &after[1..1 + value_len];
+        if attribute == name {
+            return Some(unescape_xml(value));
+        }
+        rest = after[value_len + 2..].trim_start();
+    }
+    None
+}
+
+/// XMLの実体参照と文字参照を展開する
+///
+/// # Arguments
+/// * `value` - 属性値またはテキスト
+///
+/// # Returns
+/// * `String` - 展開後の文字列（解釈できない参照はそのまま残す）
+pub fn unescape_xml(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find('&') {
+        output.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let decoded = rest.find(';').and_then(|end| {
+            let character = match &rest[1..end] {
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "amp" => '&',
+                reference => {
+                    let code = match reference.strip_prefix("#x").or_else(|| reference.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => reference.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((character, end))
+        });
+        match decoded {
+            Some((character, end)) => {
+                output.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_layer_names_reads_layers_in_document_order() {
+        let xml = r#"<?xml version="1.0"?>
+<image w="64" h="32"><stack>
+  <layer name="Top" src="data/1.png"/>
+  <layers><layer
+    name="Bottom"/></layers>
+  <layer-group name="Ignored"/>
+</stack></image>"#;
+        assert_eq!(stack_layer_names(xml), ["Top", "Bottom"]);
+    }
+
+    #[test]
+    fn stack_dimensions_reads_the_image_element() {
+        let ora = r#"<?xml version="1.0"?><image version="0.0.5" w="640" h="480"><stack/></image>"#;
+        assert_eq!(stack_dimensions(ora, ContainerKind::OpenRaster), Some((640, 480)));
+        let kra = r#"<DOC><IMAGE mime="application/x-kra" width="1200" name="a" height="900"><layers/></IMAGE></DOC>"#;
+        assert_eq!(stack_dimensions(kra, ContainerKind::Krita), Some((1200, 900)));
+        assert_eq!(stack_dimensions("<image w=\"1\"/>", ContainerKind::OpenRaster), None);
+    }
+
+    #[test]
+    fn xml_attribute_ignores_matches_inside_other_values() {
+        let tag = r#" src="a name='fake' > b" name="Real">"#;
+        assert_eq!(xml_attribute(tag, "name").as_deref(), Some("Real"));
+        assert_eq!(xml_attribute(r#" filename="x.png" name='y'"#, "name").as_deref(), Some("y"));
+        assert_eq!(xml_attribute(r#" src="x.png"/>"#, "name"), None);
+    }
+
+    #[test]
+    fn xml_attribute_handles_quotes_and_entities() {
+        let tag = r#" name="Say &quot;hi&quot; &amp; it's &lt;ok&gt;""#;
+        assert_eq!(xml_attribute(tag, "name").as_deref(), Some(r#"Say "hi" & it's <ok>"#));
+        let tag = r#" name='&#x3042;&#12354; "quoted" &amp;lt;'"#;
+        assert_eq!(xml_attribute(tag, "name").as_deref(), Some(r#"ああ "quoted" &lt;"#));
+        assert_eq!(xml_attribute(r#" name="a & b &unknown;""#, "name").as_deref(), Some("a & b &unknown;"));
+    }
+
+    #[test]
+    fn xml_attribute_rejects_unterminated_values() {
+        assert_eq!(xml_attribute(r#" name="never closed"#, "name"), None);
+        assert_eq!(xml_attribute(" name=unquoted", "name"), None);
+    }
+}
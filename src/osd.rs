@@ -0,0 +1,99 @@
+use image::{DynamicImage, ImageFormat};
+use std::path::Path;
+
+/// OSDに表示する画像の情報
+#[derive(Debug, Clone)]
+pub struct ImageInfo {
+    /// ファイル名
+    pub name: String,
+    /// 元画像の幅（テクスチャ転送時の縮小前）
+    pub width: u32,
+    /// 元画像の高さ（テクスチャ転送時の縮小前）
+    pub height: u32,
+    /// ファイルサイズ（バイト）
+    pub file_size: u64,
+    /// 画像フォーマットとピクセル形式
+    pub format: String,
+}
+
+impl ImageInfo {
+    /// 読み込んだ画像から情報を作成する
+    ///
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    /// * `name` - ファイル名
+    /// * `image` - 読み込んだ画像
+    ///
+    /// # Returns
+    /// * `ImageInfo` - 画像の情報
+    pub fn new(path: &Path, name: &str, image: &DynamicImage) -> Self {
+        let file_size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let format = match ImageFormat::from_path(path) {
+            Ok(format) => format!("{:?}", format).to_uppercase(),
+            Err(_) => path.extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_uppercase())
+                .unwrap_or_default(),
+        };
+        ImageInfo {
+            name: name.to_string(),
+            width: image.width(),
+            height: image.height(),
+            file_size,
+            format: format!("{} {:?}", format, image.color()),
+        }
+    }
+}
+
+/// OSDのテンプレートを展開する
+///
+/// 使用できるプレースホルダー: `{name}` `{index}` `{total}` `{width}` `{height}`
+/// `{size}` `{zoom}` `{format}`。`\n` で複数行に分けられる
+///
+/// # Arguments
+/// * `template` - テンプレート文字列
+/// * `info` - 画像の情報
+/// * `index` - 画像の番号（0始まり）
+/// * `total` - 画像の総数
+/// * `zoom` - 表示倍率（1.0 = 等倍）
+///
+/// # Returns
+/// * `Vec<String>` - 展開後の行
+pub fn expand_template(template: &str, info: &ImageInfo, index: usize, total: usize, zoom: f32) -> Vec<String> {
+    let expanded = template
+        .replace("{name}", &info.name)
+        .replace("{index}", &(index + 1).to_string())
+        .replace("{total}", &total.to_string())
+        .replace("{width}", &info.width.to_string())
+        .replace("{height}", &info.height.to_string())
+        .replace("{size}", &format_file_size(info.file_size))
+        .replace("{zoom}", &format!("{:.0}%", zoom * 100.0))
+        .replace("{format}", &info.format);
+    expanded
+        .replace("\\n", "\n")
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// ファイルサイズを読みやすい単位に変換する
+///
+/// # Arguments
+/// * `bytes` - バイト数
+///
+/// # Returns
+/// * `String` - 単位付きの文字列（例: "1.5 MB"）
+pub fn format_file_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
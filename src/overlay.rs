@@ -0,0 +1,817 @@
+use ab_glyph::{Font, FontArc, FontRef, FontVec, GlyphId, PxScale, ScaleFont, point};
+use image::RgbaImage;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// グリフアトラスの一辺のサイズ
+const ATLAS_SIZE: u32 = 1024;
+/// 矩形描画用に予約する白色領域のサイズ
+const WHITE_TEXEL_SIZE: u32 = 2;
+/// アトラス内のグリフ間の余白
+const GLYPH_PADDING: u32 = 1;
+
+/// 同梱フォントに含まれない文字（日本語など）のために優先して探すフォントファミリー
+const FALLBACK_FONT_FAMILIES: &[&str] = &[
+    "Meiryo",
+    "Yu Gothic",
+    "MS Gothic",
+    "Hiragino Sans",
+    "Hiragino Kaku Gothic ProN",
+    "Noto Sans CJK JP",
+    "Noto Sans JP",
+    "Source Han Sans JP",
+    "IPAexGothic",
+    "TakaoPGothic",
+];
+/// フォールバックフォントが日本語を表示できるかを確かめる文字
+const FALLBACK_PROBE_CHARS: [char; 2] = ['あ', '漢'];
+
+/// オーバーレイの頂点データ（位置はクリップ空間）
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct OverlayVertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+    color: [f32; 4],
+}
+
+impl OverlayVertex {
+    /// 頂点属性を取得する
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+            wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<OverlayVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// アトラスに格納されたグリフ
+#[derive(Debug, Clone, Copy)]
+struct CachedGlyph {
+    /// アトラス内の位置（ピクセル）
+    atlas_pos: (u32, u32),
+    /// グリフのサイズ（ピクセル）
+    size: (u32, u32),
+    /// ペン位置（ベースライン）からのオフセット
+    offset: (f32, f32),
+}
+
+/// グリフキャッシュのキー（フォント番号、グリフID、ピクセルサイズ）
+type GlyphKey = (usize, u16, u32);
+
+/// 画面上の矩形（ピクセル単位、左上原点）
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl Rect {
+    /// 新しい矩形を作成する
+    pub fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Rect { x, y, w, h }
+    }
+
+    /// 指定した点が矩形内にあるかどうかを確認する
+    ///
+    /// # Arguments
+    /// * `x` - X座標
+    /// * `y` - Y座標
+    ///
+    /// # Returns
+    /// * `bool` - 矩形内の場合は true
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.w && y >= self.y && y < self.y + self.h
+    }
+}
+
+/// 描画コマンドのまとまり（同じテクスチャを使う連続した頂点範囲）
+struct DrawBatch {
+    /// 使用するバインドグループ（None の場合はグリフアトラス）
+    bind_group: Option<wgpu::BindGroup>,
+    /// 頂点範囲
+    vertices: std::ops::Range<u32>,
+}
+
+/// 文字・矩形・テクスチャ付き矩形を描画するオーバーレイレンダラー
+///
+/// フレームごとに `begin` で頂点を破棄し、描画コマンドを積んでから
+/// `prepare` で転送、`draw` でレンダーパスに描画する
+pub struct OverlayRenderer {
+    /// レンダーパイプライン
+    pipeline: wgpu::RenderPipeline,
+    /// テクスチャのバインドグループレイアウト
+    bind_group_layout: wgpu::BindGroupLayout,
+    /// テクスチャのサンプラー
+    sampler: wgpu::Sampler,
+    /// グリフアトラスのテクスチャ
+    atlas_texture: wgpu::Texture,
+    /// グリフアトラスのバインドグループ
+    atlas_bind_group: wgpu::BindGroup,
+    /// 頂点バッファ
+    vertex_buffer: wgpu::Buffer,
+    /// 頂点バッファの容量（頂点数）
+    vertex_capacity: usize,
+    /// フォント（先頭が同梱フォント、以降が指定されたフォント。OSから探したフォントはこの後ろの番号で扱う）
+    fonts: Vec<FontArc>,
+    /// グリフキャッシュ
+    glyphs: HashMap<GlyphKey, CachedGlyph>,
+    /// アトラスの次の格納位置
+    atlas_cursor: (u32, u32),
+    /// アトラスの現在の行の高さ
+    atlas_row_height: u32,
+    /// アトラスが一杯になり、次のフレームで作り直す必要があるかどうか
+    atlas_full: bool,
+    /// 今フレームの頂点
+    vertices: Vec<OverlayVertex>,
+    /// 今フレームの描画バッチ
+    batches: Vec<DrawBatch>,
+    /// 画面サイズ（ピクセル）
+    screen_size: (f32, f32),
+    /// 表示倍率（HiDPI対応）
+    scale_factor: f32,
+}
+
+impl OverlayRenderer {
+    /// 新しい OverlayRenderer インスタンスを作成する
+    ///
+    /// # Arguments
+    /// * `device` - WGPU デバイス
+    /// * `queue` - WGPU キュー
+    /// * `surface_format` - 描画先サーフェスのフォーマット
+    /// * `font_path` - 追加で使用するフォントのパス（None の場合はOS標準の日本語フォントを探す）
+    ///
+    /// # Returns
+    /// * `OverlayRenderer` - オーバーレイレンダラー
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        surface_format: wgpu::TextureFormat,
+        font_path: Option<&Path>,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Overlay Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/overlay.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("overlay_bind_group_layout"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Overlay Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Overlay Pipeline"),
+            layout: Some(&pipeline_layout),
+            cache: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[OverlayVertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let atlas_texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: ATLAS_SIZE,
+                height: ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            label: Some("glyph_atlas"),
+            view_formats: &[],
+        });
+        let atlas_view = atlas_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let atlas_bind_group = Self::create_bind_group_with(device, &bind_group_layout, &sampler, &atlas_view);
+
+        let vertex_capacity = 4096;
+        let vertex_buffer = Self::create_vertex_buffer(device, vertex_capacity);
+
+        let mut renderer = OverlayRenderer {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            atlas_texture,
+            atlas_bind_group,
+            vertex_buffer,
+            vertex_capacity,
+            fonts: Self::load_fonts(font_path),
+            glyphs: HashMap::new(),
+            atlas_cursor: (0, 0),
+            atlas_row_height: 0,
+            atlas_full: false,
+            vertices: Vec::new(),
+            batches: Vec::new(),
+            screen_size: (1.0, 1.0),
+            scale_factor: 1.0,
+        };
+        renderer.reset_atlas(queue);
+        renderer
+    }
+
+    /// 同梱フォントと、設定で指定されたフォントを読み込む
+    ///
+    /// # Arguments
+    /// * `font_path` - 追加で使用するフォントのパス
+    ///
+    /// # Returns
+    /// * `Vec<FontArc>` - 使用するフォントのリスト
+    fn load_fonts(font_path: Option<&Path>) -> Vec<FontArc> {
+        let mut fonts = vec![
+            FontArc::try_from_slice(epaint_default_fonts::HACK_REGULAR).expect("同梱フォントの読み込みに失敗"),
+        ];
+
+        if let Some(path) = font_path {
+            match std::fs::read(path).map_err(anyhow::Error::from).and_then(|bytes| {
+                FontVec::try_from_vec_and_index(bytes, 0).map_err(anyhow::Error::from)
+            }) {
+                Ok(font) => {
+                    info!("フォールバックフォントを読み込みました: {:?}", path);
+                    fonts.push(FontArc::new(font));
+                }
+                Err(e) => warn!("フォントの読み込みに失敗: {:?} ({})", path, e),
+            }
+        }
+        fonts
+    }
+
+    /// 頂点バッファを作成する
+    fn create_vertex_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Overlay Vertex Buffer"),
+            size: (capacity * std::mem::size_of::<OverlayVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// テクスチャビューからオーバーレイ描画用のバインドグループを作成する
+    fn create_bind_group_with(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+            label: Some("overlay_bind_group"),
+        })
+    }
+
+    /// 外部のテクスチャを描画するためのバインドグループを作成する
+    ///
+    /// # Arguments
+    /// * `device` - WGPU デバイス
+    /// * `view` - 描画するテクスチャのビュー
+    ///
+    /// # Returns
+    /// * `wgpu::BindGroup` - `image` に渡すバインドグループ
+    pub fn create_image_bind_group(&self, device: &wgpu::Device, view: &wgpu::TextureView) -> wgpu::BindGroup {
+        Self::create_bind_group_with(device, &self.bind_group_layout, &self.sampler, view)
+    }
+
+    /// 8bit sRGBの画像をテクスチャとしてGPUに転送し、描画用のバインドグループを作成する
+    ///
+    /// # Arguments
+    /// * `device` - WGPU デバイス
+    /// * `queue` - WGPU キュー
+    /// * `image` - 転送する画像
+    ///
+    /// # Returns
+    /// * `wgpu::BindGroup` - `image` に渡すバインドグループ
+    pub fn create_image_texture(&self, device: &wgpu::Device, queue: &wgpu::Queue, image: &RgbaImage) -> wgpu::BindGroup {
+        let (width, height) = image.dimensions();
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            label: Some("overlay_image_texture"),
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            image.as_raw(),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.create_image_bind_group(device, &view)
+    }
+
+    /// グリフアトラスを初期化し、矩形描画用の白色領域を書き込む
+    fn reset_atlas(&mut self, queue: &wgpu::Queue) {
+        self.glyphs.clear();
+        self.atlas_cursor = (WHITE_TEXEL_SIZE + GLYPH_PADDING, 0);
+        self.atlas_row_height = WHITE_TEXEL_SIZE;
+        self.atlas_full = false;
+
+        let white = vec![255u8; (WHITE_TEXEL_SIZE * WHITE_TEXEL_SIZE * 4) as usize];
+        self.write_atlas(queue, (0, 0), (WHITE_TEXEL_SIZE, WHITE_TEXEL_SIZE), &white);
+        debug!("グリフアトラスを初期化しました");
+    }
+
+    /// グリフアトラスの一部を書き換える
+    fn write_atlas(&self, queue: &wgpu::Queue, pos: (u32, u32), size: (u32, u32), rgba: &[u8]) {
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.atlas_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: pos.0, y: pos.1, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * size.0),
+                rows_per_image: Some(size.1),
+            },
+            wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// フレームの描画を開始する
+    ///
+    /// # Arguments
+    /// * `queue` - WGPU キュー
+    /// * `screen_size` - 画面サイズ（ピクセル）
+    /// * `scale_factor` - 表示倍率
+    pub fn begin(&mut self, queue: &wgpu::Queue, screen_size: (u32, u32), scale_factor: f32) {
+        if self.atlas_full {
+            self.reset_atlas(queue);
+        }
+        self.vertices.clear();
+        self.batches.clear();
+        self.screen_size = (screen_size.0.max(1) as f32, screen_size.1.max(1) as f32);
+        self.scale_factor = scale_factor;
+    }
+
+    /// ピクセル座標の矩形とUV座標の矩形から頂点を追加する
+    fn push_quad(&mut self, rect: Rect, uv: Rect, color: [f32; 4], bind_group: Option<&wgpu::BindGroup>) {
+        let (sw, sh) = self.screen_size;
+        let to_ndc = |x: f32, y: f32| [x / sw * 2.0 - 1.0, 1.0 - y / sh * 2.0];
+        let corners = [
+            (rect.x, rect.y, uv.x, uv.y),
+            (rect.x + rect.w, rect.y, uv.x + uv.w, uv.y),
+            (rect.x + rect.w, rect.y + rect.h, uv.x + uv.w, uv.y + uv.h),
+            (rect.x, rect.y + rect.h, uv.x, uv.y + uv.h),
+        ];
+        let start = self.vertices.len() as u32;
+        for index in [0, 1, 2, 2, 3, 0] {
+            let (x, y, u, v) = corners[index];
+            self.vertices.push(OverlayVertex {
+                position: to_ndc(x, y),
+                tex_coords: [u, v],
+                color,
+            });
+        }
+        self.extend_batch(start, bind_group);
+    }
+
+    /// 直前のバッチと同じテクスチャなら頂点範囲を延長し、異なれば新しいバッチを作る
+    fn extend_batch(&mut self, start: u32, bind_group: Option<&wgpu::BindGroup>) {
+        let end = self.vertices.len() as u32;
+        if let Some(last) = self.batches.last_mut()
+            && last.bind_group.as_ref() == bind_group
+            && last.vertices.end == start
+        {
+            last.vertices.end = end;
+            return;
+        }
+        self.batches.push(DrawBatch {
+            bind_group: bind_group.cloned(),
+            vertices: start..end,
+        });
+    }
+
+    /// 塗りつぶした矩形を描画する
+    ///
+    /// # Arguments
+    /// * `rect` - 描画する矩形（ピクセル）
+    /// * `color` - 色（リニアRGBA）
+    pub fn rect(&mut self, rect: Rect, color: [f32; 4]) {
+        let texel = 1.0 / ATLAS_SIZE as f32;
+        self.push_quad(rect, Rect::new(texel * 0.5, texel * 0.5, texel, texel), color, None);
+    }
+
+    /// 枠線だけの矩形を描画する
+    ///
+    /// # Arguments
+    /// * `rect` - 描画する矩形（ピクセル）
+    /// * `thickness` - 線の太さ（ピクセル）
+    /// * `color` - 色（リニアRGBA）
+    pub fn rect_outline(&mut self, rect: Rect, thickness: f32, color: [f32; 4]) {
+        let t = thickness;
+        self.rect(Rect::new(rect.x, rect.y, rect.w, t), color);
+        self.rect(Rect::new(rect.x, rect.y + rect.h - t, rect.w, t), color);
+        self.rect(Rect::new(rect.x, rect.y + t, t, rect.h - 2.0 * t), color);
+        self.rect(Rect::new(rect.x + rect.w - t, rect.y + t, t, rect.h - 2.0 * t), color);
+    }
+
+    /// テクスチャを矩形に描画する
+    ///
+    /// # Arguments
+    /// * `rect` - 描画する矩形（ピクセル）
+    /// * `bind_group` - `create_image_bind_group` で作成したバインドグループ
+    pub fn image(&mut self, rect: Rect, bind_group: &wgpu::BindGroup) {
+        self.push_quad(rect, Rect::new(0.0, 0.0, 1.0, 1.0), [1.0, 1.0, 1.0, 1.0], Some(bind_group));
+    }
+
+    /// 文字列の描画に使うフォントとグリフIDを選ぶ
+    fn select_glyph(&self, c: char) -> (usize, GlyphId) {
+        for (index, font) in self.fonts.iter().enumerate() {
+            let id = font.glyph_id(c);
+            if id.0 != 0 {
+                return (index, id);
+            }
+        }
+        if let Some(font) = system_fallback_font() {
+            let id = font.glyph_id(c);
+            if id.0 != 0 {
+                return (self.fonts.len(), id);
+            }
+        }
+        (0, self.fonts[0].glyph_id(c))
+    }
+
+    /// `select_glyph` が返した番号のフォントを取得する
+    fn font(&self, index: usize) -> &FontArc {
+        self.fonts.get(index)
+            .or_else(|| system_fallback_font())
+            .unwrap_or(&self.fonts[0])
+    }
+
+    /// 1行の高さを取得する
+    ///
+    /// # Arguments
+    /// * `size` - 文字サイズ（論理ピクセル）
+    ///
+    /// # Returns
+    /// * `f32` - 行の高さ（ピクセル）
+    pub fn line_height(&self, size: f32) -> f32 {
+        let scaled = self.fonts[0].as_scaled(PxScale::from(size * self.scale_factor));
+        (scaled.ascent() - scaled.descent() + scaled.line_gap()).ceil()
+    }
+
+    /// 文字列の描画幅を計測する
+    ///
+    /// # Arguments
+    /// * `size` - 文字サイズ（論理ピクセル）
+    /// * `text` - 計測する文字列（1行）
+    ///
+    /// # Returns
+    /// * `f32` - 描画幅（ピクセル）
+    pub fn measure(&self, size: f32, text: &str) -> f32 {
+        let px = PxScale::from(size * self.scale_factor);
+        text.chars()
+            .map(|c| {
+                let (font_index, id) = self.select_glyph(c);
+                self.font(font_index).as_scaled(px).h_advance(id)
+            })
+            .sum()
+    }
+
+    /// グリフをアトラスに格納する（格納済みの場合はキャッシュを返す）
+    fn cache_glyph(&mut self, queue: &wgpu::Queue, font_index: usize, id: GlyphId, px: u32) -> Option<CachedGlyph> {
+        let key = (font_index, id.0, px);
+        if let Some(glyph) = self.glyphs.get(&key) {
+            return Some(*glyph);
+        }
+
+        let font = self.font(font_index).clone();
+        let outlined = font.outline_glyph(id.with_scale_and_position(px as f32, point(0.0, 0.0)))?;
+        let bounds = outlined.px_bounds();
+        let width = bounds.width().ceil() as u32;
+        let height = bounds.height().ceil() as u32;
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        // シェルフ方式でアトラス内の位置を確保する
+        if self.atlas_cursor.0 + width + GLYPH_PADDING > ATLAS_SIZE {
+            self.atlas_cursor = (0, self.atlas_cursor.1 + self.atlas_row_height + GLYPH_PADDING);
+            self.atlas_row_height = 0;
+        }
+        if self.atlas_cursor.1 + height > ATLAS_SIZE || width > ATLAS_SIZE {
+            self.atlas_full = true;
+            return None;
+        }
+        let pos = self.atlas_cursor;
+        self.atlas_cursor.0 += width + GLYPH_PADDING;
+        self.atlas_row_height = self.atlas_row_height.max(height);
+
+        let mut rgba = vec![255u8; (width * height * 4) as usize];
+        for alpha in rgba.iter_mut().skip(3).step_by(4) {
+            *alpha = 0;
+        }
+        outlined.draw(|x, y, coverage| {
+            if x < width && y < height {
+                rgba[((y * width + x) * 4 + 3) as usize] = (coverage.clamp(0.0, 1.0) * 255.0) as u8;
+            }
+        });
+        self.write_atlas(queue, pos, (width, height), &rgba);
+
+        let glyph = CachedGlyph {
+            atlas_pos: pos,
+            size: (width, height),
+            offset: (bounds.min.x, bounds.min.y),
+        };
+        self.glyphs.insert(key, glyph);
+        Some(glyph)
+    }
+
+    /// 文字列を描画する
+    ///
+    /// # Arguments
+    /// * `queue` - WGPU キュー
+    /// * `x` - 左端のX座標（ピクセル）
+    /// * `y` - 上端のY座標（ピクセル）
+    /// * `size` - 文字サイズ（論理ピクセル）
+    /// * `color` - 文字色（リニアRGBA）
+    /// * `text` - 描画する文字列（1行）
+    /// * `max_width` - 描画幅の上限（超えた分は描画しない）
+    ///
+    /// # Returns
+    /// * `f32` - 描画した幅（ピクセル）
+    #[allow(clippy::too_many_arguments)]
+    pub fn text(
+        &mut self,
+        queue: &wgpu::Queue,
+        x: f32,
+        y: f32,
+        size: f32,
+        color: [f32; 4],
+        text: &str,
+        max_width: Option<f32>,
+    ) -> f32 {
+        let px = (size * self.scale_factor).round().max(1.0) as u32;
+        let ascent = self.fonts[0].as_scaled(PxScale::from(px as f32)).ascent();
+        let baseline = (y + ascent).round();
+        let mut caret = x.round();
+        let texel = 1.0 / ATLAS_SIZE as f32;
+
+        for c in text.chars() {
+            let (font_index, id) = self.select_glyph(c);
+            let advance = self.font(font_index).as_scaled(PxScale::from(px as f32)).h_advance(id);
+            if max_width.is_some_and(|max| caret + advance - x > max) {
+                break;
+            }
+            if let Some(glyph) = self.cache_glyph(queue, font_index, id, px) {
+                let rect = Rect::new(
+                    caret + glyph.offset.0,
+                    baseline + glyph.offset.1,
+                    glyph.size.0 as f32,
+                    glyph.size.1 as f32,
+                );
+                let uv = Rect::new(
+                    glyph.atlas_pos.0 as f32 * texel,
+                    glyph.atlas_pos.1 as f32 * texel,
+                    glyph.size.0 as f32 * texel,
+                    glyph.size.1 as f32 * texel,
+                );
+                self.push_quad(rect, uv, color, None);
+            }
+            caret += advance;
+        }
+        caret - x
+    }
+
+    /// 背景付きで複数行の文字列を描画する
+    ///
+    /// # Arguments
+    /// * `queue` - WGPU キュー
+    /// * `x` - 左端のX座標（ピクセル）
+    /// * `y` - 上端のY座標（ピクセル）
+    /// * `size` - 文字サイズ（論理ピクセル）
+    /// * `lines` - 描画する行
+    /// * `max_size` - パネルの最大サイズ（ピクセル、収まらない行は省略する）
+    ///
+    /// # Returns
+    /// * `Rect` - 描画したパネルの矩形
+    pub fn text_panel(
+        &mut self,
+        queue: &wgpu::Queue,
+        x: f32,
+        y: f32,
+        size: f32,
+        lines: &[String],
+        max_size: (f32, f32),
+    ) -> Rect {
+        let panel = self.panel_rect(x, y, size, lines, max_size);
+        let padding = self.panel_padding(size);
+        let line_height = self.line_height(size);
+
+        self.rect(panel, [0.0, 0.0, 0.0, 0.6]);
+        let mut line_y = panel.y + padding;
+        for line in lines {
+            if line_y + line_height > panel.y + panel.h - padding + 0.5 {
+                break;
+            }
+            self.text(queue, panel.x + padding, line_y, size, [1.0, 1.0, 1.0, 1.0], line, Some(panel.w - padding * 2.0));
+            line_y += line_height;
+        }
+        panel
+    }
+
+    /// パネルの内側の余白を取得する
+    fn panel_padding(&self, size: f32) -> f32 {
+        (size * 0.5 * self.scale_factor).round()
+    }
+
+    /// 複数行の文字列を収めるパネルの矩形を計算する
+    ///
+    /// # Arguments
+    /// * `x` - 左端のX座標（ピクセル）
+    /// * `y` - 上端のY座標（ピクセル）
+    /// * `size` - 文字サイズ（論理ピクセル）
+    /// * `lines` - 描画する行
+    /// * `max_size` - パネルの最大サイズ（ピクセル）
+    ///
+    /// # Returns
+    /// * `Rect` - パネルの矩形
+    pub fn panel_rect(&self, x: f32, y: f32, size: f32, lines: &[String], max_size: (f32, f32)) -> Rect {
+        let padding = self.panel_padding(size);
+        let line_height = self.line_height(size);
+        let text_width = lines.iter().map(|line| self.measure(size, line)).fold(0.0, f32::max);
+        let width = (text_width + padding * 2.0).min(max_size.0).ceil();
+        let height = (line_height * lines.len() as f32 + padding * 2.0).min(max_size.1).ceil();
+        Rect::new(x, y, width, height)
+    }
+
+    /// フレームの頂点データをGPUに転送する
+    ///
+    /// # Arguments
+    /// * `device` - WGPU デバイス
+    /// * `queue` - WGPU キュー
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        if self.vertices.len() > self.vertex_capacity {
+            self.vertex_capacity = self.vertices.len().next_power_of_two();
+            self.vertex_buffer = Self::create_vertex_buffer(device, self.vertex_capacity);
+        }
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
+    }
+
+    /// 積まれた描画コマンドをレンダーパスに描画する
+    ///
+    /// # Arguments
+    /// * `render_pass` - 描画先のレンダーパス
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        for batch in &self.batches {
+            let bind_group = batch.bind_group.as_ref().unwrap_or(&self.atlas_bind_group);
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.draw(batch.vertices.clone(), 0..1);
+        }
+    }
+}
+
+/// OSのフォント設定から見つけた、日本語を表示できるフォールバックフォントを取得する
+///
+/// フォントの列挙には時間がかかるため、同梱フォントにない文字を最初に描画するときに一度だけ探す
+///
+/// # Returns
+/// * `Option<&'static FontArc>` - フォールバックフォント。見つからない場合は None
+fn system_fallback_font() -> Option<&'static FontArc> {
+    static FONT: OnceLock<Option<FontArc>> = OnceLock::new();
+    FONT.get_or_init(find_system_fallback_font).as_ref()
+}
+
+/// OSのフォント設定（Linuxではfontconfig）に登録されたフォントから、日本語を表示できるフォントを探す
+///
+/// よく使われる日本語フォントを優先し、見つからない場合は日本語のグリフを持つ最初のフォントを使う
+///
+/// # Returns
+/// * `Option<FontArc>` - 見つかったフォント
+fn find_system_fallback_font() -> Option<FontArc> {
+    let started = Instant::now();
+    let mut database = fontdb::Database::new();
+    database.load_system_fonts();
+
+    let preferred = FALLBACK_FONT_FAMILIES.iter().filter_map(|family| {
+        database.query(&fontdb::Query {
+            families: &[fontdb::Family::Name(family)],
+            ..fontdb::Query::default()
+        })
+    });
+    let found = preferred.chain(database.faces().map(|face| face.id)).find_map(|id| {
+        let font = database.with_face_data(id, |data, index| {
+            let font = FontRef::try_from_slice_and_index(data, index).ok()?;
+            if FALLBACK_PROBE_CHARS.iter().any(|&c| font.glyph_id(c).0 == 0) {
+                return None;
+            }
+            FontVec::try_from_vec_and_index(data.to_vec(), index).ok()
+        })??;
+        Some((id, font))
+    });
+
+    match found {
+        Some((id, font)) => {
+            let name = database.face(id).and_then(|face| face.families.first()).map(|(name, _)| name.as_str());
+            info!("OSのフォールバックフォントを使用します: {} ({:?})", name.unwrap_or("?"), started.elapsed());
+            Some(FontArc::new(font))
+        }
+        None => {
+            warn!("日本語を表示できるフォントが見つかりません（{}個のフォントを確認, {:?}）", database.len(), started.elapsed());
+            None
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use image::{DynamicImage, GenericImageView, Pixel, RgbaImage};
+
+/// カーソルの下にあるピクセルの値
+///
+/// 値は画像ファイルに保存されている値（SDR画像はsRGBエンコードされた値、HDR画像はリニアの値）を
+/// 0.0〜1.0 に正規化したもの
+#[derive(Debug, Clone)]
+pub struct PixelSample {
+    /// 元画像上のX座標
+    pub x: u32,
+    /// 元画像上のY座標
+    pub y: u32,
+    /// RGBAの値
+    pub rgba: [f32; 4],
+}
+
+impl PixelSample {
+    /// 画像から指定した座標のピクセルの値を読み取る
+    ///
+    /// # Arguments
+    /// * `image` - 読み込んだ画像
+    /// * `x` - X座標
+    /// * `y` - Y座標
+    ///
+    /// # Returns
+    /// * `Option<PixelSample>` - ピクセルの値（座標が画像の範囲外の場合は None）
+    pub fn read(image: &DynamicImage, x: u32, y: u32) -> Option<Self> {
+        if !image.in_bounds(x, y) {
+            return None;
+        }
+        let normalize_u16 = |pixel: image::Rgba<u16>| pixel.0.map(|v| v as f32 / 65535.0);
+        let rgba = match image {
+            DynamicImage::ImageRgb32F(buffer) => buffer.get_pixel(x, y).to_rgba().0,
+            DynamicImage::ImageRgba32F(buffer) => buffer.get_pixel(x, y).0,
+            DynamicImage::ImageLuma16(buffer) => normalize_u16(buffer.get_pixel(x, y).to_rgba()),
+            DynamicImage::ImageLumaA16(buffer) => normalize_u16(buffer.get_pixel(x, y).to_rgba()),
+            DynamicImage::ImageRgb16(buffer) => normalize_u16(buffer.get_pixel(x, y).to_rgba()),
+            DynamicImage::ImageRgba16(buffer) => normalize_u16(buffer.get_pixel(x, y).to_rgba()),
+            _ => image.get_pixel(x, y).0.map(|v| v as f32 / 255.0),
+        };
+        Some(PixelSample { x, y, rgba })
+    }
+
+    /// アニメーションのフレームから指定した座標のピクセルの値を読み取る
+    ///
+    /// # Arguments
+    /// * `frame` - 表示中のフレーム
+    /// * `x` - X座標
+    /// * `y` - Y座標
+    ///
+    /// # Returns
+    /// * `Option<PixelSample>` - ピクセルの値（座標がフレームの範囲外の場合は None）
+    pub fn read_frame(frame: &RgbaImage, x: u32, y: u32) -> Option<Self> {
+        let pixel = frame.get_pixel_checked(x, y)?;
+        Some(PixelSample { x, y, rgba: pixel.0.map(|v| v as f32 / 255.0) })
+    }
+
+    /// 8bitに換算した値を取得する
+    ///
+    /// # Returns
+    /// * `[u8; 4]` - RGBAの値（0〜255）
+    pub fn rgba8(&self) -> [u8; 4] {
+        self.rgba.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    /// HSVに変換した値を取得する
+    ///
+    /// # Returns
+    /// * `(f32, f32, f32)` - 色相（度）、彩度（0.0〜1.0）、明度（HDR画像では1.0を超えることがある）
+    pub fn hsv(&self) -> (f32, f32, f32) {
+        let [r, g, b, _] = self.rgba;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let hue = if delta <= 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let saturation = if max > 0.0 { delta / max } else { 0.0 };
+        (hue, saturation, max)
+    }
+
+    /// 16進数のカラーコードを取得する
+    ///
+    /// # Returns
+    /// * `String` - `#RRGGBBAA` 形式のカラーコード
+    pub fn hex(&self) -> String {
+        let [r, g, b, a] = self.rgba8();
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+
+    /// ピクセルインスペクターに表示する行を作成する
+    ///
+    /// # Returns
+    /// * `Vec<String>` - 座標と、8bit・浮動小数点・16進数・HSVの値
+    pub fn lines(&self) -> Vec<String> {
+        let [r8, g8, b8, a8] = self.rgba8();
+        let [r, g, b, a] = self.rgba;
+        let (h, s, v) = self.hsv();
+        vec![
+            format!("座標: ({}, {})", self.x, self.y),
+            format!("8bit: {} {} {} {}", r8, g8, b8, a8),
+            format!("float: {:.4} {:.4} {:.4} {:.4}", r, g, b, a),
+            format!("Hex: {}", self.hex()),
+            format!("HSV: {:.1}° {:.1}% {:.1}%", h, s * 100.0, v * 100.0),
+        ]
+    }
+
+    /// 標準出力に書き出す1行のテキストを作成する
+    ///
+    /// # Arguments
+    /// * `name` - 画像のファイル名
+    ///
+    /// # Returns
+    /// * `String` - ファイル名・座標・各形式の値をまとめた1行
+    pub fn to_text(&self, name: &str) -> String {
+        let [r8, g8, b8, a8] = self.rgba8();
+        let [r, g, b, a] = self.rgba;
+        let (h, s, v) = self.hsv();
+        format!(
+            "{} ({}, {}) rgba8({}, {}, {}, {}) float({:.4}, {:.4}, {:.4}, {:.4}) {} hsv({:.1}, {:.1}%, {:.1}%)",
+            name, self.x, self.y, r8, g8, b8, a8, r, g, b, a, self.hex(), h, s * 100.0, v * 100.0
+        )
+    }
+}
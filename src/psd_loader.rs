@@ -0,0 +1,966 @@
+use anyhow::{Result, anyhow, bail};
+use image::{DynamicImage, ImageBuffer, Rgba};
+use log::{debug, warn};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use crate::color_management::linear_to_srgb;
+
+/// PSD/PSBファイルのシグネチャ
+const PSD_SIGNATURE: &[u8; 4] = b"8BPS";
+/// 画像リソースブロックのシグネチャ
+const RESOURCE_SIGNATURE: &[u8; 4] = b"8BIM";
+/// サムネイルリソース（Photoshop 5.0以降、RGB順）
+const RESOURCE_THUMBNAIL: u16 = 1036;
+/// サムネイルリソース（Photoshop 4.0、BGR順）
+const RESOURCE_THUMBNAIL_LEGACY: u16 = 1033;
+/// バージョン情報リソース（統合画像の有無を含む）
+const RESOURCE_VERSION_INFO: u16 = 1057;
+/// ICCプロファイルリソース
+const RESOURCE_ICC_PROFILE: u16 = 1039;
+/// PSBで長さが64bitで格納される追加レイヤー情報のキー
+const PSB_LARGE_BLOCK_KEYS: [&[u8]; 13] = [
+    b"LMsk", b"Lr16", b"Lr32", b"Layr", b"Mt16", b"Mt32", b"Mtrn",
+    b"Alph", b"FMsk", b"lnk2", b"FEid", b"FXid", b"PxSD",
+];
+/// PSDの幅・高さの上限（仕様上の最大値）
+const MAX_PSD_DIMENSION: u32 = 30_000;
+/// PSBの幅・高さの上限（仕様上の最大値）
+const MAX_PSB_DIMENSION: u32 = 300_000;
+/// チャンネル数の上限（仕様上の最大値）
+const MAX_CHANNELS: usize = 56;
+/// 展開後の画像の最大バイト数（壊れたファイルで巨大な領域を確保しないための上限）
+const MAX_DECODED_SIZE: u64 = 1 << 32;
+
+/// PSDのカラーモード
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorMode {
+    Bitmap,
+    Grayscale,
+    Indexed,
+    Rgb,
+    Cmyk,
+    Multichannel,
+    Duotone,
+    Lab,
+}
+
+impl ColorMode {
+    /// ヘッダーの値からカラーモードを取得する
+    ///
+    /// # Arguments
+    /// * `value` - ヘッダーに格納されたカラーモード値
+    ///
+    /// # Returns
+    /// * `Result<ColorMode>` - カラーモード
+    fn from_u16(value: u16) -> Result<Self> {
+        match value {
+            0 => Ok(ColorMode::Bitmap),
+            1 => Ok(ColorMode::Grayscale),
+            2 => Ok(ColorMode::Indexed),
+            3 => Ok(ColorMode::Rgb),
+            4 => Ok(ColorMode::Cmyk),
+            7 => Ok(ColorMode::Multichannel),
+            8 => Ok(ColorMode::Duotone),
+            9 => Ok(ColorMode::Lab),
+            _ => bail!("未対応のカラーモードです: {}", value),
+        }
+    }
+
+    /// カラー情報を構成するチャンネル数を取得する
+    ///
+    /// # Returns
+    /// * `usize` - アルファを除いたチャンネル数
+    fn color_channels(self) -> usize {
+        match self {
+            ColorMode::Rgb | ColorMode::Lab => 3,
+            ColorMode::Cmyk => 4,
+            _ => 1,
+        }
+    }
+}
+
+/// ビッグエンディアンのバイト列を順に読み込むリーダー
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("PSDファイルが途中で終了しています (offset {})", self.pos))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let b = self.take(8)?;
+        Ok(u64::from_be_bytes(b.try_into()?))
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+}
+
+/// 画像リソースブロック
+pub struct ImageResource<'a> {
+    /// リソースID
+    pub id: u16,
+    /// リソースデータ
+    pub data: &'a [u8],
+}
+
+/// 解析済みのPSD/PSBドキュメント
+///
+/// 統合画像の展開に必要なセクションの位置だけを保持し、
+/// ピクセルデータの展開は必要になった時点で行う
+pub struct PsdDocument<'a> {
+    /// ファイル全体のバイト列
+    bytes: &'a [u8],
+    /// PSB（ラージドキュメント形式）かどうか
+    is_psb: bool,
+    /// チャンネル数
+    channels: usize,
+    /// 画像の幅
+    width: u32,
+    /// 画像の高さ
+    height: u32,
+    /// 1チャンネルあたりのビット数（1, 8, 16, 32）
+    depth: u16,
+    /// カラーモード
+    color_mode: ColorMode,
+    /// カラーモードデータ（インデックスカラーのパレットなど）
+    color_mode_data: &'a [u8],
+    /// 画像リソース
+    resources: Vec<ImageResource<'a>>,
+    /// レイヤーとマスク情報セクション
+    layer_section: &'a [u8],
+    /// レイヤー数
+    layer_count: usize,
+    /// 統合画像の最初のアルファチャンネルが透明度かどうか
+    merged_alpha: bool,
+    /// 画像データセクションの開始位置
+    image_data_offset: usize,
+}
+
+impl<'a> PsdDocument<'a> {
+    /// PSD/PSBファイルのヘッダーと各セクションを解析する
+    ///
+    /// # Arguments
+    /// * `bytes` - ファイル全体のバイト列
+    ///
+    /// # Returns
+    /// * `Result<PsdDocument>` - 解析済みのドキュメント
+    pub fn parse(bytes: &'a [u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+
+        // ファイルヘッダー
+        if reader.take(4)? != PSD_SIGNATURE {
+            bail!("PSDファイルのシグネチャが不正です");
+        }
+        let is_psb = match reader.u16()? {
+            1 => false,
+            2 => true,
+            version => bail!("未対応のPSDバージョンです: {}", version),
+        };
+        reader.skip(6)?;
+        let channels = reader.u16()? as usize;
+        let height = reader.u32()?;
+        let width = reader.u32()?;
+        let depth = reader.u16()?;
+        let color_mode = ColorMode::from_u16(reader.u16()?)?;
+
+        if !matches!(depth, 1 | 8 | 16 | 32) {
+            bail!("未対応のビット深度です: {}", depth);
+        }
+        let max_dimension = if is_psb { MAX_PSB_DIMENSION } else { MAX_PSD_DIMENSION };
+        if width == 0 || height == 0 || width > max_dimension || height > max_dimension
+            || channels == 0 || channels > MAX_CHANNELS
+        {
+            bail!("PSDの画像サイズまたはチャンネル数が不正です: {}x{}, {}チャンネル", width, height, channels);
+        }
+        let decoded_size = width as u64 * height as u64 * 4 * (depth.max(8) as u64 / 8);
+        if decoded_size > MAX_DECODED_SIZE {
+            bail!("PSDの画像が大きすぎます: {}x{}（上限 {} バイト）", width, height, MAX_DECODED_SIZE);
+        }
+
+        // カラーモードデータセクション
+        let color_mode_len = reader.u32()? as usize;
+        let color_mode_data = reader.take(color_mode_len)?;
+
+        // 画像リソースセクション
+        let resources_len = reader.u32()? as usize;
+        let resources = parse_resources(reader.take(resources_len)?);
+
+        // レイヤーとマスク情報セクション（レイヤー数のみ取得する）
+        let layer_section_len = if is_psb { reader.u64()? as usize } else { reader.u32()? as usize };
+        let layer_section = reader.take(layer_section_len)?;
+        let (layer_count, merged_alpha) = read_layer_count(layer_section, is_psb).unwrap_or((0, false));
+
+        let image_data_offset = reader.pos;
+
+        debug!(
+            "PSDヘッダーを解析: {}x{}, {}bit, {:?}, {}チャンネル, レイヤー数 {}, 透明度 {}, PSB {}",
+            width, height, depth, color_mode, channels, layer_count, merged_alpha, is_psb
+        );
+
+        Ok(PsdDocument {
+            bytes,
+            is_psb,
+            channels,
+            width,
+            height,
+            depth,
+            color_mode,
+            color_mode_data,
+            resources,
+            layer_section,
+            layer_count,
+            merged_alpha,
+            image_data_offset,
+        })
+    }
+
+    /// 指定したIDの画像リソースを取得する
+    pub fn resource(&self, id: u16) -> Option<&'a [u8]> {
+        self.resources.iter().find(|r| r.id == id).map(|r| r.data)
+    }
+
+    /// 埋め込みICCプロファイルを取得する
+    ///
+    /// CMYKやLabなどのプロファイルは展開時にsRGBへ変換済みの画像には適用できないため、
+    /// RGBとグレースケールの場合だけ返す
+    ///
+    /// # Returns
+    /// * `Option<Vec<u8>>` - ICCプロファイル
+    pub fn icc_profile(&self) -> Option<Vec<u8>> {
+        if !matches!(self.color_mode, ColorMode::Rgb | ColorMode::Grayscale) {
+            return None;
+        }
+        self.resource(RESOURCE_ICC_PROFILE).map(|data| data.to_vec())
+    }
+
+    /// レイヤー名の一覧を取得する
+    ///
+    /// グループの開始・終了を表すレコードは除外する
+    ///
+    /// # Returns
+    /// * `Result<Vec<String>>` - ファイル内の順（下のレイヤーから順）に並んだレイヤー名
+    pub fn layer_names(&self) -> Result<Vec<String>> {
+        if self.layer_section.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut reader = Reader::new(self.layer_section);
+        let layer_info_len = if self.is_psb { reader.u64()? as usize } else { reader.u32()? as usize };
+        if layer_info_len == 0 {
+            return Ok(Vec::new());
+        }
+        let mut reader = Reader::new(reader.take(layer_info_len)?);
+        let count = (reader.u16()? as i16).unsigned_abs();
+
+        let mut names = Vec::new();
+        for _ in 0..count {
+            if let Some(name) = read_layer_name(&mut reader, self.is_psb)? {
+                names.push(name);
+            }
+        }
+        Ok(names)
+    }
+
+    /// 実際の統合画像データが保存されているかどうかを確認する
+    ///
+    /// 「互換性を優先」をオフにして保存されたファイルは、統合画像の代わりに
+    /// 空の画像が格納されているため、バージョン情報リソースで判定する
+    ///
+    /// # Returns
+    /// * `bool` - 統合画像が存在する場合は true
+    fn has_merged_image(&self) -> bool {
+        if self.image_data_offset + 2 > self.bytes.len() {
+            return false;
+        }
+        match self.resource(RESOURCE_VERSION_INFO) {
+            Some(data) if data.len() >= 5 && self.layer_count > 0 => data[4] != 0,
+            _ => true,
+        }
+    }
+
+    /// 統合画像（最終合成画像）を展開する
+    ///
+    /// # Returns
+    /// * `Result<Option<DynamicImage>>` - 統合画像。保存されていない場合は None
+    pub fn merged_image(&self) -> Result<Option<DynamicImage>> {
+        if !self.has_merged_image() {
+            return Ok(None);
+        }
+
+        let raw = self.read_planes()?;
+        let row_bytes = self.row_bytes();
+        let planes: Vec<&[u8]> = raw.chunks_exact(row_bytes * self.height as usize).collect();
+        let color_channels = self.color_mode.color_channels();
+        // カラーチャンネル以外のチャンネルは保存した選択範囲やスポットカラーのこともあるため、
+        // レイヤー数の符号で透明度と示されている場合だけアルファとして扱う
+        let has_alpha = self.merged_alpha
+            && self.color_mode != ColorMode::Bitmap
+            && self.color_mode != ColorMode::Indexed
+            && planes.len() > color_channels;
+
+        let image = self.build_image(|i| {
+            let [r, g, b] = self.pixel_to_rgb(&planes, row_bytes, i)?;
+            let a = if has_alpha { self.sample(planes[color_channels], row_bytes, i) } else { 1.0 };
+            Ok([r, g, b, a])
+        })?;
+        Ok(Some(image))
+    }
+
+    /// 埋め込みサムネイルを展開する
+    ///
+    /// # Returns
+    /// * `Result<DynamicImage>` - サムネイル画像
+    pub fn thumbnail(&self) -> Result<DynamicImage> {
+        let (data, is_bgr) = match self.resource(RESOURCE_THUMBNAIL) {
+            Some(data) => (data, false),
+            None => (
+                self.resource(RESOURCE_THUMBNAIL_LEGACY)
+                    .ok_or_else(|| anyhow!("PSDにサムネイルリソースが含まれていません"))?,
+                true,
+            ),
+        };
+
+        // 28バイトのヘッダーの後にJFIFデータが続く
+        if data.len() <= 28 || u32::from_be_bytes([data[0], data[1], data[2], data[3]]) != 1 {
+            bail!("未対応のサムネイル形式です");
+        }
+        let mut thumbnail = image::load_from_memory_with_format(&data[28..], image::ImageFormat::Jpeg)?.to_rgba8();
+        if is_bgr {
+            for pixel in thumbnail.pixels_mut() {
+                pixel.0.swap(0, 2);
+            }
+        }
+        debug!("PSDのサムネイルを展開: {}x{}", thumbnail.width(), thumbnail.height());
+        Ok(DynamicImage::ImageRgba8(thumbnail))
+    }
+
+    /// 1チャンネル1行あたりのバイト数を取得する
+    ///
+    /// # Returns
+    /// * `usize` - 1行あたりのバイト数
+    fn row_bytes(&self) -> usize {
+        let width = self.width as usize;
+        match self.depth {
+            1 => width.div_ceil(8),
+            depth => width * (depth as usize / 8),
+        }
+    }
+
+    /// 画像データセクションから各チャンネルのプレーンを読み込む
+    ///
+    /// サンプルはファイルに格納されたビット深度のまま保持し、
+    /// 画像の作成時に1ピクセルずつ正規化する
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - 全チャンネルのプレーンを連結した生データ
+    fn read_planes(&self) -> Result<Vec<u8>> {
+        let mut reader = Reader::new(self.bytes);
+        reader.skip(self.image_data_offset)?;
+
+        let row_bytes = self.row_bytes();
+        let plane_bytes = row_bytes * self.height as usize;
+        let color_channels = self.color_mode.color_channels();
+
+        let compression = reader.u16()?;
+        let raw = match compression {
+            0 => {
+                // 末尾のチャンネルが欠けているファイルは、読めたチャンネルまでを使用する
+                let available = (reader.remaining() / plane_bytes).min(self.channels);
+                if available < color_channels {
+                    bail!("統合画像のデータが不足しています: {}チャンネル分が必要です", color_channels);
+                }
+                reader.take(plane_bytes * available)?.to_vec()
+            }
+            1 => self.read_rle_planes(&mut reader, row_bytes)?,
+            other => bail!("未対応の画像データ圧縮形式です: {}", other),
+        };
+
+        if raw.len() / plane_bytes < color_channels {
+            bail!("統合画像のチャンネル数が不足しています");
+        }
+        Ok(raw)
+    }
+
+    /// RLE（PackBits）圧縮されたチャンネルデータを展開する
+    ///
+    /// # Arguments
+    /// * `reader` - 画像データセクションの先頭（圧縮形式の直後）を指すリーダー
+    /// * `row_bytes` - 1行あたりのバイト数
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - 全チャンネルの展開済みデータ
+    fn read_rle_planes(&self, reader: &mut Reader, row_bytes: usize) -> Result<Vec<u8>> {
+        let height = self.height as usize;
+        let rows = self.channels * height;
+        let length_size = if self.is_psb { 4 } else { 2 };
+        if rows * length_size > reader.remaining() {
+            bail!("統合画像の行の長さのテーブルが途中で終了しています");
+        }
+
+        let mut row_lengths = Vec::with_capacity(rows);
+        for _ in 0..rows {
+            let len = if self.is_psb { reader.u32()? as usize } else { reader.u16()? as usize };
+            row_lengths.push(len);
+        }
+
+        // カラーチャンネルの圧縮データが揃っていることを確認してから展開先を確保する
+        let color_rows = height * self.color_mode.color_channels();
+        let color_bytes: usize = row_lengths[..color_rows].iter().sum();
+        if color_bytes > reader.remaining() {
+            bail!("統合画像の圧縮データが途中で終了しています");
+        }
+        let total_bytes: usize = row_lengths.iter().sum();
+        let stored_rows = if total_bytes <= reader.remaining() { rows } else { color_rows };
+
+        let mut output = Vec::with_capacity(stored_rows * row_bytes);
+        for (row, len) in row_lengths.into_iter().enumerate() {
+            // 末尾のチャンネルが欠けているファイルは、読めたチャンネルまでを使用する
+            let packed = match reader.take(len) {
+                Ok(packed) => packed,
+                Err(e) if row >= height * self.color_mode.color_channels() => {
+                    warn!("統合画像の追加チャンネルを読み込めません: {}", e);
+                    output.truncate(output.len() - output.len() % (row_bytes * height));
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            unpack_bits(packed, row_bytes, &mut output)?;
+        }
+        Ok(output)
+    }
+
+    /// 1チャンネル分のプレーンから指定ピクセルのサンプルを読み取り、0.0〜1.0の浮動小数点数に変換する
+    ///
+    /// # Arguments
+    /// * `plane` - 1チャンネル分の生データ
+    /// * `row_bytes` - 1行あたりのバイト数
+    /// * `i` - ピクセルインデックス
+    ///
+    /// # Returns
+    /// * `f32` - 正規化済みサンプル（32bitの場合はそのままの値）
+    fn sample(&self, plane: &[u8], row_bytes: usize, i: usize) -> f32 {
+        match self.depth {
+            1 => {
+                // ビットマップは1が黒を表す
+                let width = self.width as usize;
+                let (y, x) = (i / width, i % width);
+                if plane[y * row_bytes + x / 8] & (0x80 >> (x % 8)) != 0 { 0.0 } else { 1.0 }
+            }
+            8 => plane[i] as f32 / 255.0,
+            16 => u16::from_be_bytes([plane[i * 2], plane[i * 2 + 1]]) as f32 / 65535.0,
+            _ => {
+                let b = &plane[i * 4..i * 4 + 4];
+                f32::from_be_bytes([b[0], b[1], b[2], b[3]])
+            }
+        }
+    }
+
+    /// 指定ピクセルをカラーモードに応じてRGBに変換する
+    ///
+    /// # Arguments
+    /// * `planes` - チャンネルごとの生データ
+    /// * `row_bytes` - 1行あたりのバイト数
+    /// * `i` - ピクセルインデックス
+    ///
+    /// # Returns
+    /// * `Result<[f32; 3]>` - RGB値
+    fn pixel_to_rgb(&self, planes: &[&[u8]], row_bytes: usize, i: usize) -> Result<[f32; 3]> {
+        let channel = |c: usize| self.sample(planes[c], row_bytes, i);
+        Ok(match self.color_mode {
+            ColorMode::Rgb => [channel(0), channel(1), channel(2)],
+            ColorMode::Bitmap | ColorMode::Grayscale | ColorMode::Duotone | ColorMode::Multichannel => {
+                let v = channel(0);
+                [v, v, v]
+            }
+            ColorMode::Indexed => {
+                let index = (channel(0) * 255.0).round() as usize;
+                let palette = self.color_mode_data;
+                if palette.len() < 768 {
+                    bail!("インデックスカラーのパレットが不正です");
+                }
+                // パレットはR256個、G256個、B256個の順に格納されている
+                [
+                    palette[index] as f32 / 255.0,
+                    palette[256 + index] as f32 / 255.0,
+                    palette[512 + index] as f32 / 255.0,
+                ]
+            }
+            ColorMode::Cmyk => {
+                // PSDのCMYKは反転して格納されている（1.0 = インクなし）
+                let k = channel(3);
+                [channel(0) * k, channel(1) * k, channel(2) * k]
+            }
+            ColorMode::Lab => lab_to_srgb(channel(0), channel(1), channel(2)),
+        })
+    }
+
+    /// ピクセルごとのRGBA値からビット深度に応じた画像を作成する
+    ///
+    /// # Arguments
+    /// * `pixel` - ピクセルインデックスから0.0〜1.0（32bitの場合はリニアHDR値）のRGBA値を返す関数
+    ///
+    /// # Returns
+    /// * `Result<DynamicImage>` - 作成された画像
+    fn build_image(&self, pixel: impl Fn(usize) -> Result<[f32; 4]>) -> Result<DynamicImage> {
+        let (width, height) = (self.width, self.height);
+        let pixel_count = width as usize * height as usize;
+        let error = || anyhow!("PSDからの画像バッファ作成に失敗");
+        Ok(match self.depth {
+            16 => {
+                let mut data = Vec::with_capacity(pixel_count * 4);
+                for i in 0..pixel_count {
+                    data.extend(pixel(i)?.map(|v| (v.clamp(0.0, 1.0) * 65535.0).round() as u16));
+                }
+                DynamicImage::ImageRgba16(ImageBuffer::<Rgba<u16>, _>::from_raw(width, height, data).ok_or_else(error)?)
+            }
+            32 => {
+                let mut data = Vec::with_capacity(pixel_count * 4);
+                for i in 0..pixel_count {
+                    data.extend(pixel(i)?);
+                }
+                DynamicImage::ImageRgba32F(ImageBuffer::<Rgba<f32>, _>::from_raw(width, height, data).ok_or_else(error)?)
+            }
+            _ => {
+                let mut data = Vec::with_capacity(pixel_count * 4);
+                for i in 0..pixel_count {
+                    data.extend(pixel(i)?.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8));
+                }
+                DynamicImage::ImageRgba8(image::RgbaImage::from_raw(width, height, data).ok_or_else(error)?)
+            }
+        })
+    }
+}
+
+/// 画像リソースセクションを解析する
+///
+/// 壊れたブロックがあった場合は、それまでに読めたリソースを返す
+///
+/// # Arguments
+/// * `section` - 画像リソースセクションのバイト列
+///
+/// # Returns
+/// * `Vec<ImageResource>` - 画像リソースのリスト
+pub fn parse_resources(section: &[u8]) -> Vec<ImageResource<'_>> {
+    let mut reader = Reader::new(section);
+    let mut resources = Vec::new();
+
+    let mut next = || -> Result<ImageResource> {
+        if reader.take(4)? != RESOURCE_SIGNATURE {
+            bail!("画像リソースのシグネチャが不正です");
+        }
+        let id = reader.u16()?;
+        // パスカル文字列の名前（長さバイトを含めて偶数バイトにパディング）
+        let name_len = reader.u8()? as usize;
+        reader.skip(name_len + (name_len + 1) % 2)?;
+        let size = reader.u32()? as usize;
+        let data = reader.take(size)?;
+        if size % 2 == 1 && reader.remaining() > 0 {
+            reader.skip(1)?;
+        }
+        Ok(ImageResource { id, data })
+    };
+
+    while let Ok(resource) = next() {
+        resources.push(resource);
+    }
+    resources
+}
+
+/// レイヤーとマスク情報セクションからレイヤー数を取得する
+///
+/// # Arguments
+/// * `section` - レイヤーとマスク情報セクションのバイト列
+/// * `is_psb` - PSB形式かどうか
+///
+/// # Returns
+/// * `Result<(usize, bool)>` - レイヤー数と、統合画像の最初のアルファチャンネルが透明度かどうか
+fn read_layer_count(section: &[u8], is_psb: bool) -> Result<(usize, bool)> {
+    if section.is_empty() {
+        return Ok((0, false));
+    }
+    let mut reader = Reader::new(section);
+    let layer_info_len = if is_psb { reader.u64()? as usize } else { reader.u32()? as usize };
+    if layer_info_len == 0 {
+        return Ok((0, false));
+    }
+    // 負の値は最初のアルファチャンネルが統合画像の透明度であることを表す
+    let count = reader.u16()? as i16;
+    Ok((count.unsigned_abs() as usize, count < 0))
+}
+
+/// レイヤーレコードを1つ読み進め、レイヤー名を取得する
+///
+/// Unicodeのレイヤー名（luni）があればそれを使い、なければパスカル文字列の名前を使う
+///
+/// # Arguments
+/// * `reader` - レイヤーレコードの先頭を指すリーダー
+/// * `is_psb` - PSB形式かどうか
+///
+/// # Returns
+/// * `Result<Option<String>>` - レイヤー名。グループの開始・終了を表すレコードの場合は None
+fn read_layer_name(reader: &mut Reader, is_psb: bool) -> Result<Option<String>> {
+    // 矩形（上・左・下・右）
+    reader.skip(16)?;
+    let channels = reader.u16()? as usize;
+    reader.skip(channels * if is_psb { 10 } else { 6 })?;
+    if reader.take(4)? != RESOURCE_SIGNATURE {
+        bail!("レイヤーレコードのシグネチャが不正です");
+    }
+    // 描画モード、不透明度、クリッピング、フラグ、フィラー
+    reader.skip(8)?;
+
+    let extra_len = reader.u32()? as usize;
+    let mut extra = Reader::new(reader.take(extra_len)?);
+    let mask_len = extra.u32()? as usize;
+    extra.skip(mask_len)?;
+    let blending_ranges_len = extra.u32()? as usize;
+    extra.skip(blending_ranges_len)?;
+    // パスカル文字列の名前（長さバイトを含めて4バイト単位にパディング）
+    let name_len = extra.u8()? as usize;
+    let mut name = String::from_utf8_lossy(extra.take(name_len)?).into_owned();
+    extra.skip((name_len + 1).next_multiple_of(4) - (name_len + 1))?;
+
+    let mut is_group_marker = false;
+    while extra.remaining() >= 12 {
+        let signature = extra.take(4)?;
+        if signature != b"8BIM" && signature != b"8B64" {
+            break;
+        }
+        let key = extra.take(4)?;
+        let len = if is_psb && PSB_LARGE_BLOCK_KEYS.contains(&key) { extra.u64()? as usize } else { extra.u32()? as usize };
+        let mut data = Reader::new(extra.take(len)?);
+        match key {
+            b"luni" => {
+                let count = data.u32()? as usize;
+                let units = (0..count).map(|_| data.u16()).collect::<Result<Vec<_>>>()?;
+                name = String::from_utf16_lossy(&units).trim_end_matches('\0').to_string();
+            }
+            // 0以外はグループの開始（開いている・閉じている）と終了の区切り
+            b"lsct" => is_group_marker = data.u32()? != 0,
+            _ => {}
+        }
+    }
+    Ok((!is_group_marker).then_some(name))
+}
+
+/// PackBits圧縮された1行分のデータを展開する
+///
+/// # Arguments
+/// * `packed` - 圧縮データ
+/// * `row_bytes` - 展開後の1行あたりのバイト数
+/// * `output` - 展開先のバッファ
+///
+/// # Returns
+/// * `Result<()>` - 成功時は Ok(())
+fn unpack_bits(packed: &[u8], row_bytes: usize, output: &mut Vec<u8>) -> Result<()> {
+    let start = output.len();
+    let mut i = 0;
+    while i < packed.len() && output.len() - start < row_bytes {
+        let header = packed[i] as i8;
+        i += 1;
+        if header >= 0 {
+            let count = header as usize + 1;
+            let literal = packed.get(i..i + count)
+                .ok_or_else(|| anyhow!("RLEデータが途中で終了しています"))?;
+            output.extend_from_slice(literal);
+            i += count;
+        } else if header != -128 {
+            let count = 1 - header as isize;
+            let value = *packed.get(i).ok_or_else(|| anyhow!("RLEデータが途中で終了しています"))?;
+            output.extend(std::iter::repeat_n(value, count as usize));
+            i += 1;
+        }
+    }
+    // 行の長さが合わない場合は切り詰め、または0で埋める
+    output.resize(start + row_bytes, 0);
+    Ok(())
+}
+
+/// CIE L*a*b*（D50）をsRGBに変換する
+///
+/// # Arguments
+/// * `l` - 正規化済みのL*（0.0〜1.0）
+/// * `a` - 正規化済みのa*（0.5が中心）
+/// * `b` - 正規化済みのb*（0.5が中心）
+///
+/// # Returns
+/// * `[f32; 3]` - sRGB値
+fn lab_to_srgb(l: f32, a: f32, b: f32) -> [f32; 3] {
+    let l = l * 100.0;
+    let a = a * 255.0 - 128.0;
+    let b = b * 255.0 - 128.0;
+
+    let fy = (l + 16.0) / 116.0;
+    let fx = fy + a / 500.0;
+    let fz = fy - b / 200.0;
+    let f_inv = |t: f32| if t > 6.0 / 29.0 { t * t * t } else { 3.0 * (6.0f32 / 29.0).powi(2) * (t - 4.0 / 29.0) };
+
+    // D50白色点
+    let x = 0.9642 * f_inv(fx);
+    let y = f_inv(fy);
+    let z = 0.8249 * f_inv(fz);
+
+    // Bradford適応済みのXYZ(D50) → リニアsRGB
+    let r = 3.1339 * x - 1.6169 * y - 0.4906 * z;
+    let g = -0.9788 * x + 1.9161 * y + 0.0335 * z;
+    let b = 0.0719 * x - 0.2290 * y + 1.4052 * z;
+
+    [r, g, b].map(|v| linear_to_srgb(v.clamp(0.0, 1.0)))
+}
+
+/// レイヤーを合成して画像を作成する
+///
+/// 統合画像が保存されていないファイル向けのフォールバックで、
+/// 8bit RGBのPSDのみ対応する
+///
+/// # Arguments
+/// * `bytes` - ファイル全体のバイト列
+///
+/// # Returns
+/// * `Result<DynamicImage>` - 合成された画像
+fn composite_layers(bytes: &[u8]) -> Result<DynamicImage> {
+    let psd = psd::Psd::from_bytes(bytes)?;
+    let rgba = psd.flatten_layers_rgba(&|_| true)?;
+    let img_buffer = image::RgbaImage::from_raw(psd.width(), psd.height(), rgba)
+        .ok_or_else(|| anyhow!("PSDからの画像バッファ作成に失敗"))?;
+    debug!("PSDのレイヤーを合成: {}x{}", psd.width(), psd.height());
+    Ok(DynamicImage::ImageRgba8(img_buffer))
+}
+
+/// PSD/PSBファイルを読み込む
+///
+/// 統合画像 → レイヤー合成 → 埋め込みサムネイルの順に試し、
+/// 最初に取得できた画像を返す
+///
+/// # Arguments
+/// * `bytes` - ファイル全体のバイト列
+///
+/// # Returns
+/// * `Result<(DynamicImage, Option<Vec<u8>>)>` - 読み込まれた画像と埋め込みICCプロファイル
+///   （サムネイルはsRGBのため、プロファイルは返さない）
+pub fn load_psd(bytes: &[u8]) -> Result<(DynamicImage, Option<Vec<u8>>)> {
+    let document = PsdDocument::parse(bytes)?;
+
+    match document.merged_image() {
+        Ok(Some(img)) => return Ok((img, document.icc_profile())),
+        Ok(None) => warn!("PSDに統合画像が保存されていないため、レイヤーを合成します"),
+        Err(e) => warn!("PSDの統合画像の展開に失敗したため、レイヤーを合成します: {}", e),
+    }
+
+    if !document.is_psb && document.depth == 8 && document.color_mode == ColorMode::Rgb {
+        match composite_layers(bytes) {
+            Ok(img) => return Ok((img, document.icc_profile())),
+            Err(e) => warn!("PSDのレイヤー合成に失敗したため、サムネイルを使用します: {}", e),
+        }
+    }
+
+    Ok((document.thumbnail()?, None))
+}
+
+/// PSD/PSBファイルのヘッダーから画像の幅と高さを取得する
+///
+/// # Arguments
+/// * `path` - ファイルのパス
+///
+/// # Returns
+/// * `Result<(u32, u32)>` - 幅と高さ
+pub fn dimensions(path: &Path) -> Result<(u32, u32)> {
+    let mut header = [0; 26];
+    File::open(path)?.read_exact(&mut header)?;
+    let mut reader = Reader::new(&header);
+    if reader.take(4)? != PSD_SIGNATURE {
+        bail!("PSDファイルのシグネチャが不正です");
+    }
+    // バージョン、予約領域、チャンネル数の後に高さ、幅の順で格納されている
+    reader.skip(10)?;
+    let height = reader.u32()?;
+    let width = reader.u32()?;
+    Ok((width, height))
+}
+
+/// PSD/PSBファイルのレイヤー名一覧を取得する
+///
+/// # Arguments
+/// * `bytes` - ファイル全体のバイト列
+///
+/// # Returns
+/// * `Result<Vec<String>>` - レイヤー名のリスト
+pub fn layer_names(bytes: &[u8]) -> Result<Vec<String>> {
+    PsdDocument::parse(bytes)?.layer_names()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_bits_expands_literal_and_repeat_runs() {
+        // 3バイトのリテラル、4回の繰り返し、-128 は何もしないヘッダー
+        let packed = [2, 1, 2, 3, (-3i8) as u8, 9, 0x80, 0, 7];
+        let mut output = Vec::new();
+        unpack_bits(&packed, 8, &mut output).unwrap();
+        assert_eq!(output, [1, 2, 3, 9, 9, 9, 9, 7]);
+    }
+
+    #[test]
+    fn unpack_bits_appends_after_existing_rows() {
+        let mut output = vec![5, 5];
+        unpack_bits(&[0, 1], 1, &mut output).unwrap();
+        assert_eq!(output, [5, 5, 1]);
+    }
+
+    #[test]
+    fn unpack_bits_pads_short_rows_and_truncates_long_rows() {
+        let mut short = Vec::new();
+        unpack_bits(&[1, 4, 5], 4, &mut short).unwrap();
+        assert_eq!(short, [4, 5, 0, 0]);
+
+        let mut long = Vec::new();
+        unpack_bits(&[(-5i8) as u8, 3], 4, &mut long).unwrap();
+        assert_eq!(long, [3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn unpack_bits_rejects_truncated_data() {
+        assert!(unpack_bits(&[3, 1, 2], 4, &mut Vec::new()).is_err());
+        assert!(unpack_bits(&[(-2i8) as u8], 3, &mut Vec::new()).is_err());
+    }
+
+    /// テスト用の最小限のPSDファイルを作成する
+    fn psd_bytes(width: u32, height: u32, channels: u16, depth: u16, image_data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(PSD_SIGNATURE);
+        bytes.extend_from_slice(&1u16.to_be_bytes());
+        bytes.extend_from_slice(&[0; 6]);
+        bytes.extend_from_slice(&channels.to_be_bytes());
+        bytes.extend_from_slice(&height.to_be_bytes());
+        bytes.extend_from_slice(&width.to_be_bytes());
+        bytes.extend_from_slice(&depth.to_be_bytes());
+        bytes.extend_from_slice(&3u16.to_be_bytes());
+        // カラーモードデータ、画像リソース、レイヤーとマスク情報はすべて空
+        bytes.extend_from_slice(&[0; 12]);
+        bytes.extend_from_slice(image_data);
+        bytes
+    }
+
+    #[test]
+    fn merged_image_keeps_raw_16bit_samples() {
+        let mut data = 0u16.to_be_bytes().to_vec();
+        for value in [0xffffu16, 0x1234, 0x0000] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        let bytes = psd_bytes(1, 1, 3, 16, &data);
+        let image = PsdDocument::parse(&bytes).unwrap().merged_image().unwrap().unwrap();
+        assert_eq!(image.to_rgba16().get_pixel(0, 0).0, [0xffff, 0x1234, 0x0000, 0xffff]);
+    }
+
+    #[test]
+    fn parse_rejects_dimensions_over_the_spec_limit() {
+        let bytes = psd_bytes(MAX_PSD_DIMENSION + 1, 1, 3, 8, &[0, 0]);
+        assert!(PsdDocument::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn merged_image_rejects_truncated_rle_data() {
+        // 行の長さのテーブルは揃っているが、圧縮データが足りない
+        let mut data = 1u16.to_be_bytes().to_vec();
+        for _ in 0..3 {
+            data.extend_from_slice(&100u16.to_be_bytes());
+        }
+        data.extend_from_slice(&[0, 1]);
+        let bytes = psd_bytes(2, 1, 3, 8, &data);
+        assert!(PsdDocument::parse(&bytes).unwrap().merged_image().is_err());
+    }
+
+    /// テスト用のレイヤーレコードを作成する
+    fn layer_record(name: &str, blocks: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut extra = Vec::new();
+        // マスクと描画範囲は空
+        extra.extend_from_slice(&[0; 8]);
+        extra.push(name.len() as u8);
+        extra.extend_from_slice(name.as_bytes());
+        extra.resize(8 + (name.len() + 1).next_multiple_of(4), 0);
+        for (key, data) in blocks {
+            extra.extend_from_slice(b"8BIM");
+            extra.extend_from_slice(*key);
+            extra.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            extra.extend_from_slice(data);
+        }
+
+        let mut record = vec![0; 16];
+        // 1チャンネル（ID 0、長さ 2）
+        record.extend_from_slice(&1u16.to_be_bytes());
+        record.extend_from_slice(&[0, 0, 0, 0, 0, 2]);
+        record.extend_from_slice(b"8BIMnorm");
+        record.extend_from_slice(&[255, 0, 0, 0]);
+        record.extend_from_slice(&(extra.len() as u32).to_be_bytes());
+        record.extend_from_slice(&extra);
+        record
+    }
+
+    #[test]
+    fn read_layer_name_prefers_unicode_name_and_skips_group_markers() {
+        let mut luni = 3u32.to_be_bytes().to_vec();
+        for unit in "背景0".encode_utf16() {
+            luni.extend_from_slice(&unit.to_be_bytes());
+        }
+        let records = [
+            layer_record("Background", &[(b"luni", luni)]),
+            layer_record("</Layer group>", &[(b"lsct", 3u32.to_be_bytes().to_vec())]),
+            layer_record("Plain", &[(b"lsct", 0u32.to_be_bytes().to_vec())]),
+        ]
+        .concat();
+
+        let mut reader = Reader::new(&records);
+        assert_eq!(read_layer_name(&mut reader, false).unwrap().as_deref(), Some("背景0"));
+        assert_eq!(read_layer_name(&mut reader, false).unwrap(), None);
+        assert_eq!(read_layer_name(&mut reader, false).unwrap().as_deref(), Some("Plain"));
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn read_layer_name_rejects_truncated_records() {
+        let record = layer_record("Layer", &[]);
+        assert!(read_layer_name(&mut Reader::new(&record[..record.len() - 1]), false).is_err());
+    }
+
+    #[test]
+    fn read_layer_count_keeps_merged_alpha_flag() {
+        let section = |count: i16| [&4u32.to_be_bytes()[..], &count.to_be_bytes()[..]].concat();
+        assert_eq!(read_layer_count(&section(-3), false).unwrap(), (3, true));
+        assert_eq!(read_layer_count(&section(3), false).unwrap(), (3, false));
+        assert_eq!(read_layer_count(&[], false).unwrap(), (0, false));
+    }
+}
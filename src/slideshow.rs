@@ -0,0 +1,176 @@
+use log::info;
+use std::time::{Duration, Instant};
+use crate::config::SlideshowOrder;
+
+/// 一定時間ごとに次の画像へ進めるスライドショー
+pub struct Slideshow {
+    /// 1枚あたりの表示時間
+    interval: Duration,
+    /// 表示順
+    order: SlideshowOrder,
+    /// 最後の画像の後に最初から繰り返すかどうか
+    looping: bool,
+    /// 一時停止中かどうか
+    paused: bool,
+    /// 表示中の画像を表示し始めた時刻
+    shown_at: Instant,
+    /// ランダム順でまだ表示していない画像のインデックス（末尾から順に表示する）
+    remaining: Vec<usize>,
+    /// ランダム順で一巡分を並べたことがあるかどうか
+    shuffled: bool,
+}
+
+impl Slideshow {
+    /// 新しい Slideshow インスタンスを作成する
+    ///
+    /// # Arguments
+    /// * `interval_secs` - 1枚あたりの表示時間（秒）
+    /// * `order` - 表示順
+    /// * `looping` - 最後の画像の後に最初から繰り返すかどうか
+    ///
+    /// # Returns
+    /// * `Slideshow` - スライドショー
+    pub fn new(interval_secs: f32, order: SlideshowOrder, looping: bool) -> Self {
+        Slideshow {
+            interval: Duration::from_secs_f32(interval_secs.max(0.1)),
+            order,
+            looping,
+            paused: false,
+            shown_at: Instant::now(),
+            remaining: Vec::new(),
+            shuffled: false,
+        }
+    }
+
+    /// 1枚あたりの表示時間を取得する
+    ///
+    /// # Returns
+    /// * `Duration` - 表示時間
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// 一時停止中かどうかを取得する
+    ///
+    /// # Returns
+    /// * `bool` - 一時停止中の場合は true
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 一時停止と再開を切り替える（再開時は表示時間を数え直す）
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.shown_at = Instant::now();
+        info!("スライドショー: {}", if self.paused { "一時停止" } else { "再開" });
+    }
+
+    /// 画像が切り替わったことを通知し、表示時間を数え直す
+    pub fn restart_timer(&mut self) {
+        self.shown_at = Instant::now();
+    }
+
+    /// 次の画像へ進める時刻になったかどうかを判定する
+    ///
+    /// # Arguments
+    /// * `animation_finished` - アニメーション画像を最後まで再生したかどうか（静止画の場合は true）
+    ///
+    /// # Returns
+    /// * `bool` - 次の画像へ進める場合は true
+    pub fn is_due(&self, animation_finished: bool) -> bool {
+        !self.paused && animation_finished && self.shown_at.elapsed() >= self.interval
+    }
+
+    /// 次に表示する画像のインデックスを決める
+    ///
+    /// # Arguments
+    /// * `current` - 表示中の画像のインデックス
+    /// * `total` - 画像の総数
+    ///
+    /// # Returns
+    /// * `Option<usize>` - 次の画像のインデックス（繰り返さない設定で最後まで表示した場合は None）
+    pub fn next_index(&mut self, current: usize, total: usize) -> Option<usize> {
+        if total == 0 {
+            return None;
+        }
+        match self.order {
+            SlideshowOrder::Sequential => {
+                if current + 1 < total {
+                    Some(current + 1)
+                } else if self.looping {
+                    Some(0)
+                } else {
+                    None
+                }
+            }
+            SlideshowOrder::Random => {
+                if self.remaining.is_empty() {
+                    // 一巡したら並べ直す（表示中の画像が続けて出ないよう除く）
+                    if self.shuffled && !self.looping {
+                        return None;
+                    }
+                    self.remaining = (0..total).filter(|&index| index != current || total == 1).collect();
+                    fastrand::shuffle(&mut self.remaining);
+                    self.shuffled = true;
+                }
+                self.remaining.pop()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn sequential_order_stops_or_wraps_at_the_end() {
+        let mut once = Slideshow::new(1.0, SlideshowOrder::Sequential, false);
+        assert_eq!(once.next_index(0, 3), Some(1));
+        assert_eq!(once.next_index(2, 3), None);
+
+        let mut looping = Slideshow::new(1.0, SlideshowOrder::Sequential, true);
+        assert_eq!(looping.next_index(2, 3), Some(0));
+    }
+
+    #[test]
+    fn random_order_without_loop_shows_every_other_image_once() {
+        let mut slideshow = Slideshow::new(1.0, SlideshowOrder::Random, false);
+        let mut current = 2;
+        let mut shown = Vec::new();
+        while let Some(next) = slideshow.next_index(current, 6) {
+            shown.push(next);
+            current = next;
+        }
+        let unique: HashSet<_> = shown.iter().copied().collect();
+        assert_eq!(shown.len(), 5);
+        assert_eq!(unique, HashSet::from([0, 1, 3, 4, 5]));
+    }
+
+    #[test]
+    fn random_order_with_loop_never_repeats_within_a_round() {
+        let mut slideshow = Slideshow::new(1.0, SlideshowOrder::Random, true);
+        let mut current = 0;
+        for _ in 0..10 {
+            let round: Vec<usize> = (0..4)
+                .map(|_| {
+                    let next = slideshow.next_index(current, 5).unwrap();
+                    assert_ne!(next, current);
+                    current = next;
+                    next
+                })
+                .collect();
+            let unique: HashSet<_> = round.iter().copied().collect();
+            assert_eq!(unique.len(), round.len());
+        }
+    }
+
+    #[test]
+    fn random_order_with_a_single_image() {
+        let mut looping = Slideshow::new(1.0, SlideshowOrder::Random, true);
+        assert_eq!(looping.next_index(0, 1), Some(0));
+        assert_eq!(looping.next_index(0, 1), Some(0));
+        assert_eq!(Slideshow::new(1.0, SlideshowOrder::Random, false).next_index(0, 0), None);
+    }
+}
@@ -0,0 +1,265 @@
+use image::{DynamicImage, ImageBuffer, Pixel, Rgba, imageops};
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use crate::color_management::srgb_to_linear;
+use crate::image_handler::{ImageFile, ImageHandler};
+
+/// 見開き表示のページの組み合わせ
+///
+/// 先頭から順に2ページずつ組にし、表紙と横長のページ（見開きで描かれたページ）は1ページで表示する。
+/// どの位置から開いても同じ組み合わせになるよう、常に先頭から数える。
+/// 数えた見開きの先頭ページは覚えておき、次からはその続きから数える
+pub struct SpreadLayout {
+    /// 表紙（先頭のページ）を1ページで表示するかどうか
+    cover_alone: bool,
+    /// 横長のページかどうかのキャッシュ（並べ替えても使えるようパスで引く）
+    wide: HashMap<PathBuf, bool>,
+    /// 先頭から順に数え終えた見開きの先頭ページ
+    starts: Vec<usize>,
+    /// starts を数えたときのページ数
+    page_count: usize,
+}
+
+impl SpreadLayout {
+    /// 新しい SpreadLayout インスタンスを作成する
+    ///
+    /// # Arguments
+    /// * `cover_alone` - 表紙を1ページで表示するかどうか
+    ///
+    /// # Returns
+    /// * `SpreadLayout` - 見開き表示のページの組み合わせ
+    pub fn new(cover_alone: bool) -> Self {
+        SpreadLayout {
+            cover_alone,
+            wide: HashMap::new(),
+            starts: Vec::new(),
+            page_count: 0,
+        }
+    }
+
+    /// 数え終えた見開きの組み合わせを破棄する
+    ///
+    /// 画像リストの並び順を変えた後に呼び出す
+    pub fn reset(&mut self) {
+        self.starts.clear();
+    }
+
+    /// 指定したページを含む見開きのページ範囲を取得する
+    ///
+    /// # Arguments
+    /// * `image_handler` - 画像リストを読み込んだ画像ハンドラー
+    /// * `index` - ページのインデックス
+    ///
+    /// # Returns
+    /// * `Range<usize>` - 見開きのページ範囲（1ページまたは2ページ）
+    pub fn spread_at(&mut self, image_handler: &ImageHandler, index: usize) -> Range<usize> {
+        let images = image_handler.images();
+        if index >= images.len() {
+            return index..index + 1;
+        }
+        if self.page_count != images.len() {
+            self.starts.clear();
+            self.page_count = images.len();
+        }
+        if self.starts.is_empty() {
+            self.starts.push(0);
+        }
+
+        // 数え終えた範囲にあるページは、覚えている先頭ページから探す
+        let last = self.starts[self.starts.len() - 1];
+        if index < last {
+            let position = self.starts.partition_point(|&start| start <= index) - 1;
+            return self.starts[position]..self.starts[position + 1];
+        }
+
+        // 数え終えた最後の見開きから、指定したページを含む見開きまで数え進める
+        let mut start = last;
+        loop {
+            let end = start + self.spread_len(image_handler, images, start);
+            if index < end {
+                return start..end;
+            }
+            self.starts.push(end);
+            start = end;
+        }
+    }
+
+    /// 次の見開きの先頭ページを取得する
+    ///
+    /// # Arguments
+    /// * `image_handler` - 画像リストを読み込んだ画像ハンドラー
+    /// * `index` - 表示中のページのインデックス
+    ///
+    /// # Returns
+    /// * `usize` - 次の見開きの先頭ページ（最後の見開きの場合は先頭へ戻る）
+    pub fn next_start(&mut self, image_handler: &ImageHandler, index: usize) -> usize {
+        let end = self.spread_at(image_handler, index).end;
+        if end >= image_handler.len() { 0 } else { end }
+    }
+
+    /// 前の見開きの先頭ページを取得する
+    ///
+    /// # Arguments
+    /// * `image_handler` - 画像リストを読み込んだ画像ハンドラー
+    /// * `index` - 表示中のページのインデックス
+    ///
+    /// # Returns
+    /// * `usize` - 前の見開きの先頭ページ（最初の見開きの場合は最後の見開きへ移る）
+    pub fn previous_start(&mut self, image_handler: &ImageHandler, index: usize) -> usize {
+        let start = self.spread_at(image_handler, index).start;
+        let previous = if start == 0 { image_handler.len().saturating_sub(1) } else { start - 1 };
+        self.spread_at(image_handler, previous).start
+    }
+
+    /// 指定したページから始まる見開きのページ数を決める
+    fn spread_len(&mut self, image_handler: &ImageHandler, images: &[ImageFile], start: usize) -> usize {
+        let single = (start == 0 && self.cover_alone)
+            || start + 1 >= images.len()
+            || self.is_wide(image_handler, &images[start])
+            || self.is_wide(image_handler, &images[start + 1]);
+        if single { 1 } else { 2 }
+    }
+
+    /// ページが横長（見開きで描かれたページ）かどうかを判定する
+    fn is_wide(&mut self, image_handler: &ImageHandler, image: &ImageFile) -> bool {
+        if let Some(&wide) = self.wide.get(&image.path) {
+            return wide;
+        }
+        let wide = match image_handler.image_dimensions(&image.path) {
+            Ok((width, height)) => width > height,
+            Err(e) => {
+                warn!("画像サイズの取得に失敗したため、縦長のページとして扱います: {:?} ({:?})", image.path, e);
+                false
+            }
+        };
+        debug!("ページの向きを判定: {} ({})", image.name, if wide { "横長" } else { "縦長" });
+        self.wide.insert(image.path.clone(), wide);
+        wide
+    }
+}
+
+/// 2ページを左右に並べた1枚の画像を作成する
+///
+/// 高さの異なるページは高い方に合わせて拡大し、どちらかが浮動小数点画像（リニアHDR）の場合は
+/// もう一方もリニアの浮動小数点に変換して並べる
+///
+/// # Arguments
+/// * `left` - 左に表示するページ
+/// * `right` - 右に表示するページ
+///
+/// # Returns
+/// * `DynamicImage` - 並べた画像
+pub fn compose(left: DynamicImage, right: DynamicImage) -> DynamicImage {
+    let height = left.height().max(right.height());
+    let fit_height = |page: DynamicImage| -> DynamicImage {
+        if page.height() == height {
+            return page;
+        }
+        let width = (page.width() as u64 * height as u64 / page.height().max(1) as u64).max(1) as u32;
+        page.resize_exact(width, height, imageops::FilterType::Triangle)
+    };
+    let (left, right) = (fit_height(left), fit_height(right));
+
+    let is_float = |page: &DynamicImage| matches!(page, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_));
+    let is_16bit = |page: &DynamicImage| matches!(
+        page,
+        DynamicImage::ImageLuma16(_) | DynamicImage::ImageLumaA16(_) | DynamicImage::ImageRgb16(_) | DynamicImage::ImageRgba16(_)
+    );
+
+    if is_float(&left) || is_float(&right) {
+        let to_linear = |page: &DynamicImage| {
+            let mut rgba = page.to_rgba32f();
+            if !is_float(page) {
+                for pixel in rgba.pixels_mut() {
+                    pixel.apply_without_alpha(srgb_to_linear);
+                }
+            }
+            rgba
+        };
+        DynamicImage::ImageRgba32F(side_by_side(&to_linear(&left), &to_linear(&right)))
+    } else if is_16bit(&left) || is_16bit(&right) {
+        DynamicImage::ImageRgba16(side_by_side(&left.to_rgba16(), &right.to_rgba16()))
+    } else {
+        DynamicImage::ImageRgba8(side_by_side(&left.to_rgba8(), &right.to_rgba8()))
+    }
+}
+
+/// 同じ高さの2枚の画像を左右に並べる
+fn side_by_side<T>(left: &ImageBuffer<Rgba<T>, Vec<T>>, right: &ImageBuffer<Rgba<T>, Vec<T>>) -> ImageBuffer<Rgba<T>, Vec<T>>
+where
+    Rgba<T>: Pixel<Subpixel = T>,
+    T: image::Primitive,
+{
+    let mut canvas = ImageBuffer::new(left.width() + right.width(), left.height());
+    imageops::replace(&mut canvas, left, 0, 0);
+    imageops::replace(&mut canvas, right, left.width() as i64, 0);
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use image::RgbaImage;
+
+    /// 指定した向きのページを並べたディレクトリを作成し、読み込んだ画像ハンドラーを返す
+    fn pages(name: &str, wide: &[bool]) -> (ImageHandler, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("spread-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (index, &wide) in wide.iter().enumerate() {
+            let (width, height) = if wide { (4, 2) } else { (2, 4) };
+            RgbaImage::new(width, height).save(dir.join(format!("{:02}.png", index))).unwrap();
+        }
+        let mut handler = ImageHandler::new(Config::default());
+        handler.load_images_from_directory(&dir).unwrap();
+        (handler, dir)
+    }
+
+    /// 全ページの見開きを順に取得する
+    fn spreads(layout: &mut SpreadLayout, handler: &ImageHandler) -> Vec<Range<usize>> {
+        let mut result = Vec::new();
+        let mut index = 0;
+        while index < handler.len() {
+            let spread = layout.spread_at(handler, index);
+            index = spread.end;
+            result.push(spread);
+        }
+        result
+    }
+
+    #[test]
+    fn cover_is_shown_alone_when_enabled() {
+        let (handler, dir) = pages("cover", &[false; 5]);
+        assert_eq!(spreads(&mut SpreadLayout::new(true), &handler), [0..1, 1..3, 3..5]);
+        assert_eq!(spreads(&mut SpreadLayout::new(false), &handler), [0..2, 2..4, 4..5]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn wide_pages_are_shown_alone() {
+        let (handler, dir) = pages("wide", &[false, false, true, false, false, false]);
+        let mut layout = SpreadLayout::new(false);
+        assert_eq!(spreads(&mut layout, &handler), [0..2, 2..3, 3..5, 5..6]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn lookups_in_any_order_give_the_same_spreads() {
+        let (handler, dir) = pages("order", &[false, true, false, false, false, false, false]);
+        let mut layout = SpreadLayout::new(true);
+        // 後ろのページから引いても、先頭から数えた組み合わせと同じになる
+        assert_eq!(layout.spread_at(&handler, 6), 6..7);
+        assert_eq!(layout.spread_at(&handler, 3), 2..4);
+        assert_eq!(layout.spread_at(&handler, 1), 1..2);
+        assert_eq!(layout.spread_at(&handler, 5), 4..6);
+        assert_eq!(layout.spread_at(&handler, 0), 0..1);
+        assert_eq!(layout.next_start(&handler, 6), 0);
+        assert_eq!(layout.previous_start(&handler, 0), 6);
+        assert_eq!(layout.previous_start(&handler, 3), 1);
+        assert_eq!(layout.spread_at(&handler, 7), 7..8);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
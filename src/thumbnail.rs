@@ -0,0 +1,212 @@
+use image::{DynamicImage, RgbaImage};
+use log::{debug, warn};
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use crate::image_handler::ImageHandler;
+use crate::thumbnail_cache::ThumbnailCache;
+
+/// ワーカースレッド数の上限
+const MAX_WORKERS: usize = 4;
+
+/// サムネイル生成の結果
+pub struct ThumbnailResult {
+    /// 画像リスト内のインデックス
+    pub index: usize,
+    /// 生成したサムネイル（読み込みに失敗した場合は None）
+    pub image: Option<RgbaImage>,
+}
+
+/// ワーカースレッドと共有する状態
+struct SharedState {
+    /// 生成待ちのジョブ（インデックスとパス）
+    pending: VecDeque<(usize, PathBuf)>,
+    /// 生成中のインデックス
+    in_progress: HashSet<usize>,
+    /// 終了要求
+    shutdown: bool,
+}
+
+/// ワーカースレッドでサムネイルを生成するローダー
+///
+/// 表示範囲が変わるたびに `request` で待ち行列を置き換えるため、
+/// 見えている画像から順に生成される
+pub struct ThumbnailLoader {
+    /// ワーカースレッドと共有する状態
+    shared: Arc<(Mutex<SharedState>, Condvar)>,
+    /// 生成結果の受信側
+    receiver: Receiver<ThumbnailResult>,
+}
+
+/// 生成に使う共通のリソース
+struct Generator {
+    /// 画像の読み込みに使う画像ハンドラー
+    image_handler: Arc<ImageHandler>,
+    /// ディスク上のサムネイルキャッシュ
+    cache: Option<Arc<ThumbnailCache>>,
+    /// サムネイルの最大辺の長さ（ピクセル）
+    size: u32,
+}
+
+impl Generator {
+    /// サムネイルを生成する（キャッシュにあればそれを使い、なければ生成して保存する）
+    fn generate(&self, path: &Path) -> Option<RgbaImage> {
+        if let Some(cache) = &self.cache
+            && let Some(image) = cache.load(path)
+        {
+            return Some(fit_within(DynamicImage::ImageRgba8(image), self.size));
+        }
+
+        let image = match self.image_handler.load_image(path) {
+            Ok(image) => image,
+            Err(e) => {
+                warn!("サムネイルの生成に失敗: {:?} ({:?})", path, e);
+                return None;
+            }
+        };
+        let Some(cache) = &self.cache else {
+            return Some(fit_within(image, self.size));
+        };
+        let thumbnail = fit_within(image, cache.size());
+        if let Err(e) = cache.store(path, &thumbnail) {
+            warn!("サムネイルキャッシュへの保存に失敗: {:?} ({:?})", path, e);
+        }
+        Some(fit_within(DynamicImage::ImageRgba8(thumbnail), self.size))
+    }
+}
+
+/// 画像を指定したサイズに収まるよう縮小する（拡大はしない）
+fn fit_within(image: DynamicImage, size: u32) -> RgbaImage {
+    if image.width() > size || image.height() > size {
+        image.thumbnail(size, size).to_rgba8()
+    } else {
+        image.to_rgba8()
+    }
+}
+
+impl ThumbnailLoader {
+    /// 新しい ThumbnailLoader インスタンスを作成し、ワーカースレッドを起動する
+    ///
+    /// # Arguments
+    /// * `image_handler` - 画像の読み込みに使う画像ハンドラー
+    /// * `size` - サムネイルの最大辺の長さ（ピクセル）
+    /// * `cache` - ディスク上のサムネイルキャッシュ（None の場合は毎回生成する）
+    ///
+    /// # Returns
+    /// * `ThumbnailLoader` - サムネイルローダー
+    pub fn new(image_handler: Arc<ImageHandler>, size: u32, cache: Option<Arc<ThumbnailCache>>) -> Self {
+        let shared = Arc::new((
+            Mutex::new(SharedState {
+                pending: VecDeque::new(),
+                in_progress: HashSet::new(),
+                shutdown: false,
+            }),
+            Condvar::new(),
+        ));
+        let (sender, receiver) = mpsc::channel();
+
+        // 容量を超えたキャッシュの削除は時間がかかることがあるため、別スレッドで行う
+        if let Some(cache) = cache.clone() {
+            let spawned = thread::Builder::new()
+                .name("thumbnail-cache-eviction".to_string())
+                .spawn(move || {
+                    if let Err(e) = cache.evict() {
+                        warn!("サムネイルキャッシュの整理に失敗: {:?}", e);
+                    }
+                });
+            if let Err(e) = spawned {
+                warn!("サムネイルキャッシュ整理スレッドの起動に失敗: {:?}", e);
+            }
+        }
+        let generator = Arc::new(Generator { image_handler, cache, size });
+
+        let workers = thread::available_parallelism().map_or(2, |n| n.get()).clamp(1, MAX_WORKERS);
+        for worker in 0..workers {
+            let shared = shared.clone();
+            let sender = sender.clone();
+            let generator = generator.clone();
+            let spawned = thread::Builder::new()
+                .name(format!("thumbnail-{}", worker))
+                .spawn(move || Self::worker_loop(&shared, &sender, &generator));
+            if let Err(e) = spawned {
+                warn!("サムネイル生成スレッドの起動に失敗: {:?}", e);
+            }
+        }
+        debug!("サムネイル生成スレッドを起動しました: {}個", workers);
+
+        ThumbnailLoader { shared, receiver }
+    }
+
+    /// ワーカースレッドの処理
+    fn worker_loop(
+        shared: &(Mutex<SharedState>, Condvar),
+        sender: &Sender<ThumbnailResult>,
+        generator: &Generator,
+    ) {
+        let (lock, condvar) = shared;
+        loop {
+            let (index, path) = {
+                let mut state = lock.lock().unwrap();
+                loop {
+                    if state.shutdown {
+                        return;
+                    }
+                    if let Some(job) = state.pending.pop_front() {
+                        state.in_progress.insert(job.0);
+                        break job;
+                    }
+                    state = condvar.wait(state).unwrap();
+                }
+            };
+
+            let image = generator.generate(&path);
+            lock.lock().unwrap().in_progress.remove(&index);
+            if sender.send(ThumbnailResult { index, image }).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// 生成待ちのジョブを置き換える
+    ///
+    /// 生成中のジョブは重複して登録しない
+    ///
+    /// # Arguments
+    /// * `jobs` - 優先順に並んだインデックスとパスのリスト
+    pub fn request(&self, jobs: Vec<(usize, PathBuf)>) {
+        let (lock, condvar) = &*self.shared;
+        let mut guard = lock.lock().unwrap();
+        let state = &mut *guard;
+        state.pending = jobs.into_iter().filter(|(index, _)| !state.in_progress.contains(index)).collect();
+        condvar.notify_all();
+    }
+
+    /// サムネイルが1つ生成されるまで待つ
+    ///
+    /// # Returns
+    /// * `Option<ThumbnailResult>` - 生成結果（ワーカースレッドが終了している場合は None）
+    pub fn wait(&self) -> Option<ThumbnailResult> {
+        self.receiver.recv().ok()
+    }
+
+    /// 生成済みのサムネイルを受け取る
+    ///
+    /// # Returns
+    /// * `Vec<ThumbnailResult>` - 前回の呼び出し以降に生成されたサムネイル
+    pub fn poll(&self) -> Vec<ThumbnailResult> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+impl Drop for ThumbnailLoader {
+    fn drop(&mut self) {
+        let (lock, condvar) = &*self.shared;
+        if let Ok(mut state) = lock.lock() {
+            state.shutdown = true;
+            state.pending.clear();
+        }
+        condvar.notify_all();
+    }
+}
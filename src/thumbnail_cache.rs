@@ -0,0 +1,316 @@
+use anyhow::{Result, anyhow};
+use image::RgbaImage;
+use log::{debug, info, warn};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use crate::config::Config;
+
+/// freedesktop のサムネイル仕様で定められたサイズごとのディレクトリ名と一辺の長さ
+const SIZE_DIRECTORIES: &[(&str, u32)] = &[
+    ("normal", 128),
+    ("large", 256),
+    ("x-large", 512),
+    ("xx-large", 1024),
+];
+
+/// キャッシュ容量を超えたときに削減する目標の割合
+const EVICTION_TARGET_RATIO: f64 = 0.9;
+/// このアプリケーションが保存したサムネイルに記録する Software の値
+const SOFTWARE_NAME: &str = "rs_fast_image_viewer";
+
+/// ディスク上のサムネイルキャッシュ
+///
+/// freedesktop のサムネイル仕様に従い、`<キャッシュディレクトリ>/<サイズ>/<URIのMD5>.png` に保存する。
+/// 元ファイルのURI、更新日時、サイズをPNGのテキストチャンクに記録し、読み込み時に照合する
+pub struct ThumbnailCache {
+    /// サイズごとのサムネイルを保存するディレクトリ
+    directory: PathBuf,
+    /// 保存するサムネイルの一辺の長さ
+    size: u32,
+    /// キャッシュ容量の上限（バイト）
+    max_bytes: u64,
+}
+
+impl ThumbnailCache {
+    /// 設定からサムネイルキャッシュを作成する
+    ///
+    /// # Arguments
+    /// * `config` - アプリケーション設定
+    ///
+    /// # Returns
+    /// * `Option<ThumbnailCache>` - キャッシュが無効、または保存先が決められない場合は None
+    pub fn new(config: &Config) -> Option<Self> {
+        if !config.thumbnail_cache {
+            return None;
+        }
+        let Some(root) = config.thumbnail_cache_dir.clone().or_else(Self::default_root) else {
+            warn!("サムネイルキャッシュの保存先が見つからないため、キャッシュを使用しません");
+            return None;
+        };
+        let (name, size) = SIZE_DIRECTORIES
+            .iter()
+            .copied()
+            .find(|(_, size)| *size >= config.thumbnail_size)
+            .unwrap_or(SIZE_DIRECTORIES[SIZE_DIRECTORIES.len() - 1]);
+        let directory = root.join(name);
+        debug!("サムネイルキャッシュ: {:?}", directory);
+        Some(ThumbnailCache {
+            directory,
+            size,
+            max_bytes: config.thumbnail_cache_max_mb.saturating_mul(1024 * 1024),
+        })
+    }
+
+    /// 既定のキャッシュディレクトリを取得する
+    ///
+    /// Linux では他のアプリケーションと共有する `$XDG_CACHE_HOME/thumbnails` を使用する
+    ///
+    /// # Returns
+    /// * `Option<PathBuf>` - キャッシュディレクトリ
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn default_root() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("thumbnails"))
+    }
+
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    fn default_root() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("rs_fast_image_viewer").join("thumbnails"))
+    }
+
+    /// 保存するサムネイルの一辺の長さを取得する
+    ///
+    /// # Returns
+    /// * `u32` - 一辺の長さ（ピクセル）
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// キャッシュからサムネイルを読み込む
+    ///
+    /// 元ファイルの更新日時かサイズが記録と異なる場合は無効とする
+    ///
+    /// # Arguments
+    /// * `path` - 元画像のパス
+    ///
+    /// # Returns
+    /// * `Option<RgbaImage>` - 有効なサムネイルがある場合はその画像
+    pub fn load(&self, path: &Path) -> Option<RgbaImage> {
+        let (uri, mtime, file_size) = source_info(path)?;
+        let thumbnail_path = self.thumbnail_path(&uri);
+
+        let decoder = png::Decoder::new(BufReader::new(File::open(&thumbnail_path).ok()?));
+        let reader = decoder.read_info().ok()?;
+        let text = |keyword: &str| {
+            reader.info()
+                .uncompressed_latin1_text
+                .iter()
+                .find(|chunk| chunk.keyword == keyword)
+                .map(|chunk| chunk.text.clone())
+        };
+        if text("Thumb::MTime")? != mtime.to_string() {
+            return None;
+        }
+        // Thumb::Size は任意項目のため、記録されている場合のみ照合する
+        if text("Thumb::Size").is_some_and(|size| size != file_size.to_string()) {
+            return None;
+        }
+        drop(reader);
+
+        let image = image::open(&thumbnail_path).ok()?.to_rgba8();
+        debug!("サムネイルをキャッシュから読み込みました: {:?}", path);
+        Some(image)
+    }
+
+    /// サムネイルをキャッシュに保存する
+    ///
+    /// # Arguments
+    /// * `path` - 元画像のパス
+    /// * `image` - 保存するサムネイル
+    ///
+    /// # Returns
+    /// * `Result<()>` - 成功時は Ok(())
+    pub fn store(&self, path: &Path, image: &RgbaImage) -> Result<()> {
+        let (uri, mtime, file_size) = source_info(path)
+            .ok_or_else(|| anyhow!("元ファイルの情報を取得できません: {:?}", path))?;
+        self.create_directory()?;
+
+        // 書き込み途中のファイルを他のプロセスが読まないよう、一時ファイルに書いてから置き換える
+        let thumbnail_path = self.thumbnail_path(&uri);
+        let temp_path = thumbnail_path.with_extension(format!("png.{}.tmp", std::process::id()));
+        {
+            let mut encoder = png::Encoder::new(BufWriter::new(File::create(&temp_path)?), image.width(), image.height());
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.add_text_chunk("Thumb::URI".to_string(), uri)?;
+            encoder.add_text_chunk("Thumb::MTime".to_string(), mtime.to_string())?;
+            encoder.add_text_chunk("Thumb::Size".to_string(), file_size.to_string())?;
+            encoder.add_text_chunk("Software".to_string(), SOFTWARE_NAME.to_string())?;
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(image.as_raw())?;
+            writer.finish()?;
+        }
+        if let Err(e) = fs::rename(&temp_path, &thumbnail_path) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        debug!("サムネイルをキャッシュに保存しました: {:?}", path);
+        Ok(())
+    }
+
+    /// キャッシュディレクトリを作成する（仕様に従い所有者のみアクセス可能にする）
+    fn create_directory(&self) -> Result<()> {
+        if self.directory.is_dir() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.directory)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&self.directory, fs::Permissions::from_mode(0o700))?;
+        }
+        Ok(())
+    }
+
+    /// 元ファイルのURIに対応するサムネイルのパスを取得する
+    fn thumbnail_path(&self, uri: &str) -> PathBuf {
+        self.directory.join(format!("{:x}.png", md5::compute(uri.as_bytes())))
+    }
+
+    /// キャッシュ容量の上限を超えている場合、古いサムネイルから削除する
+    ///
+    /// キャッシュディレクトリは他のアプリケーションと共有することがあるため、
+    /// このアプリケーションが保存したサムネイルだけを数え、削除する
+    ///
+    /// # Returns
+    /// * `Result<()>` - 成功時は Ok(())
+    pub fn evict(&self) -> Result<()> {
+        if !self.directory.is_dir() {
+            return Ok(());
+        }
+        let mut entries: Vec<(PathBuf, u64, std::time::SystemTime)> = fs::read_dir(&self.directory)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|e| e == "png"))
+            .filter(|entry| is_own_thumbnail(&entry.path()))
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                let modified = metadata.modified().ok()?;
+                Some((entry.path(), metadata.len(), modified))
+            })
+            .collect();
+
+        let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+        if total <= self.max_bytes {
+            debug!("サムネイルキャッシュの使用量: {} バイト", total);
+            return Ok(());
+        }
+
+        let target = (self.max_bytes as f64 * EVICTION_TARGET_RATIO) as u64;
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        let mut removed = 0;
+        for (path, len, _) in entries {
+            if total <= target {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= len;
+                removed += 1;
+            }
+        }
+        info!("サムネイルキャッシュから{}個のファイルを削除しました（使用量: {} バイト）", removed, total);
+        Ok(())
+    }
+}
+
+/// このアプリケーションが保存したサムネイルかどうかを Software テキストチャンクで判定する
+///
+/// # Arguments
+/// * `path` - サムネイルのパス
+///
+/// # Returns
+/// * `bool` - このアプリケーションが保存したサムネイルの場合は true
+fn is_own_thumbnail(path: &Path) -> bool {
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    let Ok(reader) = png::Decoder::new(BufReader::new(file)).read_info() else {
+        return false;
+    };
+    reader.info()
+        .uncompressed_latin1_text
+        .iter()
+        .any(|chunk| chunk.keyword == "Software" && chunk.text == SOFTWARE_NAME)
+}
+
+/// 元ファイルのURI、更新日時（UNIX秒）、サイズを取得する
+fn source_info(path: &Path) -> Option<(String, u64, u64)> {
+    let absolute = fs::canonicalize(path).ok()?;
+    let metadata = fs::metadata(&absolute).ok()?;
+    let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some((file_uri(&absolute), mtime, metadata.len()))
+}
+
+/// 絶対パスを `file://` 形式のURIに変換する
+///
+/// # Arguments
+/// * `path` - 絶対パス
+///
+/// # Returns
+/// * `String` - パーセントエンコードしたURI
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    // Windows の拡張パス接頭辞を取り除く
+    let path = path.strip_prefix("//?/").unwrap_or(&path);
+    let mut uri = String::from(if path.starts_with('/') { "file://" } else { "file:///" });
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9'
+            | b'-' | b'_' | b'.' | b'~' | b'/' | b'!' | b'$' | b'&' | b'\''
+            | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=' | b':' | b'@' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_uri_keeps_unreserved_characters() {
+        assert_eq!(file_uri(Path::new("/home/user/a-b_c.~(1).png")), "file:///home/user/a-b_c.~(1).png");
+    }
+
+    #[test]
+    fn file_uri_percent_encodes_spaces_and_non_ascii() {
+        assert_eq!(file_uri(Path::new("/tmp/my image#1%.png")), "file:///tmp/my%20image%231%25.png");
+        assert_eq!(file_uri(Path::new("/写真/a.png")), "file:///%E5%86%99%E7%9C%9F/a.png");
+    }
+
+    #[test]
+    fn file_uri_converts_windows_paths() {
+        assert_eq!(file_uri(Path::new(r"\\?\C:\Users\a b.png")), "file:///C:/Users/a%20b.png");
+    }
+
+    #[test]
+    fn evict_removes_only_own_thumbnails() {
+        let root = std::env::temp_dir().join(format!("thumbnail-cache-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let cache = ThumbnailCache { directory: root.join("normal"), size: 128, max_bytes: 0 };
+        let source = root.join("source.png");
+        fs::create_dir_all(&root).unwrap();
+        RgbaImage::new(8, 8).save(&source).unwrap();
+
+        cache.store(&source, &RgbaImage::new(4, 4)).unwrap();
+        let foreign = cache.directory.join("foreign.png");
+        RgbaImage::new(4, 4).save(&foreign).unwrap();
+        assert!(cache.load(&source).is_some());
+
+        cache.evict().unwrap();
+        assert!(cache.load(&source).is_none());
+        assert!(foreign.exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
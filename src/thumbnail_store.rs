@@ -0,0 +1,167 @@
+use log::debug;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::image_handler::{ImageFile, ImageHandler};
+use crate::overlay::{OverlayRenderer, Rect};
+use crate::thumbnail::ThumbnailLoader;
+use crate::thumbnail_cache::ThumbnailCache;
+
+/// GPUに保持するサムネイルの上限数
+const CACHE_LIMIT: usize = 600;
+/// サムネイルの背景色
+const CELL_COLOR: [f32; 4] = [0.02, 0.02, 0.02, 1.0];
+/// 文字色
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// サムネイルの状態
+enum ThumbnailState {
+    /// GPUに転送済み（バインドグループとサムネイルのサイズ）
+    Loaded(wgpu::BindGroup, (u32, u32)),
+    /// 読み込みに失敗した
+    Failed,
+}
+
+/// GPUに転送したサムネイルを管理する（サムネイル一覧とフィルムストリップで共用）
+///
+/// 表示範囲のサムネイルをローダーに要求し、生成されたものをテクスチャとして保持する
+pub struct ThumbnailStore {
+    /// 画像ファイルのパス
+    paths: Vec<PathBuf>,
+    /// サムネイルローダー
+    loader: ThumbnailLoader,
+    /// 転送済みのサムネイル
+    thumbnails: HashMap<usize, ThumbnailState>,
+    /// 直前に生成を要求した範囲
+    requested_range: Option<Range<usize>>,
+}
+
+impl ThumbnailStore {
+    /// 新しい ThumbnailStore インスタンスを作成する
+    ///
+    /// # Arguments
+    /// * `images` - 画像ファイルのリスト
+    /// * `decoder` - サムネイル生成に使う画像ハンドラー
+    /// * `cache` - ディスク上のサムネイルキャッシュ
+    /// * `size` - サムネイルの最大辺の長さ（ピクセル）
+    ///
+    /// # Returns
+    /// * `ThumbnailStore` - サムネイルの管理
+    pub fn new(
+        images: &[ImageFile],
+        decoder: Arc<ImageHandler>,
+        cache: Option<Arc<ThumbnailCache>>,
+        size: u32,
+    ) -> Self {
+        ThumbnailStore {
+            paths: images.iter().map(|image| image.path.clone()).collect(),
+            loader: ThumbnailLoader::new(decoder, size, cache),
+            thumbnails: HashMap::new(),
+            requested_range: None,
+        }
+    }
+
+    /// 画像の数を取得する
+    ///
+    /// # Returns
+    /// * `usize` - 画像の数
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// 生成済みのサムネイルをGPUに転送し、表示範囲のサムネイル生成を要求する
+    ///
+    /// # Arguments
+    /// * `device` - WGPU デバイス
+    /// * `queue` - WGPU キュー
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `visible` - 表示範囲
+    /// * `prefetch` - 表示範囲の前後に先読みする数
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        overlay: &OverlayRenderer,
+        visible: Range<usize>,
+        prefetch: usize,
+    ) {
+        for result in self.loader.poll() {
+            let state = match result.image {
+                Some(image) => {
+                    let size = image.dimensions();
+                    ThumbnailState::Loaded(overlay.create_image_texture(device, queue, &image), size)
+                }
+                None => ThumbnailState::Failed,
+            };
+            self.thumbnails.insert(result.index, state);
+        }
+
+        self.evict(&visible);
+        if self.requested_range.as_ref() != Some(&visible) {
+            let ahead = visible.end..(visible.end + prefetch).min(self.paths.len());
+            let behind = visible.start.saturating_sub(prefetch)..visible.start;
+            let jobs = visible.clone()
+                .chain(ahead)
+                .chain(behind.rev())
+                .filter(|index| !self.thumbnails.contains_key(index))
+                .map(|index| (index, self.paths[index].clone()))
+                .collect();
+            self.loader.request(jobs);
+            self.requested_range = Some(visible);
+        }
+    }
+
+    /// 上限を超えたサムネイルを表示範囲から遠いものから破棄する
+    fn evict(&mut self, visible: &Range<usize>) {
+        if self.thumbnails.len() <= CACHE_LIMIT {
+            return;
+        }
+        let distance = |index: usize| {
+            if index < visible.start {
+                visible.start - index
+            } else {
+                index.saturating_sub(visible.end)
+            }
+        };
+        let mut indices: Vec<usize> = self.thumbnails.keys().copied().collect();
+        indices.sort_by_key(|&index| std::cmp::Reverse(distance(index)));
+        let excess = self.thumbnails.len() - CACHE_LIMIT * 3 / 4;
+        for index in indices.into_iter().take(excess) {
+            self.thumbnails.remove(&index);
+        }
+        // 破棄したサムネイルを再び要求できるようにする
+        self.requested_range = None;
+        debug!("サムネイルを破棄しました: 残り{}個", self.thumbnails.len());
+    }
+
+    /// サムネイルを正方形のセルに描画する
+    ///
+    /// # Arguments
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `queue` - WGPU キュー
+    /// * `index` - 画像のインデックス
+    /// * `cell` - 描画するセル（ピクセル）
+    /// * `font_size` - 読み込み失敗時の文字サイズ（論理ピクセル）
+    pub fn draw(&self, overlay: &mut OverlayRenderer, queue: &wgpu::Queue, index: usize, cell: Rect, font_size: f32) {
+        overlay.rect(cell, CELL_COLOR);
+        match self.thumbnails.get(&index) {
+            Some(ThumbnailState::Loaded(bind_group, (width, height))) => {
+                // セル内にアスペクト比を保って中央に配置する
+                let scale = (cell.w / *width as f32).min(cell.h / *height as f32);
+                let (w, h) = (*width as f32 * scale, *height as f32 * scale);
+                let rect = Rect::new(
+                    (cell.x + (cell.w - w) / 2.0).round(),
+                    (cell.y + (cell.h - h) / 2.0).round(),
+                    w.round(),
+                    h.round(),
+                );
+                overlay.image(rect, bind_group);
+            }
+            Some(ThumbnailState::Failed) => {
+                overlay.text(queue, cell.x + 4.0, cell.y + 4.0, font_size, TEXT_COLOR, "(読み込み失敗)", Some(cell.w - 8.0));
+            }
+            None => {}
+        }
+    }
+}
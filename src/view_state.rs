@@ -0,0 +1,107 @@
+use anyhow::Result;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::config::{Background, Config, FitMode, SortAlgorithm};
+
+/// 終了時のウィンドウと表示設定の状態
+///
+/// ユーザーが編集する設定ファイルとは別のファイルに保存し、次回起動時に復元する
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewState {
+    /// ウィンドウの外側の左上の位置（物理ピクセル、取得できない環境では None）
+    pub window_position: Option<(i32, i32)>,
+    /// ウィンドウの内側のサイズ（物理ピクセル、最大化・全画面表示の前の大きさ）
+    pub window_size: Option<(u32, u32)>,
+    /// 最大化していたかどうか
+    pub maximized: bool,
+    /// 全画面表示だったかどうか
+    pub fullscreen: bool,
+    /// 画像の表示サイズの決め方
+    pub fit_mode: Option<FitMode>,
+    /// 画像の背景
+    pub background: Option<Background>,
+    /// ソートアルゴリズム
+    pub sort_algorithm: Option<SortAlgorithm>,
+}
+
+impl ViewState {
+    /// 状態ファイルを読み込む
+    ///
+    /// ファイルがない場合や壊れている場合は空の状態を返す
+    ///
+    /// # Arguments
+    /// * `state_path` - 状態ファイルのパス
+    ///
+    /// # Returns
+    /// * `ViewState` - 前回終了時の状態
+    pub fn load(state_path: &Path) -> Self {
+        let content = match fs::read_to_string(state_path) {
+            Ok(content) => content,
+            Err(_) => {
+                debug!("状態ファイルがありません: {:?}", state_path);
+                return ViewState::default();
+            }
+        };
+        match toml::from_str(&content) {
+            Ok(state) => {
+                info!("状態ファイルを読み込みました: {:?}", state_path);
+                state
+            }
+            Err(e) => {
+                warn!("状態ファイルの読み込みに失敗したため、無視します: {:?} ({:?})", state_path, e);
+                ViewState::default()
+            }
+        }
+    }
+
+    /// 状態ファイルを保存する
+    ///
+    /// # Arguments
+    /// * `state_path` - 状態ファイルのパス
+    ///
+    /// # Returns
+    /// * `Result<()>` - 成功時は Ok(())
+    pub fn save(&self, state_path: &Path) -> Result<()> {
+        let content = toml::to_string_pretty(self)?;
+        fs::write(state_path, content)?;
+        info!("状態ファイルを保存しました: {:?}", state_path);
+        Ok(())
+    }
+
+    /// 保存されている表示設定で設定を上書きする
+    ///
+    /// 設定ファイルで既定値から変更されている項目は、設定ファイルの値を優先する
+    ///
+    /// # Arguments
+    /// * `config` - 上書きするアプリケーション設定
+    pub fn apply_to(&self, config: &mut Config) {
+        let defaults = Config::default();
+        if let Some(fit_mode) = self.fit_mode
+            && config.fit_mode == defaults.fit_mode
+        {
+            config.fit_mode = fit_mode;
+        }
+        if let Some(background) = self.background
+            && config.background == defaults.background
+        {
+            config.background = background;
+        }
+        if let Some(sort_algorithm) = self.sort_algorithm
+            && config.sort_algorithm == defaults.sort_algorithm
+        {
+            config.sort_algorithm = sort_algorithm;
+        }
+    }
+
+    /// 実行ファイルと同じディレクトリの状態ファイルパスを取得する
+    ///
+    /// # Returns
+    /// * `Result<PathBuf>` - 状態ファイルのパス
+    pub fn get_state_path() -> Result<PathBuf> {
+        let config_path = Config::get_config_path()?;
+        Ok(config_path.with_file_name("rs_fast_image_viewer.state.toml"))
+    }
+}
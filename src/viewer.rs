@@ -1,25 +1,200 @@
 use anyhow::Result;
 use image::{DynamicImage, GenericImageView};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use std::path::{Path, PathBuf};
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::Instant;
 use winit::{
     application::ApplicationHandler,
-    event::WindowEvent,
+    event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::ModifiersState,
     event_loop::{ActiveEventLoop, EventLoop},
-    window::{Window, WindowId},
+    monitor::MonitorHandle,
+    window::{Fullscreen, Window, WindowId},
     keyboard::{KeyCode, PhysicalKey},
-    dpi::LogicalSize,
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
 };
 use wgpu::{
     Adapter, Device, Instance, Queue, Surface, SurfaceConfiguration,
     util::DeviceExt,
 };
-use crate::config::Config;
-use crate::image_handler::ImageHandler;
+use crate::adjustments::{Adjustment, ColorAdjustments};
+use crate::animation::Animation;
+use crate::config::{Background, ChannelView, CompareMode, Config, DifferenceView, FilmstripPosition, FitMode, LoupeShape, OsdPosition, Scope, ToneMapping, Transition};
+use crate::difference::DifferenceAnalyzer;
+use crate::filmstrip::Filmstrip;
+use crate::folder_compare::FolderComparison;
+use crate::gallery::{self, Gallery};
+use crate::histogram::Scopes;
+use crate::image_handler::{EXPORT_DIR_NAME, ImageHandler, create_unique_file};
+use crate::metadata::ImageMetadata;
+use crate::minimap::Minimap;
+use crate::osd::{self, ImageInfo};
+use crate::overlay::{OverlayRenderer, Rect};
+use crate::pixel_inspector::PixelSample;
+use crate::slideshow::Slideshow;
+use crate::spread::{self, SpreadLayout};
+use crate::thumbnail_cache::ThumbnailCache;
+use crate::view_state::ViewState;
+use crate::webtoon::Webtoon;
+
+/// 露出補正キー1回あたりの変化量（EV）
+const EXPOSURE_STEP: f32 = 0.5;
+/// 拡大・縮小キー1回あたりの倍率
+const ZOOM_STEP: f32 = 1.25;
+/// 表示サイズの決め方による倍率に掛ける拡大率の範囲
+const ZOOM_RANGE: (f32, f32) = (0.05, 64.0);
+/// 分割スライダーをつかめる距離（論理ピクセル）
+const SPLIT_GRAB_DISTANCE: f32 = 6.0;
+/// 拡大鏡の倍率の範囲
+const LOUPE_MAGNIFICATION_RANGE: (f32, f32) = (1.5, 32.0);
+/// 拡大鏡の縁の太さ（論理ピクセル）
+const LOUPE_BORDER: f32 = 2.0;
+/// モニターを取得できない場合に使う作業領域の大きさ（物理ピクセル）
+const DEFAULT_WORK_AREA: (u32, u32) = (1920, 1080);
 
 /// テクスチャ情報
 struct TextureInfo {
+    /// テクスチャ（アニメーションのフレームを書き込む）
+    texture: wgpu::Texture,
+    /// テクスチャフォーマット
+    format: wgpu::TextureFormat,
     bind_group: wgpu::BindGroup,
+    /// テクスチャの値がsRGBエンコードされたままかどうか
+    srgb_encoded: bool,
+    /// HDR（リニア浮動小数点）画像かどうか
+    is_hdr: bool,
+    /// テクスチャのサイズ（幅、高さ）
+    dimensions: (u32, u32),
+}
+
+/// GPUへ転送するテクスチャデータ
+struct TextureUpload {
+    /// テクスチャフォーマット
+    format: wgpu::TextureFormat,
+    /// ピクセルデータ
+    data: Vec<u8>,
+    /// 1ピクセルあたりのバイト数
+    bytes_per_pixel: u32,
+    /// 値がsRGBエンコードされたままかどうか（シェーダーでリニア化する）
+    srgb_encoded: bool,
+    /// HDR（リニア浮動小数点）画像かどうか
+    is_hdr: bool,
+}
+
+/// シェーダーに渡す表示パラメーター
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct DisplayUniforms {
+    /// 露出補正（EV）
+    exposure: f32,
+    /// トーンマッピング（0: なし, 1: Reinhard, 2: ACES）
+    tone_mapping: u32,
+    /// テクスチャの値がsRGBエンコードされたままかどうか
+    srgb_encoded: u32,
+    /// HDR画像かどうか
+    is_hdr: u32,
+    /// 画面全体に対する画像の表示サイズの比率
+    image_scale: [f32; 2],
+    /// 背景の種類（0: 単色, 1: 市松模様）
+    background: u32,
+    /// 市松模様の1マスの大きさ（ピクセル）
+    checker_size: f32,
+    /// 背景色（リニア）
+    background_color: [f32; 4],
+    /// 切り替え前の画像の表示サイズの比率
+    previous_scale: [f32; 2],
+    /// 切り替え前の画像の値がsRGBエンコードされたままかどうか
+    previous_srgb_encoded: u32,
+    /// 切り替え前の画像がHDR画像かどうか
+    previous_is_hdr: u32,
+    /// 切り替え効果（0: なし, 1: クロスフェード, 2: スライド, 3: ズーム）
+    transition: u32,
+    /// 切り替え効果の進み具合（0.0〜1.0）
+    progress: f32,
+    /// スライドの向き（1.0: 右から, -1.0: 左から）
+    direction: f32,
+    /// 比較表示（0: なし, 1: 左右に並べる, 2: 分割スライダー）
+    compare_mode: u32,
+    /// 比較する画像の表示サイズの比率
+    compare_scale: [f32; 2],
+    /// 比較する画像の値がsRGBエンコードされたままかどうか
+    compare_srgb_encoded: u32,
+    /// 比較する画像がHDR画像かどうか
+    compare_is_hdr: u32,
+    /// 拡大表示の移動量（表示領域に対する正規化デバイス座標）
+    view_offset: [f32; 2],
+    /// 分割スライダーの位置（ピクセル）
+    split_x: f32,
+    /// 差分表示の見せ方（0: 絶対値, 1: 増幅, 2: しきい値）
+    difference_view: u32,
+    /// 差分を増幅して表示するときの倍率
+    difference_amplification: f32,
+    /// 変化したピクセルとみなす差分のしきい値
+    difference_threshold: f32,
+    /// 色補正を行うかどうか
+    adjust: u32,
+    /// チャンネルの表示方法（0: 通常, 1: R, 2: G, 3: B, 4: A, 5: ストレートアルファ, 6: 乗算済みアルファ, 7: アルファマスク）
+    channel_view: u32,
+    /// 明るさ（加算する値）
+    brightness: f32,
+    /// コントラスト（中間の灰色を中心にした倍率）
+    contrast: f32,
+    /// ガンマ
+    gamma: f32,
+    /// 彩度（倍率）
+    saturation: f32,
+    /// 拡大鏡の中心（ピクセル）
+    loupe_center: [f32; 2],
+    /// 拡大鏡の直径または一辺の長さ（ピクセル）
+    loupe_size: f32,
+    /// 拡大鏡の倍率
+    loupe_magnification: f32,
+    /// 画面全体の大きさ（ピクセル）
+    surface_size: [f32; 2],
+    /// 拡大鏡の形（0: 円形, 1: 四角形）
+    loupe_shape: u32,
+    /// 拡大鏡の縁の太さ（ピクセル）
+    loupe_border: f32,
+}
+
+/// 描画するテクスチャと元画像の幅と高さ
+type ShownTexture<'a> = (&'a TextureInfo, (u32, u32));
+
+/// 比較する画像
+struct CompareImage {
+    /// テクスチャ
+    texture: TextureInfo,
+    /// 元画像の幅と高さ
+    size: (u32, u32),
+    /// ファイル名
+    name: String,
+    /// 差分の統計の計算に使う画像
+    image: Arc<DynamicImage>,
+}
+
+/// マウスのドラッグ操作
+#[derive(Debug, Clone, Copy)]
+enum Drag {
+    /// 拡大表示の移動（直前のカーソル位置）
+    Pan((f32, f32)),
+    /// 分割スライダーの移動
+    Split,
+    /// ミニマップ上での表示範囲の移動
+    Minimap,
+}
+
+/// 画像の切り替え効果の状態
+struct TransitionState {
+    /// 切り替え効果の種類
+    kind: Transition,
+    /// 切り替え前の画像のテクスチャ（効果が終わるまで保持する）
+    previous: TextureInfo,
+    /// 切り替えを始めた時刻
+    started: Instant,
+    /// スライドの向き（1.0: 次の画像へ, -1.0: 前の画像へ）
+    direction: f32,
 }
 
 /// 画像ビューアー
@@ -40,12 +215,124 @@ pub struct ImageViewer {
     config: Option<SurfaceConfiguration>,
     /// レンダーパイプライン
     render_pipeline: Option<wgpu::RenderPipeline>,
+    /// 拡大鏡のレンダーパイプライン
+    loupe_pipeline: Option<wgpu::RenderPipeline>,
     /// サンプラー
     sampler: Option<wgpu::Sampler>,
     /// バインドグループレイアウト
     bind_group_layout: Option<wgpu::BindGroupLayout>,
     /// 現在のテクスチャ
     current_texture: Option<TextureInfo>,
+    /// 表示パラメーターのユニフォームバッファ
+    display_buffer: Option<wgpu::Buffer>,
+    /// 表示パラメーターのバインドグループ
+    display_bind_group: Option<wgpu::BindGroup>,
+    /// Rgba32Float テクスチャをフィルタリング付きでサンプリングできるかどうか
+    float32_filterable: bool,
+    /// 現在の露出補正（EV）
+    exposure: f32,
+    /// 現在のトーンマッピング
+    tone_mapping: ToneMapping,
+    /// 文字や矩形を重ねて描画するオーバーレイ
+    overlay: Option<OverlayRenderer>,
+    /// メタデータパネルを表示するかどうか
+    show_metadata: bool,
+    /// 現在の画像のメタデータ（パネル表示中のみ読み込む）
+    metadata: Option<ImageMetadata>,
+    /// 現在の画像の情報（OSD表示用、読み込み失敗時は None）
+    image_info: Option<ImageInfo>,
+    /// OSDを表示するかどうか
+    show_osd: bool,
+    /// OSDの表示を開始した時刻（自動で隠す判定に使う）
+    osd_shown_at: Instant,
+    /// サムネイル一覧（最初に開いたときに作成する）
+    gallery: Option<Gallery>,
+    /// サムネイル一覧を表示中かどうか
+    gallery_mode: bool,
+    /// フィルムストリップ（最初に表示するときに作成する）
+    filmstrip: Option<Filmstrip>,
+    /// フィルムストリップを表示するかどうか
+    show_filmstrip: bool,
+    /// マウスカーソルの位置（ピクセル）
+    cursor_position: (f32, f32),
+    /// 直前に画像をクリックした時刻（ダブルクリック判定用）
+    last_click: Option<Instant>,
+    /// ウィンドウがあるモニター（別のモニターへの移動を検出する）
+    monitor: Option<MonitorHandle>,
+    /// 画像の表示サイズの決め方
+    fit_mode: FitMode,
+    /// 画像の背景
+    background: Background,
+    /// 終了時に状態ファイルへ保存する状態（保存しない設定の場合は None）
+    view_state: Option<ViewState>,
+    /// 表示中のアニメーション画像の再生状態（静止画の場合は None）
+    animation: Option<Animation>,
+    /// スライドショー（実行していない場合は None）
+    slideshow: Option<Slideshow>,
+    /// 画像の切り替え効果（切り替え中でない場合は None）
+    transition: Option<TransitionState>,
+    /// 最後に読み込んだ画像のインデックス（切り替え効果の向きの判定に使う）
+    loaded_index: Option<usize>,
+    /// 見開き表示中かどうか
+    spread: bool,
+    /// 右から左へ読む（右綴じ）かどうか
+    right_to_left: bool,
+    /// 見開き表示のページの組み合わせ
+    spread_layout: SpreadLayout,
+    /// 表示中のページの範囲（見開き表示中は2ページになることがある）
+    shown_pages: Range<usize>,
+    /// 縦読み表示（最初に開いたときに作成する）
+    webtoon: Option<Webtoon>,
+    /// 縦読み表示中かどうか
+    webtoon_mode: bool,
+    /// 表示サイズの決め方による倍率に掛ける拡大率
+    view_zoom: f32,
+    /// 拡大表示の移動量（表示領域の中心からのずれ、ピクセル）
+    pan: (f32, f32),
+    /// ドラッグ中の操作
+    drag: Option<Drag>,
+    /// 比較する画像（固定していない場合は None）
+    compare: Option<CompareImage>,
+    /// 起動時に読み込む比較する画像のパス
+    compare_path: Option<PathBuf>,
+    /// 比較表示の方法（比較表示中でない場合は None）
+    compare_mode: Option<CompareMode>,
+    /// 分割スライダーの位置（画面の幅に対する割合）
+    split_position: f32,
+    /// 比較する2枚の左右を入れ替えているかどうか
+    compare_swapped: bool,
+    /// 2つのディレクトリを比較している場合のファイル名の対応付け
+    folder_comparison: Option<FolderComparison>,
+    /// 表示中の画像（差分の統計の計算と、ピクセルインスペクターとヒストグラムでの値の読み取りに使う）
+    source_image: Option<Arc<DynamicImage>>,
+    /// ピクセルインスペクターを表示するかどうか
+    show_inspector: bool,
+    /// 画像が表示領域に収まらないときにミニマップを表示するかどうか
+    show_minimap: bool,
+    /// 画像全体と表示範囲を示すミニマップ
+    minimap: Minimap,
+    /// 画像に重ねて表示する分析パネル
+    scope: Scope,
+    /// ヒストグラムの縦軸を対数にするかどうか
+    scope_log_scale: bool,
+    /// ヒストグラムと波形モニター（初めて表示するまでは None）
+    scopes: Option<Scopes>,
+    /// 押されている修飾キー
+    modifiers: ModifiersState,
+    /// 表示の色補正
+    adjustments: ColorAdjustments,
+    /// 色補正パネルを表示するかどうか
+    show_adjustments: bool,
+    /// チャンネルの表示方法
+    channel_view: ChannelView,
+    /// 拡大鏡を表示しているかどうか（キーを押している間だけ表示する）
+    show_loupe: bool,
+    /// 拡大鏡の倍率
+    loupe_magnification: f32,
+    /// 差分表示の見せ方
+    difference_view: DifferenceView,
+    /// 表示中の画像と比較する画像の差分の統計を計算するワーカー（初めて差分を表示するまでは None）
+    difference: Option<DifferenceAnalyzer>,
     /// 頂点バッファ
     vertex_buffer: Option<wgpu::Buffer>,
     /// インデックスバッファ
@@ -101,10 +388,11 @@ impl ImageViewer {
     /// # Arguments
     /// * `config` - アプリケーション設定
     /// * `image_handler` - 画像ハンドラー
+    /// * `view_state` - 前回終了時の状態（保存しない設定の場合は None）
     /// 
     /// # Returns
     /// * `ImageViewer` - 画像ビューアー
-    pub fn new(config: Config, image_handler: ImageHandler) -> Self {
+    pub fn new(config: Config, image_handler: ImageHandler, view_state: Option<ViewState>) -> Self {
         info!("画像ビューアーを初期化中...");
 
         // WGPU インスタンスを作成
@@ -122,9 +410,65 @@ impl ImageViewer {
             queue: None,
             config: None,
             render_pipeline: None,
+            loupe_pipeline: None,
             sampler: None,
             bind_group_layout: None,
             current_texture: None,
+            display_buffer: None,
+            display_bind_group: None,
+            float32_filterable: false,
+            exposure: config.exposure,
+            tone_mapping: config.tone_mapping,
+            overlay: None,
+            show_metadata: false,
+            metadata: None,
+            image_info: None,
+            show_osd: config.show_osd,
+            osd_shown_at: Instant::now(),
+            gallery: None,
+            gallery_mode: false,
+            filmstrip: None,
+            show_filmstrip: config.show_filmstrip,
+            cursor_position: (0.0, 0.0),
+            last_click: None,
+            monitor: None,
+            fit_mode: config.fit_mode,
+            background: config.background,
+            view_state,
+            animation: None,
+            slideshow: config.slideshow.then(|| Self::create_slideshow(&config)),
+            transition: None,
+            loaded_index: None,
+            spread: config.spread,
+            right_to_left: config.right_to_left,
+            spread_layout: SpreadLayout::new(config.spread_cover_alone),
+            shown_pages: 0..0,
+            webtoon: None,
+            webtoon_mode: false,
+            view_zoom: 1.0,
+            pan: (0.0, 0.0),
+            drag: None,
+            compare: None,
+            compare_path: None,
+            compare_mode: None,
+            split_position: 0.5,
+            compare_swapped: false,
+            folder_comparison: None,
+            source_image: None,
+            show_inspector: false,
+            show_minimap: config.show_minimap,
+            minimap: Minimap::new(),
+            scope: config.scope,
+            scope_log_scale: config.scope_log_scale,
+            scopes: None,
+            modifiers: ModifiersState::empty(),
+            adjustments: ColorAdjustments::from_config(&config),
+            show_adjustments: false,
+            channel_view: config.channel_view,
+            show_loupe: false,
+            loupe_magnification: config.loupe_magnification.clamp(LOUPE_MAGNIFICATION_RANGE.0, LOUPE_MAGNIFICATION_RANGE.1),
+            difference_view: config.difference_view,
+            difference: None,
             vertex_buffer: None,
             index_buffer: None,
             app_config: config,
@@ -148,12 +492,21 @@ impl ImageViewer {
             })
             .await?;
 
+        // HDR画像をそのままの精度でサンプリングできる場合は有効にする
+        let float32_filterable = adapter.features().contains(wgpu::Features::FLOAT32_FILTERABLE);
+        let required_features = if float32_filterable {
+            wgpu::Features::FLOAT32_FILTERABLE
+        } else {
+            wgpu::Features::empty()
+        };
+
         // デバイスとキューを取得
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
+                    required_features,
+                    // 大きな画像を扱えるよう、テクスチャサイズの上限はアダプターに合わせる
+                    required_limits: wgpu::Limits::default().using_resolution(adapter.limits()),
                     memory_hints: wgpu::MemoryHints::Performance,
                     label: None,
                     trace: wgpu::Trace::Off,
@@ -211,15 +564,33 @@ impl ImageViewer {
             label: Some("texture_bind_group_layout"),
         });
 
+        // 表示パラメーターのバインドグループレイアウトを作成
+        let display_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("display_bind_group_layout"),
+        });
+
         // レンダーパイプラインを作成
         let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
+            bind_group_layouts: &[&bind_group_layout, &display_bind_group_layout, &bind_group_layout, &bind_group_layout],
             push_constant_ranges: &[],
         });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
+        // 画像と拡大鏡は同じシェーダーのフラグメントシェーダーだけを替えて描画する
+        let create_pipeline = |label: &str, entry_point: &str| device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
             layout: Some(&render_pipeline_layout),
             cache: None,
             vertex: wgpu::VertexState {
@@ -230,7 +601,7 @@ impl ImageViewer {
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
-                entry_point: Some("fs_main"),
+                entry_point: Some(entry_point),
                 targets: &[Some(wgpu::ColorTargetState {
                     format: surface_config.format,
                     blend: Some(wgpu::BlendState {
@@ -258,6 +629,8 @@ impl ImageViewer {
             },
             multiview: None,
         });
+        let render_pipeline = create_pipeline("Render Pipeline", "fs_main");
+        let loupe_pipeline = create_pipeline("Loupe Pipeline", "fs_loupe");
 
         // サンプラーを作成
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
@@ -284,29 +657,80 @@ impl ImageViewer {
             usage: wgpu::BufferUsages::INDEX,
         });
 
+        // 表示パラメーターのユニフォームバッファを作成
+        let display_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Display Uniform Buffer"),
+            size: std::mem::size_of::<DisplayUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let display_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &display_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: display_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("display_bind_group"),
+        });
+
+        // オーバーレイを作成
+        let overlay = OverlayRenderer::new(
+            &device,
+            &queue,
+            surface_format,
+            self.app_config.overlay_font_path.as_deref(),
+        );
+
         self.surface = Some(surface);
         self.adapter = Some(adapter);
         self.device = Some(device);
         self.queue = Some(queue);
         self.config = Some(surface_config);
         self.render_pipeline = Some(render_pipeline);
+        self.loupe_pipeline = Some(loupe_pipeline);
         self.sampler = Some(sampler);
         self.bind_group_layout = Some(bind_group_layout);
+        self.display_buffer = Some(display_buffer);
+        self.display_bind_group = Some(display_bind_group);
+        self.float32_filterable = float32_filterable;
         self.vertex_buffer = Some(vertex_buffer);
         self.index_buffer = Some(index_buffer);
+        self.overlay = Some(overlay);
+        self.update_display_uniforms();
 
         info!("WGPUの初期化完了");
         Ok(())
     }
 
-    /// 画像を読み込んでテクスチャを作成する
+    /// 画像を読み込んで現在のテクスチャにする
     /// 
     /// # Arguments
     /// * `image` - 読み込む画像
     /// 
     /// # Returns
     /// * `Result<()>` - 成功時は Ok(())
-    pub fn load_texture(&mut self, image: DynamicImage) -> Result<()> {
+    pub fn load_texture(&mut self, image: &DynamicImage) -> Result<()> {
+        let texture = self.create_texture(image)?;
+        let dimensions = texture.dimensions;
+        self.current_texture = Some(texture);
+        self.update_display_uniforms();
+
+        // ウィンドウサイズを調整
+        self.adjust_window_size(dimensions.0, dimensions.1)?;
+        Ok(())
+    }
+
+    /// 画像からテクスチャを作成する
+    /// 
+    /// # Arguments
+    /// * `image` - 転送する画像
+    /// 
+    /// # Returns
+    /// * `Result<TextureInfo>` - 作成したテクスチャ
+    fn create_texture(&self, image: &DynamicImage) -> Result<TextureInfo> {
         debug!("テクスチャを作成中...");
 
         let device = self.device.as_ref().unwrap();
@@ -314,7 +738,21 @@ impl ImageViewer {
         let bind_group_layout = self.bind_group_layout.as_ref().unwrap();
         let sampler = self.sampler.as_ref().unwrap();
 
-        let rgba = image.to_rgba8();
+        // GPUのテクスチャサイズ上限を超える場合は縮小する
+        let max_dimension = device.limits().max_texture_dimension_2d;
+        let resized;
+        let image = if image.width() > max_dimension || image.height() > max_dimension {
+            info!(
+                "画像がテクスチャサイズの上限を超えるため縮小します: {}x{} (上限 {})",
+                image.width(), image.height(), max_dimension
+            );
+            resized = image.resize(max_dimension, max_dimension, image::imageops::FilterType::Triangle);
+            &resized
+        } else {
+            image
+        };
+
+        let upload = Self::prepare_texture_upload(image, self.float32_filterable);
         let dimensions = image.dimensions();
 
         let texture_size = wgpu::Extent3d {
@@ -328,7 +766,7 @@ impl ImageViewer {
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            format: upload.format,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
             label: Some("image_texture"),
             view_formats: &[],
@@ -341,10 +779,10 @@ impl ImageViewer {
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            &rgba,
+            &upload.data,
             wgpu::TexelCopyBufferLayout {
                 offset: 0,
-                bytes_per_row: Some(4 * dimensions.0),
+                bytes_per_row: Some(upload.bytes_per_pixel * dimensions.0),
                 rows_per_image: Some(dimensions.1),
             },
             texture_size,
@@ -367,15 +805,552 @@ impl ImageViewer {
             label: Some("texture_bind_group"),
         });
 
-        self.current_texture = Some(TextureInfo {
+        debug!("テクスチャフォーマット: {:?}", upload.format);
+        debug!("テクスチャの作成完了");
+        Ok(TextureInfo {
+            texture,
+            format: upload.format,
             bind_group,
+            srgb_encoded: upload.srgb_encoded,
+            is_hdr: upload.is_hdr,
+            dimensions,
+        })
+    }
+
+    /// 画像のビット深度に応じてテクスチャフォーマットを選択し、転送用データを作成する
+    /// 
+    /// # Arguments
+    /// * `image` - 転送する画像
+    /// * `float32_filterable` - Rgba32Float をフィルタリング付きでサンプリングできるかどうか
+    /// 
+    /// # Returns
+    /// * `TextureUpload` - 転送用データ
+    fn prepare_texture_upload(image: &DynamicImage, float32_filterable: bool) -> TextureUpload {
+        let to_f16_bytes = |values: &mut dyn Iterator<Item = f32>| -> Vec<u8> {
+            let bits: Vec<u16> = values.map(|v| half::f16::from_f32(v).to_bits()).collect();
+            bytemuck::cast_slice(&bits).to_vec()
+        };
+
+        match image {
+            // 浮動小数点画像（EXR/HDR/32bit PSD）はリニアHDR値として扱う
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+                let rgba = image.to_rgba32f();
+                if float32_filterable {
+                    TextureUpload {
+                        format: wgpu::TextureFormat::Rgba32Float,
+                        data: bytemuck::cast_slice(rgba.as_raw()).to_vec(),
+                        bytes_per_pixel: 16,
+                        srgb_encoded: false,
+                        is_hdr: true,
+                    }
+                } else {
+                    TextureUpload {
+                        format: wgpu::TextureFormat::Rgba16Float,
+                        data: to_f16_bytes(&mut rgba.as_raw().iter().copied()),
+                        bytes_per_pixel: 8,
+                        srgb_encoded: false,
+                        is_hdr: true,
+                    }
+                }
+            }
+            // 16bit画像はsRGBエンコードされた値のまま半精度浮動小数点で転送する
+            DynamicImage::ImageLuma16(_)
+            | DynamicImage::ImageLumaA16(_)
+            | DynamicImage::ImageRgb16(_)
+            | DynamicImage::ImageRgba16(_) => {
+                let rgba = image.to_rgba16();
+                TextureUpload {
+                    format: wgpu::TextureFormat::Rgba16Float,
+                    data: to_f16_bytes(&mut rgba.as_raw().iter().map(|&v| v as f32 / 65535.0)),
+                    bytes_per_pixel: 8,
+                    srgb_encoded: true,
+                    is_hdr: false,
+                }
+            }
+            _ => TextureUpload {
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                data: image.to_rgba8().into_raw(),
+                bytes_per_pixel: 4,
+                srgb_encoded: false,
+                is_hdr: false,
+            },
+        }
+    }
+
+    /// 表示パラメーターをユニフォームバッファに書き込む
+    fn update_display_uniforms(&self) {
+        if let (Some(queue), Some(buffer)) = (&self.queue, &self.display_buffer) {
+            let (primary, compare) = self.display_textures();
+            let (srgb_encoded, is_hdr) = primary.map_or((false, false), |(t, _)| (t.srgb_encoded, t.is_hdr));
+            let viewport = self.viewport_size().unwrap_or((1.0, 1.0));
+            let transition = self.transition.as_ref().map(|transition| {
+                (transition, self.transition_progress(transition))
+            });
+            let previous = transition.map(|(transition, _)| &transition.previous);
+            let scale_factor = self.window.as_ref().map_or(1.0, |window| window.scale_factor() as f32);
+            let uniforms = DisplayUniforms {
+                exposure: self.exposure,
+                tone_mapping: match self.tone_mapping {
+                    ToneMapping::None => 0,
+                    ToneMapping::Reinhard => 1,
+                    ToneMapping::Aces => 2,
+                },
+                srgb_encoded: srgb_encoded as u32,
+                is_hdr: is_hdr as u32,
+                image_scale: self.image_scale(primary.map(|(_, size)| size)),
+                background: (self.background == Background::Checkerboard) as u32,
+                checker_size: 8.0 * scale_factor,
+                background_color: Self::background_color(self.background),
+                previous_scale: self.image_scale(previous.map(|texture| texture.dimensions)),
+                previous_srgb_encoded: previous.is_some_and(|texture| texture.srgb_encoded) as u32,
+                previous_is_hdr: previous.is_some_and(|texture| texture.is_hdr) as u32,
+                transition: match transition.map(|(transition, _)| transition.kind) {
+                    None | Some(Transition::None) => 0,
+                    Some(Transition::Crossfade) => 1,
+                    Some(Transition::Slide) => 2,
+                    Some(Transition::Zoom) => 3,
+                },
+                progress: transition.map_or(1.0, |(_, progress)| progress),
+                direction: transition.map_or(1.0, |(transition, _)| transition.direction),
+                compare_mode: match compare.and(self.compare_mode) {
+                    None => 0,
+                    Some(CompareMode::SideBySide) => 1,
+                    Some(CompareMode::Split) => 2,
+                    Some(CompareMode::Difference) => 3,
+                },
+                compare_scale: self.image_scale(compare.map(|(_, size)| size)),
+                compare_srgb_encoded: compare.is_some_and(|(texture, _)| texture.srgb_encoded) as u32,
+                compare_is_hdr: compare.is_some_and(|(texture, _)| texture.is_hdr) as u32,
+                view_offset: [self.pan.0 * 2.0 / viewport.0, -self.pan.1 * 2.0 / viewport.1],
+                split_x: self.split_position * self.config.as_ref().map_or(0.0, |config| config.width as f32),
+                difference_view: match self.difference_view {
+                    DifferenceView::Absolute => 0,
+                    DifferenceView::Amplified => 1,
+                    DifferenceView::Threshold => 2,
+                },
+                difference_amplification: self.app_config.difference_amplification,
+                difference_threshold: self.app_config.difference_threshold,
+                adjust: !self.adjustments.is_identity() as u32,
+                channel_view: match self.channel_view {
+                    ChannelView::Color => 0,
+                    ChannelView::Red => 1,
+                    ChannelView::Green => 2,
+                    ChannelView::Blue => 3,
+                    ChannelView::Alpha => 4,
+                    ChannelView::Straight => 5,
+                    ChannelView::Premultiplied => 6,
+                    ChannelView::AlphaMask => 7,
+                },
+                brightness: self.adjustments.brightness,
+                contrast: self.adjustments.contrast,
+                gamma: self.adjustments.gamma,
+                saturation: self.adjustments.saturation,
+                loupe_center: [self.cursor_position.0, self.cursor_position.1],
+                loupe_size: self.app_config.loupe_size.max(1.0) * scale_factor,
+                loupe_magnification: self.loupe_magnification,
+                surface_size: self.config.as_ref().map_or([1.0, 1.0], |config| [config.width.max(1) as f32, config.height.max(1) as f32]),
+                loupe_shape: match self.app_config.loupe_shape {
+                    LoupeShape::Circle => 0,
+                    LoupeShape::Square => 1,
+                },
+                loupe_border: LOUPE_BORDER * scale_factor,
+            };
+            queue.write_buffer(buffer, 0, bytemuck::bytes_of(&uniforms));
+        }
+    }
+
+    /// 表示中の画像の大きさを取得する
+    ///
+    /// # Returns
+    /// * `Option<(u32, u32)>` - 元画像の幅と高さ（画像がない場合は None）
+    fn image_size(&self) -> Option<(u32, u32)> {
+        let size = match &self.image_info {
+            Some(info) => (info.width, info.height),
+            None => self.current_texture.as_ref()?.dimensions,
+        };
+        (size.0 > 0 && size.1 > 0).then_some(size)
+    }
+
+    /// 表示サイズの決め方に従って、元画像に対する表示倍率を計算する
+    ///
+    /// # Arguments
+    /// * `size` - 画像の幅と高さ
+    ///
+    /// # Returns
+    /// * `Option<f32>` - 表示倍率（1.0 = 等倍、サーフェスがない場合は None）
+    fn display_scale(&self, (width, height): (u32, u32)) -> Option<f32> {
+        let (view_width, view_height) = self.viewport_size()?;
+        let fit = (view_width / width as f32).min(view_height / height as f32);
+        let scale = match self.fit_mode {
+            FitMode::Window | FitMode::Fit => fit,
+            FitMode::ShrinkOnly => fit.min(1.0),
+            FitMode::Original => 1.0,
+        };
+        Some(scale * self.view_zoom)
+    }
+
+    /// 1枚の画像を表示する領域の大きさを取得する
+    ///
+    /// # Returns
+    /// * `Option<(f32, f32)>` - 幅と高さ（ピクセル、左右に並べて比較している場合は画面の半分）
+    fn viewport_size(&self) -> Option<(f32, f32)> {
+        let config = self.config.as_ref()?;
+        let side_by_side = self.compare.is_some() && self.compare_mode == Some(CompareMode::SideBySide);
+        let width = if side_by_side { config.width as f32 / 2.0 } else { config.width as f32 };
+        Some((width.max(1.0), config.height.max(1) as f32))
+    }
+
+    /// 描画するテクスチャを取得する
+    ///
+    /// 比較表示中に左右を入れ替えている場合は、比較する画像を1枚目として扱う
+    ///
+    /// # Returns
+    /// * `(Option<ShownTexture>, Option<ShownTexture>)` - 1枚目と、比較表示中の場合は2枚目のテクスチャ
+    fn display_textures(&self) -> (Option<ShownTexture<'_>>, Option<ShownTexture<'_>>) {
+        let current = self.current_texture.as_ref().zip(self.image_size());
+        let compare = match (&self.compare, self.compare_mode, current) {
+            (Some(compare), Some(_), Some(_)) => (&compare.texture, compare.size),
+            _ => return (current, None),
+        };
+        if self.compare_swapped {
+            (Some(compare), current)
+        } else {
+            (current, Some(compare))
+        }
+    }
+
+    /// 画面全体に対する画像の表示サイズの比率を取得する
+    ///
+    /// # Arguments
+    /// * `size` - 画像の幅と高さ（画像がない場合は None）
+    ///
+    /// # Returns
+    /// * `[f32; 2]` - 幅と高さの比率（画像がない場合は [1.0, 1.0]）
+    fn image_scale(&self, size: Option<(u32, u32)>) -> [f32; 2] {
+        let scale = size.and_then(|size| self.display_scale(size));
+        match (scale, size, self.viewport_size()) {
+            (Some(scale), Some((width, height)), Some((view_width, view_height))) => [
+                width as f32 * scale / view_width,
+                height as f32 * scale / view_height,
+            ],
+            _ => [1.0, 1.0],
+        }
+    }
+
+    /// 背景の色を取得する
+    ///
+    /// # Arguments
+    /// * `background` - 背景
+    ///
+    /// # Returns
+    /// * `[f32; 4]` - リニアの色（市松模様の場合は画像の外側の色）
+    fn background_color(background: Background) -> [f32; 4] {
+        match background {
+            Background::Black => [0.0, 0.0, 0.0, 1.0],
+            // sRGB の 128
+            Background::Gray => [0.216, 0.216, 0.216, 1.0],
+            Background::White => [1.0, 1.0, 1.0, 1.0],
+            // sRGB の 51
+            Background::Checkerboard => [0.033, 0.033, 0.033, 1.0],
+        }
+    }
+
+    /// 拡大率を変更する
+    ///
+    /// 指定した位置に表示されている画像の点が動かないよう、移動量も合わせて変更する
+    ///
+    /// # Arguments
+    /// * `factor` - 現在の拡大率に掛ける倍率
+    /// * `anchor` - 基準にする位置（ピクセル、None の場合は表示領域の中心）
+    fn zoom_by(&mut self, factor: f32, anchor: Option<(f32, f32)>) {
+        let Some((view_width, view_height)) = self.viewport_size() else {
+            return;
+        };
+        // 左右に並べて比較している場合は、カーソルがある側の表示領域の中心を基準にする
+        let (x, y) = anchor.unwrap_or((view_width / 2.0, view_height / 2.0));
+        let center_x = if x >= view_width { view_width * 1.5 } else { view_width / 2.0 };
+        let (dx, dy) = (x - center_x, y - view_height / 2.0);
+
+        let zoom = (self.view_zoom * factor).clamp(ZOOM_RANGE.0, ZOOM_RANGE.1);
+        let applied = zoom / self.view_zoom;
+        self.pan = (dx - (dx - self.pan.0) * applied, dy - (dy - self.pan.1) * applied);
+        self.view_zoom = zoom;
+        self.update_display_uniforms();
+        self.show_osd_temporarily();
+        debug!("表示倍率: {:.1}%", self.zoom() * 100.0);
+    }
+
+    /// 拡大率と移動量を表示サイズの決め方に従った状態に戻す
+    fn reset_view(&mut self) {
+        self.view_zoom = 1.0;
+        self.pan = (0.0, 0.0);
+        self.update_display_uniforms();
+    }
+
+    /// 起動時に比較表示する画像を設定する
+    ///
+    /// # Arguments
+    /// * `path` - 比較する画像ファイルのパス
+    pub fn set_compare_path(&mut self, path: PathBuf) {
+        self.compare_path = Some(path);
+    }
+
+    /// 画像を読み込んで比較する画像として固定し、比較表示を始める
+    ///
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    fn load_compare_image(&mut self, path: &Path) {
+        if self.replace_compare_image(path) {
+            info!("比較する画像を固定しました: {:?}", path);
+            self.compare_swapped = false;
+            if self.compare_mode.is_none() {
+                self.compare_mode = Some(self.app_config.compare_mode);
+            }
+            self.refresh_compare_view();
+        }
+    }
+
+    /// 画像を読み込んで比較する画像のテクスチャを置き換える
+    ///
+    /// # Arguments
+    /// * `path` - 画像ファイルのパス
+    ///
+    /// # Returns
+    /// * `bool` - 読み込めた場合は true
+    fn replace_compare_image(&mut self, path: &Path) -> bool {
+        let name = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+        let result = self.image_handler.load_image(path).and_then(|image| {
+            let size = image.dimensions();
+            Ok((self.create_texture(&image)?, size, image))
         });
+        match result {
+            Ok((texture, size, image)) => {
+                self.compare = Some(CompareImage { texture, size, name, image: Arc::new(image) });
+                true
+            }
+            Err(e) => {
+                error!("比較する画像の読み込みに失敗: {:?} ({:?})", path, e);
+                false
+            }
+        }
+    }
 
-        // ウィンドウサイズを調整
-        self.adjust_window_size(dimensions.0, dimensions.1)?;
+    /// 2つのディレクトリの同じ名前の画像どうしを比較する
+    ///
+    /// # Arguments
+    /// * `comparison` - ファイル名で対応付けた比較
+    pub fn set_folder_comparison(&mut self, comparison: FolderComparison) {
+        self.folder_comparison = Some(comparison);
+        self.compare_mode = Some(self.app_config.compare_mode);
+    }
 
-        debug!("テクスチャの作成完了");
-        Ok(())
+    /// 比較表示の方法を切り替える（左右に並べる → 分割スライダー → 差分 → 比較しない）
+    fn cycle_compare_mode(&mut self) {
+        if self.compare.is_none() {
+            info!("比較する画像がありません（P で表示中の画像を固定します）");
+            return;
+        }
+        self.compare_mode = match self.compare_mode {
+            None => Some(CompareMode::SideBySide),
+            Some(CompareMode::SideBySide) => Some(CompareMode::Split),
+            Some(CompareMode::Split) => Some(CompareMode::Difference),
+            Some(CompareMode::Difference) => None,
+        };
+        info!("比較表示: {:?}", self.compare_mode);
+        self.refresh_compare_view();
+    }
+
+    /// 比較表示の方法や比較する画像が変わったときに、表示と差分の統計を更新する
+    fn refresh_compare_view(&mut self) {
+        self.request_difference();
+        self.fit_window_to_image();
+        self.update_display_uniforms();
+    }
+
+    /// 差分表示中であれば、表示中の画像と比較する画像の差分の統計の計算を要求する
+    fn request_difference(&mut self) {
+        match (&self.compare, self.compare_mode, &self.source_image) {
+            (Some(compare), Some(CompareMode::Difference), Some(image)) => {
+                let (image, compare) = (image.clone(), compare.image.clone());
+                self.difference
+                    .get_or_insert_with(DifferenceAnalyzer::new)
+                    .request(image, compare, self.app_config.difference_threshold);
+            }
+            _ => {
+                if let Some(difference) = &mut self.difference {
+                    difference.clear();
+                }
+            }
+        }
+    }
+
+    /// 差分表示の見せ方を切り替える（絶対値 → 増幅 → しきい値）
+    fn cycle_difference_view(&mut self) {
+        self.difference_view = match self.difference_view {
+            DifferenceView::Absolute => DifferenceView::Amplified,
+            DifferenceView::Amplified => DifferenceView::Threshold,
+            DifferenceView::Threshold => DifferenceView::Absolute,
+        };
+        info!("差分表示: {:?}", self.difference_view);
+        self.update_display_uniforms();
+        self.show_osd_temporarily();
+    }
+
+    /// 比較する2枚の左右を入れ替える
+    fn swap_compare(&mut self) {
+        if self.compare.is_some() && self.compare_mode.is_some() {
+            self.compare_swapped = !self.compare_swapped;
+            self.update_display_uniforms();
+        }
+    }
+
+    /// 露出補正を変更する
+    /// 
+    /// # Arguments
+    /// * `exposure` - 新しい露出補正（EV）
+    fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure;
+        self.update_display_uniforms();
+        info!("露出補正: {:+.1} EV", self.exposure);
+    }
+
+    /// 色補正の項目を1段階ずつ変更する
+    ///
+    /// # Arguments
+    /// * `adjustment` - 変更する項目
+    /// * `steps` - 変更する段階（負の値で小さくする）
+    fn step_adjustment(&mut self, adjustment: Adjustment, steps: f32) {
+        self.adjustments.step(adjustment, steps);
+        self.update_display_uniforms();
+        self.show_osd_temporarily();
+        info!("色補正: {}", self.adjustments.summary());
+    }
+
+    /// チャンネルの表示方法を切り替える（通常 → R → G → B → A → ストレートアルファ → 乗算済みアルファ → アルファマスク）
+    fn cycle_channel_view(&mut self) {
+        self.channel_view = match self.channel_view {
+            ChannelView::Color => ChannelView::Red,
+            ChannelView::Red => ChannelView::Green,
+            ChannelView::Green => ChannelView::Blue,
+            ChannelView::Blue => ChannelView::Alpha,
+            ChannelView::Alpha => ChannelView::Straight,
+            ChannelView::Straight => ChannelView::Premultiplied,
+            ChannelView::Premultiplied => ChannelView::AlphaMask,
+            ChannelView::AlphaMask => ChannelView::Color,
+        };
+        info!("チャンネル表示: {:?}", self.channel_view);
+        self.update_display_uniforms();
+        self.show_osd_temporarily();
+        if let Some(info) = &self.image_info {
+            self.update_window_title(&info.name);
+        }
+    }
+
+    /// チャンネルの表示方法の名前を取得する
+    ///
+    /// # Returns
+    /// * `Option<&str>` - 表示方法の名前（通常の表示の場合は None）
+    fn channel_view_label(&self) -> Option<&'static str> {
+        match self.channel_view {
+            ChannelView::Color => None,
+            ChannelView::Red => Some("R"),
+            ChannelView::Green => Some("G"),
+            ChannelView::Blue => Some("B"),
+            ChannelView::Alpha => Some("A"),
+            ChannelView::Straight => Some("ストレートアルファ"),
+            ChannelView::Premultiplied => Some("乗算済みアルファ"),
+            ChannelView::AlphaMask => Some("アルファマスク"),
+        }
+    }
+
+    /// 拡大鏡を表示する・閉じる（縦読み表示とサムネイル一覧では表示しない）
+    ///
+    /// # Arguments
+    /// * `show` - 表示する場合は true
+    fn set_loupe(&mut self, show: bool) {
+        let show = show && !self.gallery_mode && !self.webtoon_mode;
+        if show != self.show_loupe {
+            self.show_loupe = show;
+            debug!("拡大鏡: {}", if show { "表示" } else { "非表示" });
+            self.update_display_uniforms();
+        }
+    }
+
+    /// 拡大鏡の倍率を変更する
+    ///
+    /// # Arguments
+    /// * `magnification` - 新しい倍率
+    fn set_loupe_magnification(&mut self, magnification: f32) {
+        self.loupe_magnification = magnification.clamp(LOUPE_MAGNIFICATION_RANGE.0, LOUPE_MAGNIFICATION_RANGE.1);
+        info!("拡大鏡の倍率: {:.1}倍", self.loupe_magnification);
+        self.update_display_uniforms();
+    }
+
+    /// 露出補正と色補正を設定ファイルの値に戻す
+    fn reset_adjustments(&mut self) {
+        self.exposure = self.app_config.exposure;
+        self.adjustments = ColorAdjustments::from_config(&self.app_config);
+        self.update_display_uniforms();
+    }
+
+    /// 表示中の画像を、表示と同じ露出補正・トーンマッピング（と設定により色補正）を適用してPNGで書き出す
+    ///
+    /// 書き出しは別スレッドで行い、書き出し先のディレクトリの `<拡張子を除いた画像ファイル名>.png` に保存する
+    /// （同じ名前のファイルがある場合は上書きせず、`<名前> (2).png` のように番号を付ける）
+    fn export_image(&self) {
+        let (Some(image), Some(image_file)) = (self.source_image.clone(), self.image_handler.current_image()) else {
+            return;
+        };
+        let export_dir = self.export_dir(&image_file.path);
+        let stem = image_file.path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| image_file.name.clone());
+        let (adjustments, exposure, tone_mapping) = (self.adjustments, self.exposure, self.tone_mapping);
+        let apply_adjustments = self.app_config.export_adjustments;
+        let spawned = std::thread::Builder::new()
+            .name("image-export".to_string())
+            .spawn(move || {
+                if let Err(e) = std::fs::create_dir_all(&export_dir) {
+                    error!("書き出し先のディレクトリを作成できません: {:?} ({:?})", export_dir, e);
+                    return;
+                }
+                let rendered = adjustments.render(&image, exposure, tone_mapping, apply_adjustments);
+                let (export_path, file) = match create_unique_file(&export_dir, &stem, "png") {
+                    Ok(created) => created,
+                    Err(e) => {
+                        error!("書き出すファイルを作成できません: {:?} ({:?})", export_dir, e);
+                        return;
+                    }
+                };
+                let mut writer = std::io::BufWriter::new(file);
+                let result = rendered.write_to(&mut writer, image::ImageFormat::Png)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|()| Ok(std::io::Write::flush(&mut writer)?));
+                match result {
+                    Ok(()) => info!("画像を書き出しました: {:?}", export_path),
+                    Err(e) => {
+                        error!("画像の書き出しに失敗: {:?} ({:?})", export_path, e);
+                        let _ = std::fs::remove_file(&export_path);
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            error!("画像の書き出しスレッドの起動に失敗: {:?}", e);
+        }
+    }
+
+    /// 画像やメタデータの書き出し先のディレクトリを取得する
+    ///
+    /// # Arguments
+    /// * `image_path` - 元の画像ファイルのパス
+    ///
+    /// # Returns
+    /// * `PathBuf` - `export_dir`（未指定の場合は元の画像と同じディレクトリの `export` フォルダ）
+    fn export_dir(&self, image_path: &Path) -> PathBuf {
+        match &self.app_config.export_dir {
+            Some(dir) => dir.clone(),
+            None => image_path.with_file_name(EXPORT_DIR_NAME),
+        }
     }
 
     /// ウィンドウサイズを画像に合わせて調整する
@@ -387,13 +1362,67 @@ impl ImageViewer {
     /// # Returns
     /// * `Result<()>` - 成功時は Ok(())
     fn adjust_window_size(&mut self, image_width: u32, image_height: u32) -> Result<()> {
-        let min_width = self.app_config.min_window_size.0;
-        let min_height = self.app_config.min_window_size.1;
+        // 全画面表示中はウィンドウサイズを変えず、画面に収めて表示する
+        if self.is_fullscreen() || self.fit_mode != FitMode::Window {
+            return Ok(());
+        }
+        let Some(window) = self.window.clone() else {
+            return Ok(());
+        };
+        // 最大化中にサイズを変更すると最大化が解除されるため、変更しない
+        if window.is_maximized() {
+            return Ok(());
+        }
+
+        // 左右に並べて比較している場合は2枚分の幅にする
+        let side_by_side = self.compare.is_some() && self.compare_mode == Some(CompareMode::SideBySide);
+        let image_width = if side_by_side { image_width.saturating_mul(2) } else { image_width };
+        let monitor = Self::monitor_for_window(&window);
+        let new_size = self.window_size_for_image(image_width, image_height, monitor.as_ref(), window.scale_factor());
+        // すぐに反映された場合は Resized イベントが来ないことがあるため、ここでサーフェスを合わせる
+        if let Some(size) = window.request_inner_size(new_size) {
+            self.resize(size.width, size.height);
+        }
+
+        debug!("ウィンドウサイズを調整: {}x{}", new_size.width, new_size.height);
+        Ok(())
+    }
+
+    /// 画像に合わせたウィンドウの内側のサイズを計算する
+    ///
+    /// 画像の1ピクセルを物理ピクセル1つで表示し、最小ウィンドウサイズ（論理ピクセル）と
+    /// モニターの作業領域に収まるよう拡大・縮小する
+    ///
+    /// # Arguments
+    /// * `image_width` - 画像の幅
+    /// * `image_height` - 画像の高さ
+    /// * `monitor` - ウィンドウがあるモニター
+    /// * `scale_factor` - 表示倍率
+    ///
+    /// # Returns
+    /// * `PhysicalSize<u32>` - ウィンドウの内側のサイズ（物理ピクセル）
+    fn window_size_for_image(
+        &self,
+        image_width: u32,
+        image_height: u32,
+        monitor: Option<&MonitorHandle>,
+        scale_factor: f64,
+    ) -> PhysicalSize<u32> {
+        let min_size: PhysicalSize<u32> = LogicalSize::new(
+            self.app_config.min_window_size.0,
+            self.app_config.min_window_size.1,
+        ).to_physical(scale_factor);
 
-        // デスクトップの解像度を取得
-        let desktop_size = self.get_desktop_size();
-        let max_width = (desktop_size.0 as f32 * 0.9) as u32;
-        let max_height = (desktop_size.1 as f32 * 0.9) as u32;
+        // モニターの作業領域を取得（取得できない場合は既定の大きさを使う）
+        let work_area = monitor.map_or(
+            PhysicalSize::new(DEFAULT_WORK_AREA.0, DEFAULT_WORK_AREA.1),
+            Self::work_area,
+        );
+        let max_width = (work_area.width as f32 * 0.9) as u32;
+        let max_height = (work_area.height as f32 * 0.9) as u32;
+        // 最小ウィンドウサイズが作業領域より大きい場合は作業領域を優先する
+        let min_width = min_size.width.min(max_width);
+        let min_height = min_size.height.min(max_height);
 
         let (new_width, new_height) = if image_width < min_width || image_height < min_height {
             // 最小サイズに収まる場合は拡大
@@ -403,7 +1432,7 @@ impl ImageViewer {
             
             ((image_width as f32 * scale) as u32, (image_height as f32 * scale) as u32)
         } else if image_width > max_width || image_height > max_height {
-            // デスクトップサイズを超える場合は縮小
+            // 作業領域を超える場合は縮小
             let scale_x = max_width as f32 / image_width as f32;
             let scale_y = max_height as f32 / image_height as f32;
             let scale = scale_x.min(scale_y);
@@ -413,33 +1442,51 @@ impl ImageViewer {
             // そのままのサイズで表示
             (image_width, image_height)
         };
+        PhysicalSize::new(new_width.max(1), new_height.max(1))
+    }
 
-        if let Some(window) = &self.window {
-            let _ = window.request_inner_size(LogicalSize::new(new_width, new_height));
-        }
-        self.resize(new_width, new_height);
-
-        debug!("ウィンドウサイズを調整: {}x{}", new_width, new_height);
-        Ok(())
+    /// ウィンドウの大きさの基準にするモニターを取得する
+    ///
+    /// ウィンドウがあるモニターを取得できない場合は、プライマリモニター、
+    /// 最初に見つかったモニターの順に代わりを探す
+    ///
+    /// # Arguments
+    /// * `window` - ウィンドウ
+    ///
+    /// # Returns
+    /// * `Option<MonitorHandle>` - モニター（見つからない場合は None）
+    fn monitor_for_window(window: &Window) -> Option<MonitorHandle> {
+        window.current_monitor()
+            .or_else(|| window.primary_monitor())
+            .or_else(|| window.available_monitors().next())
     }
 
-    /// デスクトップサイズを取得する
+    /// モニターの作業領域（タスクバーなどを除いた領域）の大きさを取得する
+    /// 
+    /// # Arguments
+    /// * `monitor` - モニター
     /// 
     /// # Returns
-    /// * `(u32, u32)` - デスクトップの幅と高さ
+    /// * `PhysicalSize<u32>` - 作業領域の幅と高さ（物理ピクセル）
     #[cfg(windows)]
-    fn get_desktop_size(&self) -> (u32, u32) {
-        use winapi::um::winuser::{GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
+    fn work_area(monitor: &MonitorHandle) -> PhysicalSize<u32> {
+        use winapi::um::winuser::{GetMonitorInfoW, MONITORINFO};
+        use winit::platform::windows::MonitorHandleExtWindows;
         unsafe {
-            let width = GetSystemMetrics(SM_CXSCREEN) as u32;
-            let height = GetSystemMetrics(SM_CYSCREEN) as u32;
-            (width, height)
+            let mut info: MONITORINFO = std::mem::zeroed();
+            info.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+            if GetMonitorInfoW(monitor.hmonitor() as winapi::shared::windef::HMONITOR, &mut info) != 0 {
+                let rect = info.rcWork;
+                return PhysicalSize::new((rect.right - rect.left) as u32, (rect.bottom - rect.top) as u32);
+            }
         }
+        monitor.size()
     }
 
+    /// 作業領域を取得する手段がないため、モニター全体の大きさを使う
     #[cfg(not(windows))]
-    fn get_desktop_size(&self) -> (u32, u32) {
-        (1920, 1080) // デフォルト値
+    fn work_area(monitor: &MonitorHandle) -> PhysicalSize<u32> {
+        monitor.size()
     }
 
     /// ウィンドウのリサイズ処理
@@ -448,15 +1495,79 @@ impl ImageViewer {
     /// * `new_width` - 新しい幅
     /// * `new_height` - 新しい高さ
     pub fn resize(&mut self, new_width: u32, new_height: u32) {
-        if new_width > 0 && new_height > 0 {
-            if let (Some(surface), Some(device), Some(config)) = 
-                (&self.surface, &self.device, &mut self.config) {
-                config.width = new_width;
-                config.height = new_height;
-                surface.configure(device, config);
-                debug!("ウィンドウをリサイズ: {}x{}", new_width, new_height);
+        if new_width > 0 && new_height > 0
+            && let (Some(surface), Some(device), Some(config)) =
+                (&self.surface, &self.device, &mut self.config)
+        {
+            config.width = new_width;
+            config.height = new_height;
+            surface.configure(device, config);
+            debug!("ウィンドウをリサイズ: {}x{}", new_width, new_height);
+        }
+        self.update_display_uniforms();
+    }
+
+    /// 全画面表示中かどうかを取得する
+    ///
+    /// # Returns
+    /// * `bool` - 全画面表示中の場合は true
+    fn is_fullscreen(&self) -> bool {
+        self.window.as_ref().is_some_and(|window| window.fullscreen().is_some())
+    }
+
+    /// 全画面表示を切り替える
+    ///
+    /// ウィンドウがあるモニターを全画面にし、解除したときは画像に合わせてウィンドウサイズを戻す
+    ///
+    /// # Arguments
+    /// * `fullscreen` - 全画面表示にする場合は true
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        let Some(window) = &self.window else {
+            return;
+        };
+        if fullscreen {
+            let mode = Self::fullscreen_mode(window.current_monitor(), self.app_config.fullscreen_exclusive);
+            info!("全画面表示: {:?}", mode);
+            window.set_fullscreen(Some(mode));
+        } else {
+            window.set_fullscreen(None);
+            info!("全画面表示を解除しました");
+            self.fit_window_to_image();
+        }
+    }
+
+    /// 表示中の画像に合わせてウィンドウサイズを調整する
+    fn fit_window_to_image(&mut self) {
+        if let Some((width, height)) = self.image_size()
+            && let Err(e) = self.adjust_window_size(width, height)
+        {
+            error!("ウィンドウサイズの調整に失敗: {:?}", e);
+        }
+    }
+
+    /// 全画面表示の方式を決める
+    ///
+    /// # Arguments
+    /// * `monitor` - 全画面にするモニター（None の場合は現在のモニター）
+    /// * `exclusive` - 排他モードを使うかどうか
+    ///
+    /// # Returns
+    /// * `Fullscreen` - 全画面表示の方式（排他モードに使える解像度がない場合はボーダーレス）
+    fn fullscreen_mode(monitor: Option<MonitorHandle>, exclusive: bool) -> Fullscreen {
+        if exclusive
+            && let Some(monitor) = &monitor
+        {
+            // モニターの現在の解像度のうち、色深度とリフレッシュレートが最も高いモードを選ぶ
+            let size = monitor.size();
+            let video_mode = monitor.video_modes()
+                .filter(|mode| mode.size() == size)
+                .max_by_key(|mode| (mode.bit_depth(), mode.refresh_rate_millihertz()));
+            match video_mode {
+                Some(video_mode) => return Fullscreen::Exclusive(video_mode),
+                None => info!("排他モードに使える解像度が見つからないため、ボーダーレス全画面にします"),
             }
         }
+        Fullscreen::Borderless(monitor)
     }
 
     /// 画面を描画する
@@ -464,12 +1575,18 @@ impl ImageViewer {
     /// # Returns
     /// * `Result<()>` - 成功時は Ok(())
     pub fn render(&mut self) -> Result<()> {
+        self.update_transition();
+        self.sync_webtoon_index();
+        self.build_overlay();
+
         let surface = self.surface.as_ref().unwrap();
         let device = self.device.as_ref().unwrap();
         let queue = self.queue.as_ref().unwrap();
         let render_pipeline = self.render_pipeline.as_ref().unwrap();
-        let vertex_buffer = self.vertex_buffer.as_ref().unwrap();
-        let index_buffer = self.index_buffer.as_ref().unwrap();
+        let loupe_pipeline = self.loupe_pipeline.as_ref().unwrap();
+
+        let [r, g, b, _] = Self::background_color(if self.gallery_mode { Background::Black } else { self.background });
+        let clear_color = wgpu::Color { r: r as f64, g: g as f64, b: b as f64, a: 1.0 };
 
         let output = surface.get_current_texture()?;
         let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
@@ -478,37 +1595,25 @@ impl ImageViewer {
             label: Some("Render Encoder"),
         });
 
+        let show_image = !self.gallery_mode && !self.webtoon_mode;
+        let show_loupe = show_image && self.show_loupe;
         {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                    depth_slice: None,
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
+            let mut render_pass = Self::begin_render_pass(&mut encoder, &view, "Render Pass", wgpu::LoadOp::Clear(clear_color));
+            if show_image {
+                self.draw_image(&mut render_pass, render_pipeline);
+            }
+            if !show_loupe && let Some(overlay) = &self.overlay {
+                overlay.draw(&mut render_pass);
+            }
+        }
 
-            render_pass.set_pipeline(render_pipeline);
-            
-            if let Some(texture_info) = &self.current_texture {
-                render_pass.set_bind_group(0, &texture_info.bind_group, &[]);
+        // 拡大鏡は画像の上に別のパスで重ね、オーバーレイはさらにその上に描画する
+        if show_loupe {
+            let mut render_pass = Self::begin_render_pass(&mut encoder, &view, "Loupe Pass", wgpu::LoadOp::Load);
+            self.draw_image(&mut render_pass, loupe_pipeline);
+            if let Some(overlay) = &self.overlay {
+                overlay.draw(&mut render_pass);
             }
-            
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..INDICES.len() as u32, 0, 0..1);
         }
 
         queue.submit(std::iter::once(encoder.finish()));
@@ -517,24 +1622,943 @@ impl ImageViewer {
         Ok(())
     }
 
-    /// 現在の画像を読み込む
-    fn load_current_image(&mut self) {
-        if let Some(image_file) = self.image_handler.current_image() {
-            let file_path = image_file.path.clone();
-            let file_name = image_file.name.clone();
-            info!("画像を読み込み中: {:?}", file_path);
-            match self.image_handler.load_image(&file_path) {
-                Ok(image) => {
-                    if let Err(e) = self.load_texture(image) {
+    /// 描画先のテクスチャに書き込むレンダーパスを開始する
+    ///
+    /// # Arguments
+    /// * `encoder` - コマンドエンコーダー
+    /// * `view` - 描画先のテクスチャビュー
+    /// * `label` - レンダーパスの名前
+    /// * `load` - 描画前の内容の扱い（消去する色、またはそのまま残す）
+    ///
+    /// # Returns
+    /// * `wgpu::RenderPass` - レンダーパス
+    fn begin_render_pass<'a>(
+        encoder: &'a mut wgpu::CommandEncoder,
+        view: &'a wgpu::TextureView,
+        label: &str,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        })
+    }
+
+    /// 表示中の画像を画面全体を覆う四角形で描画する（画像がない場合は何もしない）
+    ///
+    /// # Arguments
+    /// * `render_pass` - レンダーパス
+    /// * `pipeline` - 使用するレンダーパイプライン（通常の表示または拡大鏡）
+    fn draw_image(&self, render_pass: &mut wgpu::RenderPass<'_>, pipeline: &wgpu::RenderPipeline) {
+        let (Some((texture_info, _)), compare) = self.display_textures() else {
+            return;
+        };
+        let (Some(display_bind_group), Some(vertex_buffer), Some(index_buffer)) =
+            (&self.display_bind_group, &self.vertex_buffer, &self.index_buffer)
+        else {
+            return;
+        };
+        render_pass.set_pipeline(pipeline);
+
+        render_pass.set_bind_group(0, &texture_info.bind_group, &[]);
+        render_pass.set_bind_group(1, display_bind_group, &[]);
+        // 切り替え中は前の画像、そうでなければ現在の画像を渡す
+        let previous = self.transition.as_ref().map_or(texture_info, |transition| &transition.previous);
+        render_pass.set_bind_group(2, &previous.bind_group, &[]);
+        // 比較表示中は比較する画像、そうでなければ現在の画像を渡す
+        let compare = compare.map_or(texture_info, |(texture, _)| texture);
+        render_pass.set_bind_group(3, &compare.bind_group, &[]);
+
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..INDICES.len() as u32, 0, 0..1);
+    }
+
+    /// オーバーレイに描画する内容を組み立て、GPUに転送する
+    fn build_overlay(&mut self) {
+        if self.show_filmstrip && self.filmstrip.is_none() {
+            self.create_filmstrip();
+        }
+        if let Some(difference) = &mut self.difference {
+            difference.update();
+        }
+        let osd_lines = self.osd_lines();
+        let inspection = if self.show_inspector { self.inspect_pixel() } else { None };
+        let minimap_view = if self.show_minimap { self.minimap_viewport().zip(self.image_size()) } else { None };
+        let (Some(overlay), Some(device), Some(queue), Some(config), Some(window)) =
+            (&mut self.overlay, &self.device, &self.queue, &self.config, &self.window)
+        else {
+            return;
+        };
+        let scale_factor = window.scale_factor() as f32;
+        overlay.begin(queue, (config.width, config.height), scale_factor);
+
+        let font_size = self.app_config.overlay_font_size;
+        let (screen_width, screen_height) = (config.width as f32, config.height as f32);
+        let margin = 10.0 * scale_factor;
+
+        if self.gallery_mode {
+            if let Some(gallery) = &mut self.gallery {
+                gallery.update(device, queue, overlay, (screen_width, screen_height), scale_factor);
+                gallery.draw(overlay, queue, screen_width);
+            }
+            overlay.prepare(device, queue);
+            return;
+        }
+        if self.webtoon_mode {
+            if let Some(webtoon) = &mut self.webtoon {
+                webtoon.update(device, queue, overlay, (screen_width, screen_height));
+                webtoon.draw(overlay, queue, font_size);
+            }
+            overlay.prepare(device, queue);
+            return;
+        }
+
+        // OSDとメタデータパネルはフィルムストリップと重ならない領域に配置する
+        let mut area = Rect::new(0.0, 0.0, screen_width, screen_height);
+        if self.show_filmstrip
+            && let Some(filmstrip) = &mut self.filmstrip
+        {
+            if filmstrip.is_visible(self.cursor_position) {
+                let current = self.image_handler.current_index;
+                filmstrip.update(device, queue, overlay, (screen_width, screen_height), scale_factor, current);
+                filmstrip.draw(overlay, queue, current, font_size);
+            } else {
+                filmstrip.hide();
+            }
+            if let Some(strip) = filmstrip.strip() {
+                area = match self.app_config.filmstrip_position {
+                    FilmstripPosition::Bottom => Rect::new(0.0, 0.0, screen_width, strip.y),
+                    FilmstripPosition::Top => Rect::new(0.0, strip.h, screen_width, screen_height - strip.h),
+                    FilmstripPosition::Left => Rect::new(strip.w, 0.0, screen_width - strip.w, screen_height),
+                    FilmstripPosition::Right => Rect::new(0.0, 0.0, strip.x, screen_height),
+                };
+            }
+        }
+        let max_size = (area.w - margin * 2.0, area.h - margin * 2.0);
+        let mut metadata_top = area.y + margin;
+
+        if let Some(lines) = osd_lines {
+            let position = self.app_config.osd_position;
+            let size = overlay.panel_rect(0.0, 0.0, font_size, &lines, max_size);
+            let x = match position {
+                OsdPosition::TopLeft | OsdPosition::BottomLeft => area.x + margin,
+                OsdPosition::TopRight | OsdPosition::BottomRight => area.x + area.w - margin - size.w,
+            };
+            let y = match position {
+                OsdPosition::TopLeft | OsdPosition::TopRight => area.y + margin,
+                OsdPosition::BottomLeft | OsdPosition::BottomRight => area.y + area.h - margin - size.h,
+            };
+            let panel = overlay.text_panel(queue, x, y, font_size, &lines, max_size);
+            // メタデータパネルと重ならないよう、OSDの下から表示する
+            if position == OsdPosition::TopLeft {
+                metadata_top = panel.y + panel.h + margin;
+            }
+        }
+
+        // ミニマップは左上（OSDが左上にある場合はその下）に表示し、メタデータパネルはさらにその下から表示する
+        match (minimap_view, &self.source_image) {
+            (Some((viewport, image_size)), Some(image)) => {
+                self.minimap.update(device, queue, overlay, image);
+                let (width, height) = Minimap::size(image_size, scale_factor);
+                let rect = Rect::new(area.x + margin, metadata_top, width, height);
+                self.minimap.draw(overlay, rect, viewport, scale_factor);
+                metadata_top = rect.y + rect.h + margin;
+            }
+            _ => self.minimap.hide(),
+        }
+
+        if self.scope != Scope::None
+            && let Some(scopes) = &mut self.scopes
+        {
+            // OSDと重ならないよう、OSDが右下にある場合は右上に表示する
+            scopes.update(device, queue, overlay);
+            let (width, height) = Scopes::panel_size(scale_factor);
+            let x = area.x + area.w - margin - width;
+            let y = match self.app_config.osd_position {
+                OsdPosition::BottomRight => area.y + margin,
+                _ => area.y + area.h - margin - height,
+            };
+            let panel = Rect::new(x, y, width, height);
+            scopes.draw(overlay, queue, panel, self.scope, self.scope_log_scale, font_size);
+        }
+
+        if self.show_adjustments {
+            let lines = vec![
+                "色補正".to_string(),
+                format!("露出補正: {:+.1} EV  ([ / ])", self.exposure),
+                format!("明るさ: {:+.2}  (1 / 2)", self.adjustments.brightness),
+                format!("コントラスト: {:.2}  (3 / 4)", self.adjustments.contrast),
+                format!("ガンマ: {:.2}  (5 / 6)", self.adjustments.gamma),
+                format!("彩度: {:.2}  (7 / 8)", self.adjustments.saturation),
+                "元に戻す: 9  書き出し: Ctrl+S".to_string(),
+            ];
+            // OSDと重ならないよう、OSDが右上にある場合は右下に表示する
+            let size = overlay.panel_rect(0.0, 0.0, font_size, &lines, max_size);
+            let x = area.x + area.w - margin - size.w;
+            let y = match self.app_config.osd_position {
+                OsdPosition::TopRight => area.y + area.h - margin - size.h,
+                _ => area.y + margin,
+            };
+            overlay.text_panel(queue, x, y, font_size, &lines, max_size);
+        }
+
+        if self.show_inspector
+            && let Some((name, sample)) = inspection
+        {
+            // カーソルの右下に表示し、画面からはみ出す場合は反対側に寄せる
+            let mut lines = vec![name];
+            lines.extend(sample.lines());
+            let (cursor_x, cursor_y) = self.cursor_position;
+            let offset = 16.0 * scale_factor;
+            let size = overlay.panel_rect(0.0, 0.0, font_size, &lines, max_size);
+            let x = if cursor_x + offset + size.w > screen_width { cursor_x - offset - size.w } else { cursor_x + offset };
+            let y = if cursor_y + offset + size.h > screen_height { cursor_y - offset - size.h } else { cursor_y + offset };
+            overlay.text_panel(queue, x.max(0.0), y.max(0.0), font_size, &lines, max_size);
+        }
+
+        if self.show_metadata {
+            let mut lines = vec![match self.image_handler.current_image() {
+                Some(image_file) => format!("メタデータ: {}", image_file.name),
+                None => "メタデータ".to_string(),
+            }];
+            match &self.metadata {
+                Some(metadata) if !metadata.is_empty() => lines.extend(metadata.lines()),
+                _ => lines.push("(メタデータなし)".to_string()),
+            }
+            let max_size = (max_size.0, area.y + area.h - margin - metadata_top);
+            overlay.text_panel(queue, area.x + margin, metadata_top, font_size, &lines, max_size);
+        }
+
+        overlay.prepare(device, queue);
+    }
+
+    /// ミニマップに示す表示範囲を取得する
+    ///
+    /// # Returns
+    /// * `Option<Rect>` - 画像全体に対する表示範囲（0.0〜1.0 の割合、画像全体が表示領域に収まる場合は None）
+    fn minimap_viewport(&self) -> Option<Rect> {
+        let (width, height) = self.image_size()?;
+        let scale = self.display_scale((width, height))?;
+        let (view_width, view_height) = self.viewport_size()?;
+        let (shown_width, shown_height) = (width as f32 * scale, height as f32 * scale);
+        if shown_width <= view_width + 0.5 && shown_height <= view_height + 0.5 {
+            return None;
+        }
+        // 表示領域の左上を原点とした画像の左上の位置
+        let left = (view_width - shown_width) / 2.0 + self.pan.0;
+        let top = (view_height - shown_height) / 2.0 + self.pan.1;
+        let x0 = (-left / shown_width).clamp(0.0, 1.0);
+        let y0 = (-top / shown_height).clamp(0.0, 1.0);
+        let x1 = ((view_width - left) / shown_width).clamp(0.0, 1.0);
+        let y1 = ((view_height - top) / shown_height).clamp(0.0, 1.0);
+        Some(Rect::new(x0, y0, x1 - x0, y1 - y0))
+    }
+
+    /// ミニマップ上の位置が表示領域の中心になるように表示位置を移動する
+    ///
+    /// # Arguments
+    /// * `x` - X座標
+    /// * `y` - Y座標
+    fn pan_to_minimap(&mut self, x: f32, y: f32) {
+        let (Some((u, v)), Some(size)) = (self.minimap.image_position(x, y), self.image_size()) else {
+            return;
+        };
+        let Some(scale) = self.display_scale(size) else {
+            return;
+        };
+        let (shown_width, shown_height) = (size.0 as f32 * scale, size.1 as f32 * scale);
+        self.pan = (shown_width * (0.5 - u), shown_height * (0.5 - v));
+        self.update_display_uniforms();
+    }
+
+    /// OSDに表示する行を作成する
+    ///
+    /// # Returns
+    /// * `Option<Vec<String>>` - 表示する行（非表示の場合は None）
+    fn osd_lines(&self) -> Option<Vec<String>> {
+        if !self.show_osd {
+            return None;
+        }
+        let auto_hide = self.app_config.osd_auto_hide_secs;
+        if auto_hide > 0.0 && self.osd_shown_at.elapsed().as_secs_f32() > auto_hide {
+            return None;
+        }
+
+        let image_file = self.image_handler.current_image()?;
+        let index = self.image_handler.current_index;
+        let total = self.image_handler.len();
+        let mut lines = match &self.image_info {
+            Some(info) => osd::expand_template(&self.app_config.osd_template, info, index, total, self.zoom()),
+            None => vec![format!("{}  [{}/{}]  (読み込み失敗)", image_file.name, index + 1, total)],
+        };
+        if let (Some(compare), Some(mode)) = (&self.compare, self.compare_mode) {
+            let (left, right) = if self.compare_swapped {
+                (compare.name.as_str(), image_file.name.as_str())
+            } else {
+                (image_file.name.as_str(), compare.name.as_str())
+            };
+            let mode = match (mode, self.difference_view) {
+                (CompareMode::SideBySide, _) => "左右",
+                (CompareMode::Split, _) => "分割",
+                (CompareMode::Difference, DifferenceView::Absolute) => "差分",
+                (CompareMode::Difference, DifferenceView::Amplified) => "差分×増幅",
+                (CompareMode::Difference, DifferenceView::Threshold) => "差分・しきい値",
+            };
+            lines.push(format!("比較（{}）: {} | {}", mode, left, right));
+            if let Some(comparison) = &self.folder_comparison {
+                lines.push(comparison.summary());
+            }
+            if let Some(difference) = &self.difference {
+                match difference.stats() {
+                    Some(Ok(stats)) => lines.push(stats.summary()),
+                    Some(Err(e)) => lines.push(e.to_string()),
+                    None if difference.is_pending() => lines.push("差分を計算中…".to_string()),
+                    None => {}
+                }
+            }
+        }
+        if !self.adjustments.is_identity() {
+            lines.push(format!("色補正: {}", self.adjustments.summary()));
+        }
+        if let Some(label) = self.channel_view_label() {
+            lines.push(format!("チャンネル: {}", label));
+        }
+        if self.shown_pages.len() > 1 {
+            lines.push(format!(
+                "見開き{}: {}-{}ページ",
+                if self.right_to_left { "（右綴じ）" } else { "" },
+                self.shown_pages.start + 1,
+                self.shown_pages.end,
+            ));
+        }
+        if let Some(slideshow) = &self.slideshow {
+            lines.push(if slideshow.is_paused() {
+                "スライドショー: 一時停止中".to_string()
+            } else {
+                format!("スライドショー: {:.1}秒ごと", slideshow.interval().as_secs_f32())
+            });
+        }
+        Some(lines)
+    }
+
+    /// OSDを表示し、自動で隠すまでの時間をリセットする
+    fn show_osd_temporarily(&mut self) {
+        self.osd_shown_at = Instant::now();
+    }
+
+    /// 画面上の位置にある画像のピクセルを求める
+    ///
+    /// 拡大率・表示位置と比較表示の配置を逆にたどり、元画像の座標に変換する
+    ///
+    /// # Arguments
+    /// * `position` - 画面上の位置（ピクセル）
+    ///
+    /// # Returns
+    /// * `Option<(bool, u32, u32)>` - 比較する画像かどうかと、元画像上の座標（画像の外側の場合は None）
+    fn image_pixel_at(&self, (x, y): (f32, f32)) -> Option<(bool, u32, u32)> {
+        let (view_width, view_height) = self.viewport_size()?;
+        let (primary, compare) = self.display_textures();
+        // 画面上の位置に表示している画像と、その表示領域の左端を決める
+        let (second, origin_x) = match (compare, self.compare_mode) {
+            (Some(_), Some(CompareMode::SideBySide)) if x >= view_width => (true, view_width),
+            (Some(_), Some(CompareMode::Split)) => (x >= self.split_position * view_width, 0.0),
+            _ => (false, 0.0),
+        };
+        let (_, (width, height)) = if second { compare? } else { primary? };
+        let scale = self.display_scale((width, height))?;
+
+        // 画像の中心は表示領域の中心から移動量だけずれた位置にある
+        let image_x = (x - origin_x - view_width / 2.0 - self.pan.0) / scale + width as f32 / 2.0;
+        let image_y = (y - view_height / 2.0 - self.pan.1) / scale + height as f32 / 2.0;
+        if image_x < 0.0 || image_y < 0.0 || image_x >= width as f32 || image_y >= height as f32 {
+            return None;
+        }
+        Some((second != self.compare_swapped, image_x as u32, image_y as u32))
+    }
+
+    /// カーソルの下にあるピクセルの値を読み取る
+    ///
+    /// # Returns
+    /// * `Option<(String, PixelSample)>` - 画像のファイル名とピクセルの値（画像の外側の場合は None）
+    fn inspect_pixel(&self) -> Option<(String, PixelSample)> {
+        if self.gallery_mode || self.webtoon_mode {
+            return None;
+        }
+        let (is_compare, x, y) = self.image_pixel_at(self.cursor_position)?;
+        if is_compare {
+            let compare = self.compare.as_ref()?;
+            return Some((compare.name.clone(), PixelSample::read(&compare.image, x, y)?));
+        }
+        let name = self.image_info.as_ref()?.name.clone();
+        // アニメーション再生中は表示中のフレームから読み取る
+        let sample = match &self.animation {
+            Some(animation) => PixelSample::read_frame(animation.current_frame(), x, y)?,
+            None => PixelSample::read(self.source_image.as_ref()?, x, y)?,
+        };
+        Some((name, sample))
+    }
+
+    /// 分析パネルを表示している場合は、表示中の画像の集計を要求する
+    fn request_scopes(&mut self) {
+        if self.scope == Scope::None {
+            return;
+        }
+        let Some(image) = self.source_image.clone() else {
+            return;
+        };
+        self.scopes.get_or_insert_with(Scopes::new).request(image);
+    }
+
+    /// 分析パネルを切り替える（なし → ヒストグラム → 波形モニター）
+    fn cycle_scope(&mut self) {
+        self.scope = match self.scope {
+            Scope::None => Scope::Histogram,
+            Scope::Histogram => Scope::Waveform,
+            Scope::Waveform => Scope::None,
+        };
+        info!("分析パネル: {:?}", self.scope);
+        // ヒストグラムと波形モニターは同時に集計するため、表示し始めたときだけ集計する
+        if self.scope == Scope::Histogram {
+            self.request_scopes();
+        }
+    }
+
+    /// カーソルの下にあるピクセルの値を標準出力に書き出す
+    ///
+    /// ログ（標準エラー出力）には書き出さず、標準出力だけを読めば値が1回ずつ得られるようにする
+    fn copy_pixel_value(&self) {
+        match self.inspect_pixel() {
+            Some((name, sample)) => println!("{}", sample.to_text(&name)),
+            None => info!("カーソルの下に画像がありません"),
+        }
+    }
+
+    /// 現在の表示倍率を取得する
+    ///
+    /// # Returns
+    /// * `f32` - 元画像に対する表示倍率（1.0 = 等倍）
+    fn zoom(&self) -> f32 {
+        self.image_size().and_then(|size| self.display_scale(size)).unwrap_or(1.0)
+    }
+
+    /// サムネイル生成に使う画像ハンドラーとディスクキャッシュを作成する
+    ///
+    /// # Returns
+    /// * `(Arc<ImageHandler>, Option<Arc<ThumbnailCache>>)` - 画像ハンドラーとキャッシュ
+    fn thumbnail_sources(&self) -> (Arc<ImageHandler>, Option<Arc<ThumbnailCache>>) {
+        // サムネイル生成スレッドでは画像リストを持たない画像ハンドラーで読み込む
+        let decoder = Arc::new(ImageHandler::new(self.app_config.clone()));
+        let cache = ThumbnailCache::new(&self.app_config).map(Arc::new);
+        (decoder, cache)
+    }
+
+    /// フィルムストリップを作成する
+    fn create_filmstrip(&mut self) {
+        let (decoder, cache) = self.thumbnail_sources();
+        self.filmstrip = Some(Filmstrip::new(
+            self.image_handler.images(),
+            decoder,
+            cache,
+            self.app_config.filmstrip_thumbnail_size,
+            self.app_config.filmstrip_position,
+            self.app_config.filmstrip_auto_hide_secs,
+        ));
+    }
+
+    /// フィルムストリップを表示し、自動で隠すまでの時間をリセットする
+    fn show_filmstrip_temporarily(&mut self) {
+        if let Some(filmstrip) = &mut self.filmstrip {
+            filmstrip.touch();
+        }
+    }
+
+    /// サムネイル一覧を表示する
+    fn enter_gallery(&mut self) {
+        let index = self.image_handler.current_index;
+        match &mut self.gallery {
+            Some(gallery) => gallery.select(index),
+            None => {
+                let (decoder, cache) = self.thumbnail_sources();
+                self.gallery = Some(Gallery::new(
+                    self.image_handler.images(),
+                    decoder,
+                    cache,
+                    index,
+                    self.app_config.thumbnail_size,
+                    self.app_config.overlay_font_size,
+                ));
+            }
+        }
+        self.gallery_mode = true;
+        info!("サムネイル一覧を表示します");
+    }
+
+    /// サムネイル一覧で選択中の画像を開く
+    fn open_gallery_selection(&mut self) {
+        if let Some(gallery) = &self.gallery {
+            self.image_handler.current_index = gallery.selected();
+        }
+        self.gallery_mode = false;
+        self.load_current_image();
+    }
+
+    /// サムネイル一覧でのキー入力を処理する
+    ///
+    /// # Arguments
+    /// * `key` - 押されたキー
+    fn handle_gallery_key(&mut self, key: PhysicalKey) {
+        let Some(gallery) = &mut self.gallery else {
+            return;
+        };
+        match key {
+            PhysicalKey::Code(KeyCode::ArrowRight) | PhysicalKey::Code(KeyCode::KeyX) => gallery.move_selection(1),
+            PhysicalKey::Code(KeyCode::ArrowLeft) | PhysicalKey::Code(KeyCode::KeyZ) => gallery.move_selection(-1),
+            PhysicalKey::Code(KeyCode::ArrowDown) => gallery.move_rows(1),
+            PhysicalKey::Code(KeyCode::ArrowUp) => gallery.move_rows(-1),
+            PhysicalKey::Code(KeyCode::PageDown) => gallery.move_pages(1),
+            PhysicalKey::Code(KeyCode::PageUp) => gallery.move_pages(-1),
+            PhysicalKey::Code(KeyCode::Home) => gallery.select(0),
+            PhysicalKey::Code(KeyCode::End) => gallery.select(usize::MAX),
+            PhysicalKey::Code(KeyCode::Enter) => self.open_gallery_selection(),
+            PhysicalKey::Code(KeyCode::Escape) | PhysicalKey::Code(KeyCode::KeyG) => {
+                self.gallery_mode = false;
+                info!("サムネイル一覧を閉じます");
+            }
+            _ => {}
+        }
+    }
+
+    /// 縦読み表示を開く
+    fn enter_webtoon(&mut self) {
+        let index = self.image_handler.current_index;
+        match &mut self.webtoon {
+            Some(webtoon) => webtoon.jump_to(index),
+            None => {
+                let decoder = Arc::new(ImageHandler::new(self.app_config.clone()));
+                self.webtoon = Some(Webtoon::new(self.image_handler.images(), decoder, index));
+            }
+        }
+        self.webtoon_mode = true;
+        info!("縦読み表示を開きます");
+    }
+
+    /// 縦読み表示を閉じ、画面の中央に表示していた画像を開く
+    fn leave_webtoon(&mut self) {
+        if let Some(webtoon) = &mut self.webtoon {
+            webtoon.release();
+        }
+        self.webtoon_mode = false;
+        info!("縦読み表示を閉じます");
+        self.load_current_image();
+    }
+
+    /// 縦読み表示で画面の中央にある画像を現在の画像とし、ウィンドウタイトルに反映する
+    fn sync_webtoon_index(&mut self) {
+        if !self.webtoon_mode {
+            return;
+        }
+        let Some(index) = self.webtoon.as_ref().and_then(Webtoon::current) else {
+            return;
+        };
+        if index != self.image_handler.current_index {
+            self.image_handler.current_index = index;
+            if let Some(image_file) = self.image_handler.current_image() {
+                let title = format!("{} [{}/{}]", image_file.name, index + 1, self.image_handler.len());
+                self.update_window_title(&title);
+            }
+        }
+    }
+
+    /// 縦読み表示でのキー入力を処理する
+    ///
+    /// # Arguments
+    /// * `key` - 押されたキー
+    fn handle_webtoon_key(&mut self, key: PhysicalKey) {
+        let Some(webtoon) = &mut self.webtoon else {
+            return;
+        };
+        match key {
+            PhysicalKey::Code(KeyCode::ArrowDown) => webtoon.scroll_by(webtoon.wheel_step()),
+            PhysicalKey::Code(KeyCode::ArrowUp) => webtoon.scroll_by(-webtoon.wheel_step()),
+            PhysicalKey::Code(KeyCode::PageDown) | PhysicalKey::Code(KeyCode::Space) => webtoon.scroll_pages(1.0),
+            PhysicalKey::Code(KeyCode::PageUp) => webtoon.scroll_pages(-1.0),
+            PhysicalKey::Code(KeyCode::Home) => webtoon.scroll_to_edge(false),
+            PhysicalKey::Code(KeyCode::End) => webtoon.scroll_to_edge(true),
+            PhysicalKey::Code(KeyCode::ArrowRight) | PhysicalKey::Code(KeyCode::KeyX) => webtoon.move_images(1),
+            PhysicalKey::Code(KeyCode::ArrowLeft) | PhysicalKey::Code(KeyCode::KeyZ) => webtoon.move_images(-1),
+            PhysicalKey::Code(KeyCode::F11) => self.set_fullscreen(!self.is_fullscreen()),
+            PhysicalKey::Code(KeyCode::Escape) | PhysicalKey::Code(KeyCode::KeyW) => self.leave_webtoon(),
+            _ => {}
+        }
+    }
+
+    /// 現在の画像のメタデータを読み込む
+    fn refresh_metadata(&mut self) {
+        self.metadata = self.image_handler.current_image().and_then(|image_file| {
+            match self.image_handler.read_metadata(&image_file.path) {
+                Ok(metadata) => Some(metadata),
+                Err(e) => {
+                    error!("メタデータの読み込みに失敗: {:?}", e);
+                    None
+                }
+            }
+        });
+    }
+
+    /// メタデータパネルの表示を切り替える
+    fn toggle_metadata(&mut self) {
+        self.show_metadata = !self.show_metadata;
+        if self.show_metadata && self.metadata.is_none() {
+            self.refresh_metadata();
+        }
+        info!("メタデータ表示: {}", if self.show_metadata { "オン" } else { "オフ" });
+    }
+
+    /// 現在の画像のメタデータを標準出力と書き出し先のフォルダのテキストファイルに書き出す
+    fn export_metadata(&mut self) {
+        if self.metadata.is_none() {
+            self.refresh_metadata();
+        }
+        let (Some(image_file), Some(metadata)) = (self.image_handler.current_image(), &self.metadata) else {
+            return;
+        };
+        let text = metadata.to_text(&image_file.path);
+        println!("{}", text);
+
+        let export_dir = self.export_dir(&image_file.path);
+        let export_path = export_dir.join(format!("{}.metadata.txt", image_file.name));
+        match std::fs::create_dir_all(&export_dir).and_then(|()| std::fs::write(&export_path, &text)) {
+            Ok(()) => info!("メタデータを書き出しました: {:?}", export_path),
+            Err(e) => error!("メタデータの書き出しに失敗: {:?} ({:?})", export_path, e),
+        }
+    }
+
+    /// 設定からスライドショーを作成する
+    ///
+    /// # Arguments
+    /// * `config` - アプリケーション設定
+    ///
+    /// # Returns
+    /// * `Slideshow` - スライドショー
+    fn create_slideshow(config: &Config) -> Slideshow {
+        info!(
+            "スライドショーを開始します: {}秒ごと ({:?}, 繰り返し{})",
+            config.slideshow_interval_secs,
+            config.slideshow_order,
+            if config.slideshow_loop { "あり" } else { "なし" },
+        );
+        Slideshow::new(config.slideshow_interval_secs, config.slideshow_order, config.slideshow_loop)
+    }
+
+    /// スライドショーの開始と終了を切り替える
+    fn toggle_slideshow(&mut self) {
+        if self.slideshow.take().is_some() {
+            info!("スライドショーを終了します");
+        } else {
+            self.slideshow = Some(Self::create_slideshow(&self.app_config));
+        }
+    }
+
+    /// 表示時間が過ぎていればスライドショーの次の画像へ進める
+    ///
+    /// アニメーション画像は最後まで再生してから進める
+    fn advance_slideshow(&mut self) {
+        let animation_finished = self.animation.as_ref().is_none_or(|animation| animation.has_played_once());
+        let Some(slideshow) = &mut self.slideshow else {
+            return;
+        };
+        if self.gallery_mode || self.webtoon_mode || !slideshow.is_due(animation_finished) {
+            return;
+        }
+        // 見開き表示中は、表示中の見開きの次のページから進める
+        let current = self.shown_pages.end.saturating_sub(1).max(self.image_handler.current_index);
+        match slideshow.next_index(current, self.image_handler.len()) {
+            Some(index) => {
+                self.image_handler.current_index = index;
+                self.load_current_image();
+            }
+            None => {
+                info!("最後の画像まで表示したため、スライドショーを終了します");
+                self.slideshow = None;
+            }
+        }
+    }
+
+    /// アニメーション画像の表示フレームを経過時間に応じて進める
+    fn update_animation(&mut self) {
+        let (Some(animation), Some(texture), Some(queue)) = (&mut self.animation, &self.current_texture, &self.queue) else {
+            return;
+        };
+        if !animation.update() {
+            return;
+        }
+        let frame = animation.current_frame();
+        let (width, height) = frame.dimensions();
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            frame.as_raw(),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// 読み込んだアニメーションの再生を開始する
+    ///
+    /// # Arguments
+    /// * `animation` - 現在の画像のアニメーション
+    fn start_animation(&mut self, animation: Animation) {
+        // 8bitのテクスチャにそのまま書き込めるフレームのみ再生する（縮小して転送した場合などは静止画とする）
+        let writable = self.current_texture.as_ref().is_some_and(|texture| {
+            texture.format == wgpu::TextureFormat::Rgba8UnormSrgb
+                && texture.dimensions == animation.current_frame().dimensions()
+        });
+        if writable {
+            info!("アニメーションを再生します: {:.1}秒", animation.duration().as_secs_f32());
+            self.animation = Some(animation);
+        } else {
+            warn!("アニメーションのフレームをテクスチャに書き込めないため、静止画として表示します");
+        }
+    }
+
+    /// 画像の切り替え効果が有効かどうかを取得する
+    ///
+    /// # Returns
+    /// * `bool` - 有効な場合は true
+    fn transition_enabled(&self) -> bool {
+        self.app_config.transition != Transition::None && self.app_config.transition_duration_secs > 0.0
+    }
+
+    /// 画像の切り替え効果を開始する
+    ///
+    /// # Arguments
+    /// * `previous` - 切り替え前の画像のテクスチャ
+    /// * `previous_index` - 切り替え前の画像のインデックス
+    /// * `index` - 切り替え後の画像のインデックス
+    fn start_transition(&mut self, previous: TextureInfo, previous_index: usize, index: usize) {
+        // 末尾と先頭をまたいで移動した場合も、操作した向きにスライドさせる
+        let last = self.image_handler.len().saturating_sub(1);
+        let forward = match (previous_index, index) {
+            (previous_index, 0) if previous_index == last => true,
+            (0, index) if index == last => false,
+            _ => index > previous_index,
+        };
+        self.transition = Some(TransitionState {
+            kind: self.app_config.transition,
+            previous,
+            started: Instant::now(),
+            direction: if forward { 1.0 } else { -1.0 },
+        });
+        self.update_display_uniforms();
+    }
+
+    /// 画像の切り替え効果の進み具合を取得する
+    ///
+    /// # Arguments
+    /// * `transition` - 切り替え効果の状態
+    ///
+    /// # Returns
+    /// * `f32` - 進み具合（0.0〜1.0、緩急をつけた値）
+    fn transition_progress(&self, transition: &TransitionState) -> f32 {
+        let duration = self.app_config.transition_duration_secs.max(f32::EPSILON);
+        let t = (transition.started.elapsed().as_secs_f32() / duration).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// 画像の切り替え効果を進め、終わっていれば前の画像のテクスチャを破棄する
+    fn update_transition(&mut self) {
+        let Some(transition) = &self.transition else {
+            return;
+        };
+        if transition.started.elapsed().as_secs_f32() >= self.app_config.transition_duration_secs {
+            self.transition = None;
+        }
+        self.update_display_uniforms();
+    }
+
+    /// 現在の画像を読み込む
+    fn load_current_image(&mut self) {
+        self.animation = None;
+        if let Some(slideshow) = &mut self.slideshow {
+            slideshow.restart_timer();
+        }
+        self.metadata = None;
+        if self.show_metadata {
+            self.refresh_metadata();
+        }
+        self.image_info = None;
+        self.show_osd_temporarily();
+        self.show_filmstrip_temporarily();
+
+        // 別の画像へ切り替えるときは、切り替え効果が終わるまで前の画像のテクスチャを残しておく
+        let index = self.image_handler.current_index;
+        let previous_index = self.loaded_index.replace(index);
+        self.transition = None;
+        // 補正を保たない設定の場合は、別の画像へ切り替えるたびに設定ファイルの値に戻す
+        if !self.app_config.sticky_adjustments && previous_index != Some(index) {
+            self.reset_adjustments();
+        }
+        // 比較表示中は同じ部分を見比べられるよう、拡大率と移動量を保つ
+        if self.compare_mode.is_none() {
+            self.view_zoom = 1.0;
+            self.pan = (0.0, 0.0);
+        }
+        let previous = match previous_index {
+            Some(previous_index) if previous_index != index && self.transition_enabled() && self.compare_mode.is_none() => {
+                self.current_texture.take().map(|texture| (texture, previous_index))
+            }
+            _ => None,
+        };
+
+        if let Some(image_file) = self.image_handler.current_image() {
+            let file_path = image_file.path.clone();
+            let file_name = image_file.name.clone();
+            // フォルダー比較中は、同じ名前の画像を比較する画像にする
+            if let Some(counterpart) = self.folder_comparison.as_ref().and_then(|c| c.counterpart(&file_name)).map(Path::to_path_buf)
+                && !self.replace_compare_image(&counterpart)
+            {
+                self.compare = None;
+            }
+            info!("画像を読み込み中: {:?}", file_path);
+            match self.image_handler.load_image_with_animation(&file_path) {
+                Ok((image, animation)) => {
+                    let (image, file_name) = self.compose_spread(image, file_name);
+                    self.image_info = Some(ImageInfo::new(&file_path, &file_name, &image));
+                    let loaded = self.load_texture(&image);
+                    self.source_image = Some(Arc::new(image));
+                    self.request_difference();
+                    self.request_scopes();
+                    if let Err(e) = loaded {
                         error!("テクスチャの読み込みに失敗: {:?}", e);
                     } else {
+                        if let Some((texture, previous_index)) = previous {
+                            self.start_transition(texture, previous_index, index);
+                        }
+                        // 見開きで並べた画像はアニメーションを再生しない
+                        if let Some(animation) = animation
+                            && self.shown_pages.len() == 1
+                        {
+                            self.start_animation(animation);
+                        }
                         // ウィンドウタイトルを更新
                         self.update_window_title(&file_name);
                     }
                 }
                 Err(e) => {
                     error!("画像ファイルの読み込みに失敗: {:?}", e);
+                    self.shown_pages = index..index + 1;
+                    // 前の画像を表示したままにしないよう、テクスチャを破棄する
+                    self.current_texture = None;
+                    self.source_image = None;
+                    self.request_difference();
+                    self.update_window_title(&format!("{} (読み込み失敗)", file_name));
+                }
+            }
+        }
+    }
+
+    /// 見開き表示中であれば、表示中のページと組になるページを読み込んで左右に並べる
+    ///
+    /// # Arguments
+    /// * `image` - 表示中のページの画像
+    /// * `file_name` - 表示中のページのファイル名
+    ///
+    /// # Returns
+    /// * `(DynamicImage, String)` - 表示する画像と、読む順に並べたファイル名
+    fn compose_spread(&mut self, image: DynamicImage, file_name: String) -> (DynamicImage, String) {
+        let index = self.image_handler.current_index;
+        self.shown_pages = index..index + 1;
+        if !self.spread {
+            return (image, file_name);
+        }
+        let pages = self.spread_layout.spread_at(&self.image_handler, index);
+        if pages.len() < 2 {
+            return (image, file_name);
+        }
+
+        let other_index = if index == pages.start { pages.start + 1 } else { pages.start };
+        let other_file = &self.image_handler.images()[other_index];
+        let other = match self.image_handler.load_image(&other_file.path) {
+            Ok(other) => other,
+            Err(e) => {
+                warn!("見開きのもう一方のページの読み込みに失敗したため、1ページで表示します: {:?}", e);
+                return (image, file_name);
+            }
+        };
+        let other_name = other_file.name.clone();
+
+        // 読む順に並べ、右綴じの場合は先のページを右に置く
+        let (first, second, first_name, second_name) = if index == pages.start {
+            (image, other, file_name, other_name)
+        } else {
+            (other, image, other_name, file_name)
+        };
+        let composed = if self.right_to_left {
+            spread::compose(second, first)
+        } else {
+            spread::compose(first, second)
+        };
+        debug!("見開き表示: {} | {}", first_name, second_name);
+        self.shown_pages = pages;
+        (composed, format!("{} | {}", first_name, second_name))
+    }
+
+    /// 次のページ（見開き表示中は次の見開き）へ移動する
+    fn next_page(&mut self) {
+        if self.spread {
+            self.image_handler.current_index = self.spread_layout.next_start(&self.image_handler, self.image_handler.current_index);
+        } else {
+            self.image_handler.next_image();
+        }
+        self.load_current_image();
+    }
+
+    /// 前のページ（見開き表示中は前の見開き）へ移動する
+    fn previous_page(&mut self) {
+        if self.spread {
+            self.image_handler.current_index = self.spread_layout.previous_start(&self.image_handler, self.image_handler.current_index);
+        } else {
+            self.image_handler.previous_image();
+        }
+        self.load_current_image();
+    }
+
+    /// 現在の画像のレイヤー名一覧をログに出力する
+    fn log_layers(&self) {
+        if let Some(image_file) = self.image_handler.current_image() {
+            match self.image_handler.layer_names(&image_file.path) {
+                Ok(names) if names.is_empty() => info!("レイヤー情報がありません: {}", image_file.name),
+                Ok(names) => {
+                    info!("レイヤー一覧 ({}個): {}", names.len(), image_file.name);
+                    for (index, name) in names.iter().enumerate() {
+                        info!("  [{}] {}", index, name);
+                    }
                 }
+                Err(e) => error!("レイヤー情報の取得に失敗: {:?}", e),
             }
         }
     }
@@ -545,12 +2569,103 @@ impl ImageViewer {
     /// * `filename` - 表示するファイル名
     fn update_window_title(&self, filename: &str) {
         if let Some(window) = &self.window {
-            let title = format!("{} - rs_fast_image_viewer", filename);
+            let title = match self.channel_view_label() {
+                Some(label) => format!("{} [{}] - rs_fast_image_viewer", filename, label),
+                None => format!("{} - rs_fast_image_viewer", filename),
+            };
             window.set_title(&title);
             debug!("ウィンドウタイトルを更新: {}", title);
         }
     }
 
+    /// 保存されていたウィンドウの位置とサイズを、接続中のモニターに収まるよう補正する
+    ///
+    /// 最も重なりの大きいモニター（重ならない場合はプライマリモニター）の中に収める
+    ///
+    /// # Arguments
+    /// * `event_loop` - イベントループ
+    /// * `position` - 保存されていた位置（物理ピクセル）
+    /// * `size` - 保存されていたサイズ（物理ピクセル）
+    ///
+    /// # Returns
+    /// * `(Option<PhysicalPosition<i32>>, PhysicalSize<u32>)` - 補正した位置とサイズ（位置を決められない場合は None）
+    fn restore_geometry(
+        event_loop: &ActiveEventLoop,
+        position: Option<(i32, i32)>,
+        size: (u32, u32),
+    ) -> (Option<PhysicalPosition<i32>>, PhysicalSize<u32>) {
+        let overlap = |monitor: &MonitorHandle, (x, y): (i32, i32)| -> i64 {
+            let origin = monitor.position();
+            let extent = monitor.size();
+            let width = (x + size.0 as i32).min(origin.x + extent.width as i32) - x.max(origin.x);
+            let height = (y + size.1 as i32).min(origin.y + extent.height as i32) - y.max(origin.y);
+            width.max(0) as i64 * height.max(0) as i64
+        };
+        let monitor = position
+            .and_then(|position| {
+                event_loop.available_monitors()
+                    .map(|monitor| (overlap(&monitor, position), monitor))
+                    .filter(|(area, _)| *area > 0)
+                    .max_by_key(|(area, _)| *area)
+                    .map(|(_, monitor)| monitor)
+            })
+            .or_else(|| event_loop.primary_monitor())
+            .or_else(|| event_loop.available_monitors().next());
+        let Some(monitor) = monitor else {
+            return (position.map(PhysicalPosition::from), PhysicalSize::new(size.0, size.1));
+        };
+
+        let origin = monitor.position();
+        let extent = monitor.size();
+        let width = size.0.clamp(1, extent.width.max(1));
+        let height = size.1.clamp(1, extent.height.max(1));
+        let position = position.map(|(x, y)| {
+            PhysicalPosition::new(
+                x.clamp(origin.x, origin.x + extent.width.saturating_sub(width) as i32),
+                y.clamp(origin.y, origin.y + extent.height.saturating_sub(height) as i32),
+            )
+        });
+        debug!("ウィンドウの位置とサイズを復元: {:?} {}x{} ({:?})", position, width, height, monitor.name());
+        (position, PhysicalSize::new(width, height))
+    }
+
+    /// 最大化・全画面表示していないときのウィンドウの位置とサイズを記録する
+    fn record_window_geometry(&mut self) {
+        let (Some(window), Some(state)) = (&self.window, &mut self.view_state) else {
+            return;
+        };
+        if window.fullscreen().is_some() || window.is_maximized() || window.is_minimized() == Some(true) {
+            return;
+        }
+        let size = window.inner_size();
+        if size.width > 0 && size.height > 0 {
+            state.window_size = Some((size.width, size.height));
+        }
+        if let Ok(position) = window.outer_position() {
+            state.window_position = Some((position.x, position.y));
+        }
+    }
+
+    /// 現在のウィンドウと表示設定の状態を状態ファイルに保存する
+    fn save_view_state(&mut self) {
+        self.record_window_geometry();
+        let Some(state) = &mut self.view_state else {
+            return;
+        };
+        if let Some(window) = &self.window {
+            state.maximized = window.is_maximized();
+            state.fullscreen = window.fullscreen().is_some();
+        }
+        state.fit_mode = Some(self.fit_mode);
+        state.background = Some(self.background);
+        state.sort_algorithm = Some(self.image_handler.sort_algorithm());
+
+        let result = ViewState::get_state_path().and_then(|path| state.save(&path));
+        if let Err(e) = result {
+            error!("状態ファイルの保存に失敗: {:?}", e);
+        }
+    }
+
     /// イベントループを実行する
     /// 
     /// # Arguments
@@ -567,15 +2682,30 @@ impl ImageViewer {
 impl ApplicationHandler for ImageViewer {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         if self.window.is_none() {
-            let window_attributes = Window::default_attributes()
+            let mut window_attributes = Window::default_attributes()
                 .with_title("rs_fast_image_viewer")
                 .with_inner_size(LogicalSize::new(
                     self.app_config.min_window_size.0,
                     self.app_config.min_window_size.1,
                 ));
+            // 前回終了時の位置とサイズを、接続中のモニターに収まるよう補正して復元する
+            if let Some(state) = &self.view_state {
+                if let Some(size) = state.window_size {
+                    let (position, size) = Self::restore_geometry(event_loop, state.window_position, size);
+                    window_attributes = window_attributes.with_inner_size(size);
+                    if let Some(position) = position {
+                        window_attributes = window_attributes.with_position(position);
+                    }
+                }
+                window_attributes = window_attributes.with_maximized(state.maximized);
+            }
             
             let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+            self.monitor = window.current_monitor();
             self.window = Some(window);
+            if self.app_config.fullscreen || self.view_state.as_ref().is_some_and(|state| state.fullscreen) {
+                self.set_fullscreen(true);
+            }
 
             // WGPUを初期化（非同期処理をブロック）
             pollster::block_on(async {
@@ -587,10 +2717,20 @@ impl ApplicationHandler for ImageViewer {
 
                 // 最初の画像を読み込む
                 self.load_current_image();
+                if let Some(path) = self.compare_path.take() {
+                    self.load_compare_image(&path);
+                }
+                if self.app_config.webtoon {
+                    self.enter_webtoon();
+                }
             });
         }
     }
 
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        self.save_view_state();
+    }
+
     fn window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
         if Some(window_id) != self.window.as_ref().map(|w| w.id()) {
             return;
@@ -601,30 +2741,321 @@ impl ApplicationHandler for ImageViewer {
                 info!("アプリケーションを終了します");
                 event_loop.exit();
             }
-            WindowEvent::KeyboardInput { event, .. } => {
-                if event.state.is_pressed() {
-                    match event.physical_key {
-                        PhysicalKey::Code(KeyCode::Escape) | PhysicalKey::Code(KeyCode::Enter) => {
-                            info!("アプリケーションを終了します");
-                            event_loop.exit();
-                        }
-                        PhysicalKey::Code(KeyCode::ArrowRight) | PhysicalKey::Code(KeyCode::KeyX) => {
-                            self.image_handler.next_image();
-                            self.load_current_image();
-                        }
-                        PhysicalKey::Code(KeyCode::ArrowLeft) | PhysicalKey::Code(KeyCode::KeyZ) => {
-                            self.image_handler.previous_image();
-                            self.load_current_image();
+            // 拡大鏡はキーを押している間だけ表示する
+            WindowEvent::KeyboardInput { event, .. } if event.physical_key == PhysicalKey::Code(KeyCode::KeyQ) => {
+                self.set_loupe(event.state.is_pressed());
+            }
+            WindowEvent::Focused(false) => {
+                // キーを離したことが伝わらない場合があるため、フォーカスを失ったら拡大鏡を閉じる
+                self.set_loupe(false);
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state.is_pressed() => {
+                self.show_osd_temporarily();
+                self.show_filmstrip_temporarily();
+                if self.gallery_mode {
+                    self.handle_gallery_key(event.physical_key);
+                    return;
+                }
+                if self.webtoon_mode {
+                    self.handle_webtoon_key(event.physical_key);
+                    return;
+                }
+                match event.physical_key {
+                    PhysicalKey::Code(KeyCode::Escape) if self.is_fullscreen() => {
+                        self.set_fullscreen(false);
+                    }
+                    PhysicalKey::Code(KeyCode::Escape) | PhysicalKey::Code(KeyCode::Enter) => {
+                        info!("アプリケーションを終了します");
+                        event_loop.exit();
+                    }
+                    // 矢印キーは読む向きに合わせ、右綴じでは左が次のページになる
+                    PhysicalKey::Code(KeyCode::ArrowRight) if self.right_to_left => self.previous_page(),
+                    PhysicalKey::Code(KeyCode::ArrowLeft) if self.right_to_left => self.next_page(),
+                    PhysicalKey::Code(KeyCode::ArrowRight) | PhysicalKey::Code(KeyCode::KeyX) => self.next_page(),
+                    PhysicalKey::Code(KeyCode::ArrowLeft) | PhysicalKey::Code(KeyCode::KeyZ) => self.previous_page(),
+                    PhysicalKey::Code(KeyCode::BracketRight) => {
+                        self.set_exposure(self.exposure + EXPOSURE_STEP);
+                    }
+                    PhysicalKey::Code(KeyCode::BracketLeft) => {
+                        self.set_exposure(self.exposure - EXPOSURE_STEP);
+                    }
+                    PhysicalKey::Code(KeyCode::Backslash) => {
+                        self.set_exposure(self.app_config.exposure);
+                    }
+                    PhysicalKey::Code(KeyCode::Digit1) => self.step_adjustment(Adjustment::Brightness, -1.0),
+                    PhysicalKey::Code(KeyCode::Digit2) => self.step_adjustment(Adjustment::Brightness, 1.0),
+                    PhysicalKey::Code(KeyCode::Digit3) => self.step_adjustment(Adjustment::Contrast, -1.0),
+                    PhysicalKey::Code(KeyCode::Digit4) => self.step_adjustment(Adjustment::Contrast, 1.0),
+                    PhysicalKey::Code(KeyCode::Digit5) => self.step_adjustment(Adjustment::Gamma, -1.0),
+                    PhysicalKey::Code(KeyCode::Digit6) => self.step_adjustment(Adjustment::Gamma, 1.0),
+                    PhysicalKey::Code(KeyCode::Digit7) => self.step_adjustment(Adjustment::Saturation, -1.0),
+                    PhysicalKey::Code(KeyCode::Digit8) => self.step_adjustment(Adjustment::Saturation, 1.0),
+                    PhysicalKey::Code(KeyCode::Digit9) => {
+                        self.reset_adjustments();
+                        info!("露出補正と色補正を元に戻しました");
+                    }
+                    PhysicalKey::Code(KeyCode::KeyA) => {
+                        self.show_adjustments = !self.show_adjustments;
+                    }
+                    PhysicalKey::Code(KeyCode::KeyU) => {
+                        self.cycle_channel_view();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyS) if self.modifiers.control_key() => {
+                        self.export_image();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyT) => {
+                        self.tone_mapping = self.tone_mapping.next();
+                        self.update_display_uniforms();
+                        info!("トーンマッピング: {:?}", self.tone_mapping);
+                    }
+                    PhysicalKey::Code(KeyCode::KeyC) => {
+                        let enabled = !self.image_handler.color_management();
+                        self.image_handler.set_color_management(enabled);
+                        self.load_current_image();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyL) => {
+                        self.log_layers();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyI) => {
+                        self.toggle_metadata();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyE) => {
+                        self.export_metadata();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyG) => {
+                        self.enter_gallery();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyF) => {
+                        self.show_filmstrip = !self.show_filmstrip;
+                        self.show_filmstrip_temporarily();
+                        info!("フィルムストリップ表示: {}", if self.show_filmstrip { "オン" } else { "オフ" });
+                    }
+                    PhysicalKey::Code(KeyCode::KeyO) => {
+                        self.show_osd = !self.show_osd;
+                        info!("OSD表示: {}", if self.show_osd { "オン" } else { "オフ" });
+                    }
+                    PhysicalKey::Code(KeyCode::KeyM) => {
+                        self.fit_mode = self.fit_mode.next();
+                        self.fit_window_to_image();
+                        self.update_display_uniforms();
+                        info!("表示サイズ: {:?}", self.fit_mode);
+                    }
+                    PhysicalKey::Code(KeyCode::KeyB) => {
+                        self.background = self.background.next();
+                        self.update_display_uniforms();
+                        info!("背景: {:?}", self.background);
+                    }
+                    PhysicalKey::Code(KeyCode::KeyS) => {
+                        let sort_algorithm = self.image_handler.sort_algorithm().next();
+                        self.image_handler.set_sort_algorithm(sort_algorithm);
+                        // 並び順が変わるため、サムネイル一覧とフィルムストリップと縦読み表示を作り直し、見開きの組み合わせも数え直す
+                        self.spread_layout.reset();
+                        self.gallery = None;
+                        self.filmstrip = None;
+                        self.webtoon = None;
+                    }
+                    PhysicalKey::Code(KeyCode::KeyW) => {
+                        self.enter_webtoon();
+                    }
+                    PhysicalKey::Code(KeyCode::Equal) | PhysicalKey::Code(KeyCode::NumpadAdd) => {
+                        self.zoom_by(ZOOM_STEP, None);
+                    }
+                    PhysicalKey::Code(KeyCode::Minus) | PhysicalKey::Code(KeyCode::NumpadSubtract) => {
+                        self.zoom_by(1.0 / ZOOM_STEP, None);
+                    }
+                    PhysicalKey::Code(KeyCode::Digit0) | PhysicalKey::Code(KeyCode::Numpad0) => {
+                        self.reset_view();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyP) => {
+                        if let Some(path) = self.image_handler.current_image().map(|image| image.path.clone()) {
+                            self.load_compare_image(&path);
                         }
-                        PhysicalKey::Code(KeyCode::F4) => {
-                            // Alt+F4 の処理は OS レベルで処理される
+                    }
+                    PhysicalKey::Code(KeyCode::KeyV) => {
+                        self.cycle_compare_mode();
+                    }
+                    PhysicalKey::Code(KeyCode::Tab) => {
+                        self.swap_compare();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyJ) => {
+                        self.cycle_difference_view();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyN) => {
+                        self.show_minimap = !self.show_minimap;
+                        info!("ミニマップ: {}", if self.show_minimap { "表示" } else { "非表示" });
+                    }
+                    PhysicalKey::Code(KeyCode::KeyK) => {
+                        self.show_inspector = !self.show_inspector;
+                        info!("ピクセルインスペクター: {}", if self.show_inspector { "表示" } else { "非表示" });
+                    }
+                    PhysicalKey::Code(KeyCode::KeyY) => {
+                        self.copy_pixel_value();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyH) if self.modifiers.shift_key() => {
+                        self.scope_log_scale = !self.scope_log_scale;
+                        info!("ヒストグラムの縦軸: {}", if self.scope_log_scale { "対数" } else { "線形" });
+                    }
+                    PhysicalKey::Code(KeyCode::KeyH) => {
+                        self.cycle_scope();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyD) => {
+                        self.spread = !self.spread;
+                        info!("見開き表示: {}", if self.spread { "オン" } else { "オフ" });
+                        self.load_current_image();
+                    }
+                    PhysicalKey::Code(KeyCode::KeyR) => {
+                        self.right_to_left = !self.right_to_left;
+                        info!("読む向き: {}", if self.right_to_left { "右から左" } else { "左から右" });
+                        self.load_current_image();
+                    }
+                    PhysicalKey::Code(KeyCode::F5) => {
+                        self.toggle_slideshow();
+                    }
+                    PhysicalKey::Code(KeyCode::Space) => {
+                        if let Some(slideshow) = &mut self.slideshow {
+                            slideshow.toggle_pause();
                         }
-                        _ => {}
+                    }
+                    PhysicalKey::Code(KeyCode::F11) => {
+                        self.set_fullscreen(!self.is_fullscreen());
+                    }
+                    PhysicalKey::Code(KeyCode::F4) => {
+                        // Alt+F4 の処理は OS レベルで処理される
+                    }
+                    _ => {}
+                }
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let (x, y) = (position.x as f32, position.y as f32);
+                match self.drag {
+                    Some(Drag::Pan((last_x, last_y))) => {
+                        self.pan = (self.pan.0 + x - last_x, self.pan.1 + y - last_y);
+                        self.drag = Some(Drag::Pan((x, y)));
+                        self.update_display_uniforms();
+                    }
+                    Some(Drag::Split) => {
+                        let width = self.config.as_ref().map_or(1, |config| config.width.max(1));
+                        self.split_position = (x / width as f32).clamp(0.0, 1.0);
+                        self.update_display_uniforms();
+                    }
+                    Some(Drag::Minimap) => self.pan_to_minimap(x, y),
+                    None => {}
+                }
+                self.cursor_position = (x, y);
+                if self.show_loupe {
+                    self.update_display_uniforms();
+                }
+                self.show_filmstrip_temporarily();
+            }
+            WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. } => {
+                self.drag = None;
+            }
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => {
+                let (x, y) = self.cursor_position;
+                if self.gallery_mode {
+                    if let Some(gallery) = &mut self.gallery
+                        && gallery.click(x, y)
+                    {
+                        self.open_gallery_selection();
+                    }
+                } else if self.show_filmstrip
+                    && !self.webtoon_mode
+                    && let Some(index) = self.filmstrip.as_ref().and_then(|f| f.index_at(x, y))
+                {
+                    if index != self.image_handler.current_index {
+                        self.image_handler.current_index = index;
+                        self.load_current_image();
+                    }
+                } else if !self.webtoon_mode && self.minimap.contains(x, y) {
+                    // ミニマップをクリックした位置を表示し、そのままドラッグで表示範囲を動かす
+                    self.drag = Some(Drag::Minimap);
+                    self.pan_to_minimap(x, y);
+                } else {
+                    // 分割スライダーの近くならスライダーを、そうでなければ画像をドラッグで動かす
+                    let split_x = self.split_position * self.config.as_ref().map_or(0.0, |config| config.width as f32);
+                    let grab = SPLIT_GRAB_DISTANCE * self.window.as_ref().map_or(1.0, |window| window.scale_factor() as f32);
+                    self.drag = if self.webtoon_mode {
+                        None
+                    } else if self.compare.is_some()
+                        && self.compare_mode == Some(CompareMode::Split)
+                        && (x - split_x).abs() <= grab
+                    {
+                        Some(Drag::Split)
+                    } else {
+                        Some(Drag::Pan((x, y)))
+                    };
+
+                    // 画像をダブルクリックしたら全画面表示を切り替える
+                    let now = Instant::now();
+                    if self.last_click.is_some_and(|last| now.duration_since(last) < gallery::DOUBLE_CLICK_INTERVAL) {
+                        self.last_click = None;
+                        self.set_fullscreen(!self.is_fullscreen());
+                    } else {
+                        self.last_click = Some(now);
+                    }
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                if self.webtoon_mode
+                    && let Some(webtoon) = &mut self.webtoon
+                {
+                    let amount = match delta {
+                        MouseScrollDelta::LineDelta(_, lines) => -lines * webtoon.wheel_step(),
+                        MouseScrollDelta::PixelDelta(position) => -position.y as f32,
+                    };
+                    webtoon.scroll_by(amount);
+                } else if self.gallery_mode
+                    && let Some(gallery) = &mut self.gallery
+                {
+                    let amount = match delta {
+                        MouseScrollDelta::LineDelta(_, lines) => -lines * gallery.wheel_step(),
+                        MouseScrollDelta::PixelDelta(position) => -position.y as f32,
+                    };
+                    gallery.scroll_by(amount);
+                } else if !self.gallery_mode {
+                    let steps = match delta {
+                        MouseScrollDelta::LineDelta(_, lines) => lines,
+                        MouseScrollDelta::PixelDelta(position) => position.y as f32 / 100.0,
+                    };
+                    if self.show_loupe {
+                        // 拡大鏡の表示中は拡大鏡の倍率を変える
+                        self.set_loupe_magnification(self.loupe_magnification * ZOOM_STEP.powf(steps));
+                    } else {
+                        // カーソルの位置を中心に拡大・縮小する
+                        self.zoom_by(ZOOM_STEP.powf(steps), Some(self.cursor_position));
                     }
                 }
             }
             WindowEvent::Resized(physical_size) => {
                 self.resize(physical_size.width, physical_size.height);
+                self.record_window_geometry();
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, mut inner_size_writer } => {
+                // 最小ウィンドウサイズは論理ピクセルのため、新しい表示倍率で計算し直す
+                info!("表示倍率が変更されました: {}", scale_factor);
+                if !self.is_fullscreen()
+                    && self.fit_mode == FitMode::Window
+                    && let Some((width, height)) = self.image_size()
+                {
+                    let monitor = self.window.as_deref().and_then(Self::monitor_for_window);
+                    let new_size = self.window_size_for_image(width, height, monitor.as_ref(), scale_factor);
+                    if let Err(e) = inner_size_writer.request_inner_size(new_size) {
+                        error!("ウィンドウサイズの変更に失敗: {:?}", e);
+                    }
+                }
+            }
+            WindowEvent::Moved(_) => {
+                // 別のモニターに移動した場合は、そのモニターの作業領域に合わせてウィンドウサイズを調整する
+                let monitor = self.window.as_ref().and_then(|window| window.current_monitor());
+                if monitor != self.monitor {
+                    debug!("モニターを移動しました: {:?}", monitor.as_ref().and_then(|m| m.name()));
+                    self.monitor = monitor;
+                    self.fit_window_to_image();
+                }
+                self.record_window_geometry();
             }
             WindowEvent::RedrawRequested => {
                 match self.render() {
@@ -650,6 +3081,8 @@ impl ApplicationHandler for ImageViewer {
     }
 
     fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        self.update_animation();
+        self.advance_slideshow();
         if let Some(window) = &self.window {
             window.request_redraw();
         }
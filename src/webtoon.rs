@@ -0,0 +1,445 @@
+use image::{DynamicImage, RgbaImage, imageops};
+use log::{debug, warn};
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Instant;
+use crate::image_handler::{ImageFile, ImageHandler};
+use crate::overlay::{OverlayRenderer, Rect};
+
+/// 1枚のテクスチャに転送する最大の高さ（縦に長い画像はこの高さごとに分割する）
+const TILE_HEIGHT: u32 = 4096;
+/// スクロールの追従の速さ（1秒あたり、大きいほど速く目標位置に近づく）
+const SCROLL_SMOOTHING: f32 = 14.0;
+/// 表示範囲の上下に先読みする量（画面の高さに対する割合）
+const PREFETCH_SCREENS: f32 = 0.5;
+/// 読み込み中の画像の代わりに表示する色
+const PLACEHOLDER_COLOR: [f32; 4] = [0.02, 0.02, 0.02, 1.0];
+/// 文字色
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// 読み込みを要求する画像
+struct PageJob {
+    /// 画像リスト内のインデックス
+    index: usize,
+    /// 画像ファイルのパス
+    path: PathBuf,
+    /// 縮小後の幅（ピクセル、これより小さい画像は拡大しない）
+    width: u32,
+}
+
+/// 画像の読み込み結果
+struct PageResult {
+    /// 画像リスト内のインデックス
+    index: usize,
+    /// 要求した幅
+    width: u32,
+    /// 読み込んだ画像（失敗した場合は None）
+    image: Option<RgbaImage>,
+}
+
+/// 画像の状態
+enum PageState {
+    /// GPUに転送済み（要求した幅と、上から順に並べたテクスチャと高さ）
+    Loaded(u32, Vec<(wgpu::BindGroup, u32)>),
+    /// 読み込みに失敗した
+    Failed,
+}
+
+/// 縦に並べた画像のレイアウト（ピクセル単位）
+struct StripLayout {
+    /// 画像の表示幅（画面の幅）
+    width: f32,
+    /// 各画像の上端の位置
+    tops: Vec<f32>,
+    /// 全体の高さ
+    total_height: f32,
+    /// 画面の高さ
+    screen_height: f32,
+}
+
+impl StripLayout {
+    /// 画像の表示上の高さを取得する
+    fn height(&self, index: usize) -> f32 {
+        self.tops.get(index + 1).copied().unwrap_or(self.total_height) - self.tops[index]
+    }
+
+    /// 指定した位置にある画像のインデックスを取得する
+    fn index_at(&self, y: f32) -> usize {
+        self.tops.partition_point(|&top| top <= y).saturating_sub(1)
+    }
+
+    /// 指定した範囲に重なる画像の範囲を取得する
+    fn range(&self, top: f32, bottom: f32) -> Range<usize> {
+        if self.tops.is_empty() {
+            return 0..0;
+        }
+        let start = self.index_at(top.max(0.0));
+        let end = self.tops.partition_point(|&y| y < bottom).max(start + 1);
+        start..end
+    }
+
+    /// スクロール量の上限を取得する
+    fn max_scroll(&self) -> f32 {
+        (self.total_height - self.screen_height).max(0.0)
+    }
+}
+
+/// 画像を縦に途切れなく並べ、スクロールして読む縦読み（Webtoon）表示
+///
+/// 画像は画面の幅に合わせて表示し、表示範囲の画像だけをワーカースレッドで読み込んでGPUに保持する
+pub struct Webtoon {
+    /// 画像ファイルのパス
+    paths: Vec<PathBuf>,
+    /// 画像ファイル名
+    names: Vec<String>,
+    /// 元画像の幅と高さ（取得できない場合は None）
+    sizes: Vec<Option<(u32, u32)>>,
+    /// 読み込み要求の送信側
+    requests: Sender<Vec<PageJob>>,
+    /// 読み込み結果の受信側
+    results: Receiver<PageResult>,
+    /// GPUに転送済みの画像
+    pages: HashMap<usize, PageState>,
+    /// 直前に読み込みを要求した範囲と幅
+    requested: Option<(Range<usize>, u32)>,
+    /// 現在のスクロール量（ピクセル）
+    scroll: f32,
+    /// スクロールの目標位置（ピクセル）
+    target_scroll: f32,
+    /// 次のレイアウト計算時に上端を合わせる画像のインデックス
+    scroll_to_index: Option<usize>,
+    /// 直前にスクロール位置を更新した時刻
+    last_update: Instant,
+    /// 直前のフレームのレイアウト
+    layout: Option<StripLayout>,
+}
+
+impl Webtoon {
+    /// 新しい Webtoon インスタンスを作成し、読み込みスレッドを起動する
+    ///
+    /// # Arguments
+    /// * `images` - 表示する画像ファイルのリスト
+    /// * `decoder` - 画像の読み込みに使う画像ハンドラー
+    /// * `index` - 最初に表示する画像のインデックス
+    ///
+    /// # Returns
+    /// * `Webtoon` - 縦読み表示
+    pub fn new(images: &[ImageFile], decoder: Arc<ImageHandler>, index: usize) -> Self {
+        // 全体の高さを決めるため、各画像の大きさを先に調べる
+        let started = Instant::now();
+        let sizes = images.iter()
+            .map(|image| match decoder.image_dimensions(&image.path) {
+                Ok(size) => Some(size),
+                Err(e) => {
+                    warn!("画像サイズの取得に失敗: {:?} ({:?})", image.path, e);
+                    None
+                }
+            })
+            .collect();
+        debug!("縦読み表示のレイアウト用に画像サイズを取得しました: {}個 ({:?})", images.len(), started.elapsed());
+
+        let (requests, job_receiver) = mpsc::channel();
+        let (result_sender, results) = mpsc::channel();
+        let spawned = thread::Builder::new()
+            .name("webtoon-loader".to_string())
+            .spawn(move || Self::worker_loop(&decoder, &job_receiver, &result_sender));
+        if let Err(e) = spawned {
+            warn!("縦読み表示の読み込みスレッドの起動に失敗: {:?}", e);
+        }
+
+        Webtoon {
+            paths: images.iter().map(|image| image.path.clone()).collect(),
+            names: images.iter().map(|image| image.name.clone()).collect(),
+            sizes,
+            requests,
+            results,
+            pages: HashMap::new(),
+            requested: None,
+            scroll: 0.0,
+            target_scroll: 0.0,
+            scroll_to_index: Some(index),
+            last_update: Instant::now(),
+            layout: None,
+        }
+    }
+
+    /// 読み込みスレッドの処理
+    ///
+    /// 新しい要求が届いたら、まだ読み込んでいない分を捨てて置き換える
+    fn worker_loop(decoder: &ImageHandler, jobs: &Receiver<Vec<PageJob>>, results: &Sender<PageResult>) {
+        let mut pending = VecDeque::new();
+        loop {
+            if pending.is_empty() {
+                match jobs.recv() {
+                    Ok(next) => pending = VecDeque::from(next),
+                    Err(_) => return,
+                }
+            }
+            while let Ok(next) = jobs.try_recv() {
+                pending = VecDeque::from(next);
+            }
+            let Some(job) = pending.pop_front() else {
+                continue;
+            };
+
+            let image = match decoder.load_image(&job.path) {
+                Ok(image) => Some(Self::fit_width(image, job.width)),
+                Err(e) => {
+                    warn!("縦読み表示の画像の読み込みに失敗: {:?} ({:?})", job.path, e);
+                    None
+                }
+            };
+            if results.send(PageResult { index: job.index, width: job.width, image }).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// 画像を指定した幅に縮小する（拡大はしない）
+    fn fit_width(image: DynamicImage, width: u32) -> RgbaImage {
+        if image.width() <= width {
+            return image.to_rgba8();
+        }
+        let height = (image.height() as u64 * width as u64 / image.width() as u64).max(1) as u32;
+        image.resize_exact(width, height, imageops::FilterType::Triangle).to_rgba8()
+    }
+
+    /// 画面の中央に表示している画像のインデックスを取得する
+    ///
+    /// # Returns
+    /// * `Option<usize>` - 画像のインデックス（レイアウト前は None）
+    pub fn current(&self) -> Option<usize> {
+        let layout = self.layout.as_ref()?;
+        (!layout.tops.is_empty()).then(|| layout.index_at(self.scroll + layout.screen_height / 2.0))
+    }
+
+    /// 指定した画像の上端までスクロールする
+    ///
+    /// # Arguments
+    /// * `index` - 画像のインデックス
+    pub fn jump_to(&mut self, index: usize) {
+        self.scroll_to_index = Some(index.min(self.paths.len().saturating_sub(1)));
+    }
+
+    /// 前後の画像の上端までスクロールする
+    ///
+    /// # Arguments
+    /// * `delta` - 移動する画像の数（負の値で前へ）
+    pub fn move_images(&mut self, delta: isize) {
+        let Some(layout) = &self.layout else {
+            return;
+        };
+        // 画像の途中を表示している場合、前へ戻るとその画像の上端に合わせる
+        let top = layout.index_at(self.target_scroll + 1.0);
+        let target = if delta < 0 && layout.tops[top] < self.target_scroll - 1.0 {
+            top.saturating_add_signed(delta + 1)
+        } else {
+            top.saturating_add_signed(delta)
+        };
+        let target = target.min(layout.tops.len() - 1);
+        self.target_scroll = layout.tops[target].min(layout.max_scroll());
+    }
+
+    /// 縦方向にスクロールする
+    ///
+    /// # Arguments
+    /// * `delta` - スクロール量（ピクセル、正の値で下へ）
+    pub fn scroll_by(&mut self, delta: f32) {
+        let max_scroll = self.layout.as_ref().map_or(f32::MAX, StripLayout::max_scroll);
+        self.target_scroll = (self.target_scroll + delta).clamp(0.0, max_scroll);
+    }
+
+    /// 画面の高さを単位に縦方向にスクロールする（前後が少し重なるようにする）
+    ///
+    /// # Arguments
+    /// * `pages` - スクロールする画面数（負の値で上へ）
+    pub fn scroll_pages(&mut self, pages: f32) {
+        let screen_height = self.layout.as_ref().map_or(0.0, |layout| layout.screen_height);
+        self.scroll_by(pages * screen_height * 0.9);
+    }
+
+    /// 先頭または末尾までスクロールする
+    ///
+    /// # Arguments
+    /// * `end` - 末尾へスクロールする場合は true
+    pub fn scroll_to_edge(&mut self, end: bool) {
+        self.target_scroll = if end { f32::MAX } else { 0.0 };
+        self.scroll_by(0.0);
+    }
+
+    /// マウスホイール1段あたりのスクロール量を取得する
+    ///
+    /// # Returns
+    /// * `f32` - スクロール量（ピクセル）
+    pub fn wheel_step(&self) -> f32 {
+        self.layout.as_ref().map_or(100.0, |layout| layout.screen_height * 0.15)
+    }
+
+    /// GPUに転送した画像をすべて破棄する（縦読み表示を閉じたとき）
+    pub fn release(&mut self) {
+        self.pages.clear();
+        self.requested = None;
+    }
+
+    /// 画面の幅から各画像の位置を計算する
+    fn compute_layout(&self, screen_size: (f32, f32)) -> StripLayout {
+        let width = screen_size.0.max(1.0);
+        let mut tops = Vec::with_capacity(self.sizes.len());
+        let mut y = 0.0;
+        for size in &self.sizes {
+            tops.push(y);
+            // 大きさが分からない画像は正方形として扱う
+            y += size.map_or(width, |(w, h)| h as f32 * width / w.max(1) as f32);
+        }
+        StripLayout { width, tops, total_height: y, screen_height: screen_size.1.max(1.0) }
+    }
+
+    /// レイアウトの更新、スクロール、読み込んだ画像の転送、表示範囲の読み込み要求を行う
+    ///
+    /// # Arguments
+    /// * `device` - WGPU デバイス
+    /// * `queue` - WGPU キュー
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `screen_size` - 画面サイズ（ピクセル）
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        overlay: &OverlayRenderer,
+        screen_size: (f32, f32),
+    ) {
+        let layout = self.compute_layout(screen_size);
+        // 画面の幅が変わっても、表示中の画像の同じ位置を表示し続ける
+        if let Some(index) = self.scroll_to_index.take() {
+            self.scroll = layout.tops.get(index).copied().unwrap_or(0.0);
+            self.target_scroll = self.scroll;
+        } else if let Some(previous) = &self.layout
+            && previous.width != layout.width
+            && !previous.tops.is_empty()
+        {
+            let rescale = |y: f32| {
+                let index = previous.index_at(y);
+                let fraction = (y - previous.tops[index]) / previous.height(index).max(1.0);
+                layout.tops[index] + fraction * layout.height(index)
+            };
+            self.scroll = rescale(self.scroll);
+            self.target_scroll = rescale(self.target_scroll);
+        }
+        self.target_scroll = self.target_scroll.clamp(0.0, layout.max_scroll());
+
+        // 目標位置へ滑らかに近づける
+        let elapsed = self.last_update.elapsed().as_secs_f32();
+        self.last_update = Instant::now();
+        let follow = 1.0 - (-elapsed * SCROLL_SMOOTHING).exp();
+        self.scroll += (self.target_scroll - self.scroll) * follow;
+        if (self.target_scroll - self.scroll).abs() < 0.5 {
+            self.scroll = self.target_scroll;
+        }
+
+        // 表示範囲（と上下の先読み分）の外にある画像はGPUから破棄する
+        let prefetch = layout.screen_height * PREFETCH_SCREENS;
+        let resident = layout.range(self.scroll - prefetch, self.scroll + layout.screen_height + prefetch);
+        let before = self.pages.len();
+        self.pages.retain(|index, _| resident.contains(index));
+        if self.pages.len() != before {
+            debug!("縦読み表示の画像を破棄しました: 残り{}枚", self.pages.len());
+        }
+
+        let width = layout.width.round() as u32;
+        let max_dimension = device.limits().max_texture_dimension_2d;
+        for result in self.results.try_iter().collect::<Vec<_>>() {
+            if !resident.contains(&result.index) {
+                continue;
+            }
+            let state = match result.image {
+                Some(image) => PageState::Loaded(result.width, Self::upload(device, queue, overlay, &image, max_dimension)),
+                None => PageState::Failed,
+            };
+            // 古い幅で読み込んだ結果の場合は、今の幅で読み込み直す
+            if result.width != width {
+                self.requested = None;
+            }
+            self.pages.insert(result.index, state);
+        }
+
+        let request = (resident.clone(), width);
+        if self.requested.as_ref() != Some(&request) {
+            let jobs = resident.clone()
+                .filter(|index| match self.pages.get(index) {
+                    Some(PageState::Loaded(loaded_width, _)) => *loaded_width != width,
+                    Some(PageState::Failed) => false,
+                    None => true,
+                })
+                .map(|index| PageJob { index, path: self.paths[index].clone(), width })
+                .collect();
+            if self.requests.send(jobs).is_err() {
+                warn!("縦読み表示の読み込みスレッドが終了しています");
+            }
+            self.requested = Some(request);
+        }
+        self.layout = Some(layout);
+    }
+
+    /// 画像を縦に分割してテクスチャとしてGPUに転送する
+    fn upload(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        overlay: &OverlayRenderer,
+        image: &RgbaImage,
+        max_dimension: u32,
+    ) -> Vec<(wgpu::BindGroup, u32)> {
+        let (width, height) = image.dimensions();
+        let tile_height = TILE_HEIGHT.min(max_dimension);
+        if height <= tile_height {
+            return vec![(overlay.create_image_texture(device, queue, image), height)];
+        }
+        (0..height)
+            .step_by(tile_height as usize)
+            .map(|y| {
+                let rows = tile_height.min(height - y);
+                let tile = imageops::crop_imm(image, 0, y, width, rows).to_image();
+                (overlay.create_image_texture(device, queue, &tile), rows)
+            })
+            .collect()
+    }
+
+    /// 縦読み表示を描画する
+    ///
+    /// # Arguments
+    /// * `overlay` - オーバーレイレンダラー
+    /// * `queue` - WGPU キュー
+    /// * `font_size` - 読み込み失敗時の文字サイズ（論理ピクセル）
+    pub fn draw(&self, overlay: &mut OverlayRenderer, queue: &wgpu::Queue, font_size: f32) {
+        let Some(layout) = &self.layout else {
+            return;
+        };
+        for index in layout.range(self.scroll, self.scroll + layout.screen_height) {
+            let top = layout.tops[index] - self.scroll;
+            let height = layout.height(index);
+            match self.pages.get(&index) {
+                Some(PageState::Loaded(_, tiles)) => {
+                    // 分割したテクスチャの境目に隙間ができないよう、端の位置を丸めて揃える
+                    let total_rows: u32 = tiles.iter().map(|(_, rows)| rows).sum();
+                    let scale = height / total_rows.max(1) as f32;
+                    let mut row = 0;
+                    for (bind_group, rows) in tiles {
+                        let y0 = (top + row as f32 * scale).round();
+                        let y1 = (top + (row + rows) as f32 * scale).round();
+                        overlay.image(Rect::new(0.0, y0, layout.width, y1 - y0), bind_group);
+                        row += rows;
+                    }
+                }
+                Some(PageState::Failed) => {
+                    overlay.rect(Rect::new(0.0, top, layout.width, height), PLACEHOLDER_COLOR);
+                    let text = format!("{} (読み込み失敗)", self.names[index]);
+                    overlay.text(queue, 8.0, top.max(0.0) + 8.0, font_size, TEXT_COLOR, &text, Some(layout.width - 16.0));
+                }
+                None => overlay.rect(Rect::new(0.0, top, layout.width, height), PLACEHOLDER_COLOR),
+            }
+        }
+    }
+}